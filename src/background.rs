@@ -0,0 +1,135 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+const OSC11_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Background {
+    Light,
+    Dark,
+    Unknown,
+}
+
+/// Picks the terminal background from `$COLORFGBG`, falling back to an
+/// OSC 11 query when the variable is missing or inconclusive.
+pub fn detect_background() -> Background {
+    if let Ok(value) = env::var("COLORFGBG") {
+        let background = classify_background(&value);
+        if background != Background::Unknown {
+            return background;
+        }
+    }
+    query_osc11().unwrap_or(Background::Unknown)
+}
+
+/// Classifies a `COLORFGBG` value (`fg;bg` or `fg;default;bg`) by its final
+/// field: ANSI 7 and 9-15 are light backgrounds, 0-6 and 8 are dark.
+pub fn classify_background(colorfgbg: &str) -> Background {
+    match colorfgbg.rsplit(';').next().map(str::trim) {
+        Some(bg) => match bg.parse::<u8>() {
+            Ok(0..=6 | 8) => Background::Dark,
+            Ok(7 | 9..=15) => Background::Light,
+            _ => Background::Unknown,
+        },
+        None => Background::Unknown,
+    }
+}
+
+/// Parses the `rgb:RRRR/GGGG/BBBB` payload of an OSC 11 reply.
+fn classify_osc11_reply(reply: &str) -> Background {
+    let Some(rgb) = reply.split("rgb:").nth(1) else {
+        return Background::Unknown;
+    };
+    let channels: Vec<f32> = rgb
+        .split('/')
+        .take(3)
+        .filter_map(|part| {
+            let hex: String = part.chars().take_while(char::is_ascii_hexdigit).collect();
+            let max = 16f32.powi(hex.len() as i32) - 1.0;
+            u32::from_str_radix(&hex, 16).ok().map(|v| v as f32 / max)
+        })
+        .collect();
+    let [r, g, b] = channels[..] else {
+        return Background::Unknown;
+    };
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    }
+}
+
+fn query_osc11() -> Option<Background> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return None;
+    }
+    enable_raw_mode().ok()?;
+    let reply = read_osc11_reply();
+    let _ = disable_raw_mode();
+    match classify_osc11_reply(&reply?) {
+        Background::Unknown => None,
+        background => Some(background),
+    }
+}
+
+/// The reply arrives on stdin and is decoded by crossterm as a burst of key
+/// events; collect their characters until the terminator or the timeout.
+fn read_osc11_reply() -> Option<String> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]11;?\x1b\\").ok()?;
+    stdout.flush().ok()?;
+
+    let deadline = Instant::now() + OSC11_TIMEOUT;
+    let mut reply = String::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || !event::poll(remaining).ok()? {
+            break;
+        }
+        let Event::Key(key) = event::read().ok()? else {
+            continue;
+        };
+        match key.code {
+            KeyCode::Char('\\') if reply.contains("rgb:") => break,
+            KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char(ch) => reply.push(ch),
+            _ => {}
+        }
+    }
+    (!reply.is_empty()).then_some(reply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_colorfgbg_values() {
+        assert_eq!(classify_background("15;0"), Background::Dark);
+        assert_eq!(classify_background("0;15"), Background::Light);
+        assert_eq!(classify_background("0;7"), Background::Light);
+        assert_eq!(classify_background("7;8"), Background::Dark);
+        assert_eq!(classify_background("12;default;0"), Background::Dark);
+        assert_eq!(classify_background("0;default;15"), Background::Light);
+        assert_eq!(classify_background("default;default"), Background::Unknown);
+        assert_eq!(classify_background(""), Background::Unknown);
+    }
+
+    #[test]
+    fn classifies_osc11_replies_by_luminance() {
+        assert_eq!(
+            classify_osc11_reply("]11;rgb:ffff/ffff/ffff"),
+            Background::Light
+        );
+        assert_eq!(
+            classify_osc11_reply("]11;rgb:1e1e/1e1e/2e2e"),
+            Background::Dark
+        );
+        assert_eq!(classify_osc11_reply("garbage"), Background::Unknown);
+    }
+}
@@ -1,17 +1,40 @@
-use ratatui::style::Color;
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 
+use crate::markdown::LineKind;
 use crate::theme::Theme;
 
-pub fn apply_beeline(lines: &[Line<'static>], theme: &Theme) -> Vec<Line<'static>> {
+/// Tints prose lines with the theme's BeeLine gradient, leaving code and
+/// table lines (per `kinds`) untouched. `intensity` scales how strongly the
+/// gradient tints text: 0.0 leaves text untouched, 1.0 applies the full
+/// gradient.
+pub fn apply_beeline(
+    lines: &[Line<'static>],
+    kinds: &[LineKind],
+    theme: &Theme,
+    intensity: f32,
+) -> Vec<Line<'static>> {
     lines
         .iter()
         .enumerate()
-        .map(|(idx, line)| apply_beeline_line(line, idx, theme))
+        .map(
+            |(idx, line)| match kinds.get(idx).copied().unwrap_or_default() {
+                LineKind::Prose => apply_beeline_line(line, idx, theme, intensity),
+                LineKind::Code | LineKind::Table => line.clone(),
+            },
+        )
         .collect()
 }
 
-fn apply_beeline_line(line: &Line<'static>, index: usize, theme: &Theme) -> Line<'static> {
+fn apply_beeline_line(
+    line: &Line<'static>,
+    index: usize,
+    theme: &Theme,
+    intensity: f32,
+) -> Line<'static> {
+    if intensity <= 0.0 {
+        return line.clone();
+    }
     let total_len = line
         .spans
         .iter()
@@ -36,7 +59,8 @@ fn apply_beeline_line(line: &Line<'static>, index: usize, theme: &Theme) -> Line
             let t = if forward { t } else { 1.0 - t };
             let mut style = span.style;
             if style.fg.is_none() {
-                style.fg = Some(lerp_color(theme.beeline_start, theme.beeline_end, t));
+                let gradient = lerp_color(theme.beeline_start, theme.beeline_end, t);
+                style.fg = Some(lerp_color(theme.text, gradient, intensity));
             }
             spans.push(Span::styled(ch.to_string(), style));
             pos += 1;
@@ -50,6 +74,75 @@ fn apply_beeline_line(line: &Line<'static>, index: usize, theme: &Theme) -> Line
     }
 }
 
+/// Bolds the leading part of each word on prose lines, leaving code and
+/// table lines (per `kinds`) untouched.
+pub fn apply_bionic(lines: &[Line<'static>], kinds: &[LineKind]) -> Vec<Line<'static>> {
+    lines
+        .iter()
+        .enumerate()
+        .map(
+            |(idx, line)| match kinds.get(idx).copied().unwrap_or_default() {
+                LineKind::Prose => apply_bionic_line(line),
+                LineKind::Code | LineKind::Table => line.clone(),
+            },
+        )
+        .collect()
+}
+
+fn apply_bionic_line(line: &Line<'static>) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for span in &line.spans {
+        let chars: Vec<char> = span.content.chars().collect();
+        let mut idx = 0usize;
+        while idx < chars.len() {
+            if !chars[idx].is_alphanumeric() {
+                let start = idx;
+                while idx < chars.len() && !chars[idx].is_alphanumeric() {
+                    idx += 1;
+                }
+                push_run(&mut spans, &chars[start..idx], span.style);
+                continue;
+            }
+            let start = idx;
+            while idx < chars.len() && chars[idx].is_alphanumeric() {
+                idx += 1;
+            }
+            let split = start + bionic_prefix_len(idx - start);
+            push_run(
+                &mut spans,
+                &chars[start..split],
+                span.style.add_modifier(Modifier::BOLD),
+            );
+            push_run(
+                &mut spans,
+                &chars[split..idx],
+                span.style.add_modifier(Modifier::DIM),
+            );
+        }
+    }
+
+    Line {
+        spans,
+        style: line.style,
+        alignment: line.alignment,
+    }
+}
+
+fn bionic_prefix_len(word_len: usize) -> usize {
+    (word_len * 2).div_ceil(5).clamp(1, word_len.max(1))
+}
+
+fn push_run(spans: &mut Vec<Span<'static>>, chars: &[char], style: Style) {
+    if chars.is_empty() {
+        return;
+    }
+    let text: String = chars.iter().collect();
+    match spans.last_mut() {
+        Some(last) if last.style == style => last.content.to_mut().push_str(&text),
+        _ => spans.push(Span::styled(text, style)),
+    }
+}
+
 fn lerp_color(start: Color, end: Color, t: f32) -> Color {
     match (start, end) {
         (Color::Rgb(sr, sg, sb), Color::Rgb(er, eg, eb)) => {
@@ -68,3 +161,87 @@ fn lerp_u8(a: u8, b: u8, t: f32) -> u8 {
     let v = a + (b - a) * t.clamp(0.0, 1.0);
     v.round().clamp(0.0, 255.0) as u8
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beeline_skips_code_lines_but_tints_prose() {
+        let theme = Theme::pastel();
+        let code_color = Color::Rgb(10, 20, 30);
+        let lines = vec![
+            Line::raw("plain prose"),
+            Line::from(vec![
+                Span::styled("│ ", Style::new().fg(theme.code)),
+                Span::styled("let", Style::new().fg(code_color)),
+                Span::raw(" x"),
+            ]),
+        ];
+        let kinds = [LineKind::Prose, LineKind::Code];
+
+        let out = apply_beeline(&lines, &kinds, &theme, 1.0);
+
+        assert_eq!(out[0].spans[0].style.fg, Some(theme.beeline_start));
+        assert_eq!(out[1], lines[1]);
+        assert_eq!(out[1].spans[1].style.fg, Some(code_color));
+        assert_eq!(out[1].spans[2].style.fg, None);
+    }
+
+    #[test]
+    fn beeline_intensity_scales_tint_between_untouched_and_full() {
+        let theme = Theme::pastel();
+        let lines = vec![Line::raw("ab")];
+        let kinds = [LineKind::Prose];
+
+        let off = apply_beeline(&lines, &kinds, &theme, 0.0);
+        assert_eq!(off, lines);
+
+        let full = apply_beeline(&lines, &kinds, &theme, 1.0);
+        assert_eq!(full[0].spans[0].style.fg, Some(theme.beeline_start));
+        assert_eq!(full[0].spans[1].style.fg, Some(theme.beeline_end));
+
+        let half = apply_beeline(&lines, &kinds, &theme, 0.5);
+        assert_eq!(
+            half[0].spans[0].style.fg,
+            Some(lerp_color(theme.text, theme.beeline_start, 0.5))
+        );
+    }
+
+    #[test]
+    fn bionic_bolds_leading_characters_of_each_word() {
+        let line = Line::from(Span::styled(
+            "Reading fast",
+            Style::new().fg(Color::Rgb(1, 2, 3)),
+        ));
+
+        let out = apply_bionic(&[line], &[LineKind::Prose]);
+        let spans = &out[0].spans;
+
+        assert_eq!(spans[0].content, "Rea");
+        assert!(spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(spans[0].style.fg, Some(Color::Rgb(1, 2, 3)));
+        assert_eq!(spans[1].content, "ding");
+        assert!(spans[1].style.add_modifier.contains(Modifier::DIM));
+        assert_eq!(spans[3].content, "fa");
+        assert!(spans[3].style.add_modifier.contains(Modifier::BOLD));
+        let text: String = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(text, "Reading fast");
+    }
+
+    #[test]
+    fn bionic_skips_code_and_table_lines() {
+        let lines = vec![
+            Line::raw("prose"),
+            Line::raw("let x = 1;"),
+            Line::raw("| Name | Size |"),
+        ];
+        let kinds = [LineKind::Prose, LineKind::Code, LineKind::Table];
+
+        let out = apply_bionic(&lines, &kinds);
+
+        assert!(out[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(out[1], lines[1]);
+        assert_eq!(out[2], lines[2]);
+    }
+}
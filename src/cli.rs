@@ -1,9 +1,129 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use mdr::color::ColorDepth;
+
+use crate::background::Background;
+use crate::file_discovery::SortOrder;
+
+const USAGE: &str = "Usage: mdr [--beeline|--no-beeline] [--dump [--width N]] [--list] [--export-html <out.html>] [--export-text <out.txt>] [--theme light|dark] [--colors truecolor|256|16] [--code-theme <name>] [--full-render] [--wpm N] [--tab-width N] [--padding N] [--max-text-width N] [--start-line N] [--top-align-matches] [--browser <cmd>] [--lossy] [--resume] [--watch] [--follow] [--smooth-scroll] [--no-mouse] [--any-text] [--sticky-search] [--line-numbers] [--no-wrap-queue] [--no-emoji] [--no-reformat-json] [--no-highlight] [--sort name|mtime|size] <path-to-markdown> [more paths or directories]";
+
+const OPTIONS: &str = "Options:
+  --beeline              Start with the BeeLine gradient on (the default)
+  --no-beeline           Start with the BeeLine gradient off
+  --dump, --plain        Print the rendered document to stdout instead of opening the reader
+  --width N              Wrap width for --dump (defaults to the terminal width) and --export-text (80)
+  --list                 Print the paths that would be queued, in order, and exit
+  --export-html <file>   Write the current document as standalone HTML and exit
+  --export-text <file>   Write the current document as rendered plain text and exit
+  --theme light|dark     Force the palette instead of detecting the terminal background
+  --colors <depth>       Color depth: truecolor, 256, or 16 (detected from $COLORTERM/$TERM by default)
+  --code-theme <name>    Syntax highlighting theme for code blocks
+  --full-render          Style files larger than 2 MiB instead of showing them as plain text
+  --wpm N                Reading speed used for the time estimate (default 200)
+  --tab-width N          Columns per tab stop, 1-16 (default 4)
+  --padding N            Blank columns left and right of the text, 0-40 (default 0)
+  --max-text-width N     Wrap text at most N columns wide, centered on wide terminals
+  --start-line N         Open the first document scrolled to source line N
+  --top-align-matches    Put search matches and headings at the top of the view instead of centering them
+  --browser <cmd>        Command used to open links (defaults to $BROWSER)
+  --lossy                Replace invalid UTF-8 instead of refusing to open the file
+  --resume               Save the queue on exit; with no paths, reopen the saved queue
+  --watch                Reload the current document when it changes on disk
+  --follow               Like --watch, and stay at the bottom as the document grows
+  --smooth-scroll        Animate page up/down instead of jumping
+  --no-mouse             Leave the mouse to the terminal (keyboard-only)
+  --any-text             Also open .txt and extensionless files (shown as plain text)
+  --sticky-search        Keep the search query when switching documents
+  --line-numbers         Number every rendered line in a gutter (toggle with L)
+  --no-wrap-queue        Stop at the first/last document instead of wrapping around
+  --no-emoji             Show :shortcodes: as typed instead of as emoji
+  --no-reformat-json     Show minified JSON code blocks as written instead of reindented
+  --no-highlight         Draw code blocks in one color instead of syntax highlighting
+  --sort <order>         Queue order: name (default), mtime (newest first), size (largest first)
+  -h, --help             Show this help
+  --version              Show the version
+  --                     Treat every following argument as a path";
+
+/// What the command line asks for: run the reader, or print help/version.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliCommand {
+    Run(Box<CliArgs>),
+    Help,
+    Version,
+}
+
+pub fn help_text() -> String {
+    format!("{}\n\n{}", USAGE, OPTIONS)
+}
+
+pub fn version_text() -> String {
+    format!("mdr {}", env!("CARGO_PKG_VERSION"))
+}
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct CliArgs {
-    pub enable_beeline: bool,
+    /// `Some` only when `--beeline` or `--no-beeline` was given.
+    pub enable_beeline: Option<bool>,
+    pub dump: bool,
+    /// Print the queued paths instead of opening the reader.
+    pub list: bool,
+    pub full_render: bool,
+    pub top_align_matches: bool,
+    pub lossy: bool,
+    pub resume: bool,
+    pub watch: bool,
+    pub follow: bool,
+    pub smooth_scroll: bool,
+    /// False with `--no-mouse`.
+    pub mouse: bool,
+    pub any_text: bool,
+    pub sticky_search: bool,
+    pub line_numbers: bool,
+    /// False with `--no-wrap-queue`.
+    pub wrap_queue: bool,
+    /// False with `--no-emoji`.
+    pub emoji: bool,
+    /// False with `--no-reformat-json`.
+    pub reformat_json: bool,
+    /// False with `--no-highlight`.
+    pub highlight: bool,
+    pub width: Option<u16>,
+    pub words_per_minute: Option<usize>,
+    pub tab_width: Option<usize>,
+    pub padding: u16,
+    pub max_text_width: Option<u16>,
+    pub export_html: Option<PathBuf>,
+    pub export_text: Option<PathBuf>,
+    pub theme: Option<Background>,
+    /// `Some` only when `--colors` was given; skips detection.
+    pub colors: Option<ColorDepth>,
+    pub sort: SortOrder,
+    pub code_theme: Option<String>,
+    pub browser: Option<String>,
     pub inputs: Vec<PathBuf>,
+    /// `guide.md#install` given as an input: open that file at the heading.
+    pub start_heading: Option<(PathBuf, String)>,
+    /// 1-based source line to open the first document at.
+    pub start_line: Option<usize>,
+}
+
+/// Like [`parse_args`], but `--help`/`-h` and `--version` anywhere before a
+/// `--` win over everything else, including the missing-input error.
+pub fn parse_command<I, S>(args: I) -> Result<CliCommand, String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let args: Vec<S> = args.into_iter().collect();
+    for arg in args.iter().skip(1) {
+        match arg.as_ref() {
+            "--" => break,
+            "--help" | "-h" => return Ok(CliCommand::Help),
+            "--version" => return Ok(CliCommand::Version),
+            _ => {}
+        }
+    }
+    parse_args(args).map(|args| CliCommand::Run(Box::new(args)))
 }
 
 pub fn parse_args<I, S>(args: I) -> Result<CliArgs, String>
@@ -11,30 +131,267 @@ where
     I: IntoIterator<Item = S>,
     S: AsRef<str>,
 {
-    let mut enable_beeline = true;
+    let mut enable_beeline = None;
+    let mut dump = false;
+    let mut list = false;
+    let mut full_render = false;
+    let mut top_align_matches = false;
+    let mut lossy = false;
+    let mut resume = false;
+    let mut watch = false;
+    let mut follow = false;
+    let mut smooth_scroll = false;
+    let mut mouse = true;
+    let mut any_text = false;
+    let mut sticky_search = false;
+    let mut line_numbers = false;
+    let mut wrap_queue = true;
+    let mut emoji = true;
+    let mut reformat_json = true;
+    let mut highlight = true;
+    let mut width = None;
+    let mut words_per_minute = None;
+    let mut tab_width = None;
+    let mut padding = 0;
+    let mut max_text_width = None;
+    let mut export_html = None;
+    let mut export_text = None;
+    let mut theme = None;
+    let mut colors = None;
+    let mut sort = SortOrder::default();
+    let mut code_theme = None;
+    let mut browser = None;
     let mut inputs = Vec::new();
+    let mut start_heading = None;
+    let mut start_line = None;
 
-    for arg in args.into_iter().skip(1) {
+    let mut args = args.into_iter().skip(1);
+    while let Some(arg) = args.next() {
         let arg = arg.as_ref();
+        if arg == "--" {
+            inputs.extend(args.by_ref().map(|rest| PathBuf::from(rest.as_ref())));
+            break;
+        }
         if arg == "--no-beeline" {
-            enable_beeline = false;
+            enable_beeline = Some(false);
+        } else if arg == "--beeline" {
+            enable_beeline = Some(true);
+        } else if arg == "--dump" || arg == "--plain" {
+            dump = true;
+        } else if arg == "--list" {
+            list = true;
+        } else if arg == "--full-render" {
+            full_render = true;
+        } else if arg == "--top-align-matches" {
+            top_align_matches = true;
+        } else if arg == "--lossy" {
+            lossy = true;
+        } else if arg == "--resume" {
+            resume = true;
+        } else if arg == "--watch" {
+            watch = true;
+        } else if arg == "--follow" {
+            follow = true;
+        } else if arg == "--smooth-scroll" {
+            smooth_scroll = true;
+        } else if arg == "--no-mouse" {
+            mouse = false;
+        } else if arg == "--any-text" {
+            any_text = true;
+        } else if arg == "--sticky-search" {
+            sticky_search = true;
+        } else if arg == "--line-numbers" {
+            line_numbers = true;
+        } else if arg == "--no-wrap-queue" {
+            wrap_queue = false;
+        } else if arg == "--no-emoji" {
+            emoji = false;
+        } else if arg == "--no-reformat-json" {
+            reformat_json = false;
+        } else if arg == "--no-highlight" {
+            highlight = false;
+        } else if let Some(value) = option_value(arg, "--width", &mut args)? {
+            width = Some(parse_width(&value)?);
+        } else if let Some(value) = option_value(arg, "--wpm", &mut args)? {
+            words_per_minute = Some(parse_wpm(&value)?);
+        } else if let Some(value) = option_value(arg, "--tab-width", &mut args)? {
+            tab_width = Some(parse_tab_width(&value)?);
+        } else if let Some(value) = option_value(arg, "--padding", &mut args)? {
+            padding = parse_padding(&value)?;
+        } else if let Some(value) = option_value(arg, "--max-text-width", &mut args)? {
+            max_text_width = Some(parse_max_text_width(&value)?);
+        } else if let Some(value) = option_value(arg, "--start-line", &mut args)? {
+            start_line = Some(parse_start_line(&value)?);
+        } else if let Some(value) = option_value(arg, "--export-html", &mut args)? {
+            export_html = Some(PathBuf::from(value));
+        } else if let Some(value) = option_value(arg, "--export-text", &mut args)? {
+            export_text = Some(PathBuf::from(value));
+        } else if let Some(value) = option_value(arg, "--theme", &mut args)? {
+            theme = Some(parse_theme(&value)?);
+        } else if let Some(value) = option_value(arg, "--colors", &mut args)? {
+            colors = Some(parse_colors(&value)?);
+        } else if let Some(value) = option_value(arg, "--sort", &mut args)? {
+            sort = parse_sort(&value)?;
+        } else if let Some(value) = option_value(arg, "--code-theme", &mut args)? {
+            code_theme = Some(value);
+        } else if let Some(value) = option_value(arg, "--browser", &mut args)? {
+            browser = Some(value);
         } else {
-            inputs.push(PathBuf::from(arg));
+            let (path, fragment) = split_fragment(arg);
+            if let Some(fragment) = fragment
+                && start_heading.is_none()
+            {
+                start_heading = Some((path.clone(), fragment));
+            }
+            inputs.push(path);
         }
     }
 
-    if inputs.is_empty() {
-        return Err(
-            "Usage: mdr [--no-beeline] <path-to-markdown> [more paths or directories]".to_string(),
-        );
+    if inputs.is_empty() && !resume {
+        return Err(USAGE.to_string());
     }
 
     Ok(CliArgs {
         enable_beeline,
+        dump,
+        list,
+        full_render,
+        top_align_matches,
+        lossy,
+        resume,
+        watch,
+        follow,
+        smooth_scroll,
+        mouse,
+        any_text,
+        sticky_search,
+        line_numbers,
+        wrap_queue,
+        emoji,
+        reformat_json,
+        highlight,
+        width,
+        words_per_minute,
+        tab_width,
+        padding,
+        max_text_width,
+        export_html,
+        export_text,
+        theme,
+        colors,
+        sort,
+        code_theme,
+        browser,
         inputs,
+        start_heading,
+        start_line,
     })
 }
 
+fn option_value<I, S>(arg: &str, name: &str, rest: &mut I) -> Result<Option<String>, String>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    if arg == name {
+        return rest
+            .next()
+            .map(|value| Some(value.as_ref().to_string()))
+            .ok_or_else(|| format!("{} requires a value\n{}", name, USAGE));
+    }
+    Ok(arg
+        .strip_prefix(name)
+        .and_then(|tail| tail.strip_prefix('='))
+        .map(str::to_string))
+}
+
+fn parse_width(value: &str) -> Result<u16, String> {
+    match value.parse::<u16>() {
+        Ok(width) if width > 0 => Ok(width),
+        _ => Err(format!("Invalid --width value: {}", value)),
+    }
+}
+
+pub fn parse_wpm(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(wpm) if wpm > 0 => Ok(wpm),
+        _ => Err(format!("Invalid --wpm value: {}", value)),
+    }
+}
+
+pub fn parse_tab_width(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(width) if (1..=16).contains(&width) => Ok(width),
+        _ => Err(format!("Invalid --tab-width value: {}", value)),
+    }
+}
+
+/// Splits `guide.md#install` into the path and heading fragment. A name
+/// that exists as given, `#` included, is left whole.
+fn split_fragment(arg: &str) -> (PathBuf, Option<String>) {
+    match arg.rsplit_once('#') {
+        Some((path, fragment))
+            if !path.is_empty()
+                && !fragment.is_empty()
+                && !fragment.contains('/')
+                && !Path::new(arg).exists() =>
+        {
+            (PathBuf::from(path), Some(fragment.to_string()))
+        }
+        _ => (PathBuf::from(arg), None),
+    }
+}
+
+fn parse_padding(value: &str) -> Result<u16, String> {
+    match value.parse::<u16>() {
+        Ok(padding) if padding <= 40 => Ok(padding),
+        _ => Err(format!("Invalid --padding value: {}", value)),
+    }
+}
+
+fn parse_max_text_width(value: &str) -> Result<u16, String> {
+    match value.parse::<u16>() {
+        Ok(width) if width >= 20 => Ok(width),
+        _ => Err(format!(
+            "Invalid --max-text-width value: {} (expected 20 or more)",
+            value
+        )),
+    }
+}
+
+fn parse_start_line(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(line) if line > 0 => Ok(line),
+        _ => Err(format!("Invalid --start-line value: {}", value)),
+    }
+}
+
+pub fn parse_theme(value: &str) -> Result<Background, String> {
+    match value {
+        "light" => Ok(Background::Light),
+        "dark" => Ok(Background::Dark),
+        _ => Err(format!("Invalid --theme value: {}", value)),
+    }
+}
+
+pub fn parse_colors(value: &str) -> Result<ColorDepth, String> {
+    match value {
+        "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+        "256" => Ok(ColorDepth::Ansi256),
+        "16" => Ok(ColorDepth::Ansi16),
+        _ => Err(format!("Invalid --colors value: {}", value)),
+    }
+}
+
+pub fn parse_sort(value: &str) -> Result<SortOrder, String> {
+    match value {
+        "name" => Ok(SortOrder::Name),
+        "mtime" => Ok(SortOrder::Mtime),
+        "size" => Ok(SortOrder::Size),
+        _ => Err(format!("Invalid --sort value: {}", value)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,16 +399,252 @@ mod tests {
     #[test]
     fn parses_no_beeline_and_multiple_inputs() {
         let parsed = parse_args(["mdr", "--no-beeline", "a.md", "docs"]).unwrap();
-        assert!(!parsed.enable_beeline);
+        assert_eq!(parsed.enable_beeline, Some(false));
         assert_eq!(
             parsed.inputs,
             vec![PathBuf::from("a.md"), PathBuf::from("docs")]
         );
     }
 
+    #[test]
+    fn help_and_version_short_circuit_before_inputs() {
+        assert_eq!(parse_command(["mdr", "--help"]).unwrap(), CliCommand::Help);
+        assert_eq!(
+            parse_command(["mdr", "a.md", "-h"]).unwrap(),
+            CliCommand::Help
+        );
+        assert_eq!(
+            parse_command(["mdr", "--version"]).unwrap(),
+            CliCommand::Version
+        );
+        assert!(help_text().contains("--tab-width"));
+        assert!(version_text().starts_with("mdr "));
+
+        match parse_command(["mdr", "a.md"]).unwrap() {
+            CliCommand::Run(args) => assert_eq!(args.inputs, vec![PathBuf::from("a.md")]),
+            other => panic!("expected run, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn double_dash_ends_flag_parsing() {
+        let parsed = parse_args(["mdr", "--", "--weird-name.md"]).unwrap();
+        assert_eq!(parsed.inputs, vec![PathBuf::from("--weird-name.md")]);
+
+        let parsed = parse_args(["mdr", "a.md", "--", "--no-beeline"]).unwrap();
+        assert_eq!(parsed.enable_beeline, None);
+        assert_eq!(
+            parsed.inputs,
+            vec![PathBuf::from("a.md"), PathBuf::from("--no-beeline")]
+        );
+
+        match parse_command(["mdr", "--", "--help"]).unwrap() {
+            CliCommand::Run(args) => assert_eq!(args.inputs, vec![PathBuf::from("--help")]),
+            other => panic!("expected run, got {:?}", other),
+        }
+    }
+
     #[test]
     fn returns_usage_error_when_no_inputs() {
         let err = parse_args(["mdr"]).unwrap_err();
         assert!(err.contains("Usage: mdr"));
     }
+
+    #[test]
+    fn queue_wraps_unless_no_wrap_queue_is_given() {
+        assert!(parse_args(["mdr", "a.md"]).unwrap().wrap_queue);
+        assert!(
+            !parse_args(["mdr", "--no-wrap-queue", "a.md"])
+                .unwrap()
+                .wrap_queue
+        );
+    }
+
+    #[test]
+    fn emoji_shortcodes_are_on_unless_no_emoji_is_given() {
+        assert!(parse_args(["mdr", "a.md"]).unwrap().emoji);
+        assert!(!parse_args(["mdr", "--no-emoji", "a.md"]).unwrap().emoji);
+    }
+
+    #[test]
+    fn json_reformatting_is_on_unless_disabled() {
+        assert!(parse_args(["mdr", "a.md"]).unwrap().reformat_json);
+        assert!(
+            !parse_args(["mdr", "--no-reformat-json", "a.md"])
+                .unwrap()
+                .reformat_json
+        );
+    }
+
+    #[test]
+    fn highlighting_is_on_unless_no_highlight_is_given() {
+        assert!(parse_args(["mdr", "a.md"]).unwrap().highlight);
+        assert!(
+            !parse_args(["mdr", "--no-highlight", "a.md"])
+                .unwrap()
+                .highlight
+        );
+    }
+
+    #[test]
+    fn mouse_is_enabled_unless_no_mouse_is_given() {
+        assert!(parse_args(["mdr", "a.md"]).unwrap().mouse);
+        assert!(!parse_args(["mdr", "--no-mouse", "a.md"]).unwrap().mouse);
+    }
+
+    #[test]
+    fn parses_sort_order() {
+        assert_eq!(parse_args(["mdr", "a.md"]).unwrap().sort, SortOrder::Name);
+        let parsed = parse_args(["mdr", "--sort", "mtime", "docs"]).unwrap();
+        assert_eq!(parsed.sort, SortOrder::Mtime);
+        let parsed = parse_args(["mdr", "--sort=size", "docs"]).unwrap();
+        assert_eq!(parsed.sort, SortOrder::Size);
+        assert!(parse_args(["mdr", "--sort", "date", "docs"]).is_err());
+    }
+
+    #[test]
+    fn parses_theme_override() {
+        let parsed = parse_args(["mdr", "--theme=light", "a.md"]).unwrap();
+        assert_eq!(parsed.theme, Some(Background::Light));
+        let err = parse_args(["mdr", "--theme", "solarized", "a.md"]).unwrap_err();
+        assert!(err.contains("Invalid --theme value"));
+    }
+
+    #[test]
+    fn parses_color_depth_override() {
+        let parsed = parse_args(["mdr", "--colors=16", "a.md"]).unwrap();
+        assert_eq!(parsed.colors, Some(ColorDepth::Ansi16));
+        let parsed = parse_args(["mdr", "--colors", "truecolor", "a.md"]).unwrap();
+        assert_eq!(parsed.colors, Some(ColorDepth::TrueColor));
+        assert_eq!(parse_args(["mdr", "a.md"]).unwrap().colors, None);
+        let err = parse_args(["mdr", "--colors", "8", "a.md"]).unwrap_err();
+        assert!(err.contains("Invalid --colors value"));
+    }
+
+    #[test]
+    fn resume_allows_missing_inputs() {
+        let parsed = parse_args(["mdr", "--resume"]).unwrap();
+        assert!(parsed.resume);
+        assert!(parsed.inputs.is_empty());
+    }
+
+    #[test]
+    fn parses_list_flag() {
+        assert!(parse_args(["mdr", "--list", "docs"]).unwrap().list);
+        assert!(!parse_args(["mdr", "docs"]).unwrap().list);
+    }
+
+    #[test]
+    fn parses_line_numbers_flag() {
+        assert!(
+            parse_args(["mdr", "--line-numbers", "a.md"])
+                .unwrap()
+                .line_numbers
+        );
+        assert!(!parse_args(["mdr", "a.md"]).unwrap().line_numbers);
+    }
+
+    #[test]
+    fn parses_dump_with_width_override() {
+        let parsed = parse_args(["mdr", "--dump", "--width", "60", "a.md"]).unwrap();
+        assert!(parsed.dump);
+        assert_eq!(parsed.width, Some(60));
+
+        let err = parse_args(["mdr", "--dump", "--width", "zero", "a.md"]).unwrap_err();
+        assert!(err.contains("--width"));
+    }
+
+    #[test]
+    fn splits_heading_fragment_off_input_paths() {
+        let parsed = parse_args(["mdr", "a.md", "guide.md#installation"]).unwrap();
+        assert_eq!(
+            parsed.inputs,
+            vec![PathBuf::from("a.md"), PathBuf::from("guide.md")]
+        );
+        assert_eq!(
+            parsed.start_heading,
+            Some((PathBuf::from("guide.md"), "installation".to_string()))
+        );
+
+        let parsed = parse_args(["mdr", "docs/guide.md"]).unwrap();
+        assert_eq!(parsed.inputs, vec![PathBuf::from("docs/guide.md")]);
+        assert_eq!(parsed.start_heading, None);
+
+        assert_eq!(split_fragment("a.md#"), (PathBuf::from("a.md#"), None));
+        assert_eq!(split_fragment("#a"), (PathBuf::from("#a"), None));
+        assert_eq!(
+            split_fragment("odd#dir/a.md"),
+            (PathBuf::from("odd#dir/a.md"), None)
+        );
+    }
+
+    #[test]
+    fn keeps_existing_file_names_that_contain_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("c#.md");
+        std::fs::write(&path, "# c").unwrap();
+        let arg = path.to_str().unwrap();
+        assert_eq!(split_fragment(arg), (path.clone(), None));
+    }
+
+    #[test]
+    fn parses_padding_within_range() {
+        assert_eq!(parse_args(["mdr", "a.md"]).unwrap().padding, 0);
+        let parsed = parse_args(["mdr", "--padding", "6", "a.md"]).unwrap();
+        assert_eq!(parsed.padding, 6);
+
+        let err = parse_args(["mdr", "--padding", "41", "a.md"]).unwrap_err();
+        assert!(err.contains("--padding"));
+    }
+
+    #[test]
+    fn parses_max_text_width() {
+        assert_eq!(parse_args(["mdr", "a.md"]).unwrap().max_text_width, None);
+        let parsed = parse_args(["mdr", "--max-text-width", "80", "a.md"]).unwrap();
+        assert_eq!(parsed.max_text_width, Some(80));
+
+        let err = parse_args(["mdr", "--max-text-width", "5", "a.md"]).unwrap_err();
+        assert!(err.contains("--max-text-width"));
+    }
+
+    #[test]
+    fn parses_follow_flag() {
+        assert!(parse_args(["mdr", "--follow", "log.md"]).unwrap().follow);
+        assert!(!parse_args(["mdr", "log.md"]).unwrap().follow);
+    }
+
+    #[test]
+    fn parses_start_line() {
+        let parsed = parse_args(["mdr", "--start-line", "42", "a.md"]).unwrap();
+        assert_eq!(parsed.start_line, Some(42));
+        assert_eq!(parse_args(["mdr", "a.md"]).unwrap().start_line, None);
+        assert!(parse_args(["mdr", "--start-line", "0", "a.md"]).is_err());
+        assert!(parse_args(["mdr", "--start-line=x", "a.md"]).is_err());
+    }
+
+    #[test]
+    fn parses_export_html_output_path() {
+        let parsed = parse_args(["mdr", "--export-html", "out.html", "a.md"]).unwrap();
+        assert_eq!(parsed.export_html, Some(PathBuf::from("out.html")));
+        assert_eq!(parsed.inputs, vec![PathBuf::from("a.md")]);
+
+        let err = parse_args(["mdr", "a.md", "--export-html"]).unwrap_err();
+        assert!(err.contains("--export-html"));
+    }
+
+    #[test]
+    fn parses_export_text_output_path() {
+        let parsed = parse_args(["mdr", "--export-text=out.txt", "a.md"]).unwrap();
+        assert_eq!(parsed.export_text, Some(PathBuf::from("out.txt")));
+        assert_eq!(parse_args(["mdr", "a.md"]).unwrap().export_text, None);
+    }
+
+    #[test]
+    fn parses_code_theme_in_both_forms() {
+        let parsed = parse_args(["mdr", "--code-theme", "InspiredGitHub", "a.md"]).unwrap();
+        assert_eq!(parsed.code_theme.as_deref(), Some("InspiredGitHub"));
+
+        let parsed = parse_args(["mdr", "--code-theme=Solarized (light)", "a.md"]).unwrap();
+        assert_eq!(parsed.code_theme.as_deref(), Some("Solarized (light)"));
+    }
 }
@@ -0,0 +1,199 @@
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+
+/// How many colors the terminal can display.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorDepth::TrueColor => "24-bit truecolor",
+            ColorDepth::Ansi256 => "256 colors",
+            ColorDepth::Ansi16 => "16 colors",
+        }
+    }
+
+    /// Infers the depth from `$COLORTERM` and `$TERM`. Only terminals known
+    /// to be limited (the Linux console, VT terminals, `dumb`) get 16 colors;
+    /// anything unrecognized, including a missing `$TERM` as on Windows,
+    /// gets 256.
+    pub fn detect(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if colorterm.is_some_and(|value| value.contains("truecolor") || value.contains("24bit")) {
+            ColorDepth::TrueColor
+        } else if term.is_some_and(is_limited_term) {
+            ColorDepth::Ansi16
+        } else {
+            ColorDepth::Ansi256
+        }
+    }
+}
+
+/// `$TERM` values for terminals that only show the 16 ANSI colors.
+fn is_limited_term(term: &str) -> bool {
+    matches!(term, "dumb" | "linux" | "ansi" | "cons25")
+        || term.starts_with("vt")
+        || term.ends_with("-16color")
+        || term.ends_with("-8color")
+}
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Nearest xterm 256-color index: either a 6x6x6 cube entry or one of the
+/// 24 grayscale steps, whichever is closer.
+pub fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    let cube_index = |v: u8| match v {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (v - 35) / 40,
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = (
+        CUBE_LEVELS[ri as usize],
+        CUBE_LEVELS[gi as usize],
+        CUBE_LEVELS[bi as usize],
+    );
+
+    let average = (r as u16 + g as u16 + b as u16) / 3;
+    let gray_index = if average > 238 {
+        23
+    } else {
+        average.saturating_sub(3) / 10
+    } as u8;
+    let gray = 8 + 10 * gray_index;
+
+    if distance((r, g, b), (gray, gray, gray)) < distance((r, g, b), cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Nearest of the 16 standard ANSI colors, using xterm's default palette.
+pub fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, rgb)| distance((r, g, b), *rgb))
+        .map(|(color, _)| *color)
+        .unwrap_or(Color::Reset)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Maps RGB colors down to what `depth` supports; other colors pass through.
+pub fn downgrade_color(color: Color, depth: ColorDepth) -> Color {
+    match (color, depth) {
+        (Color::Rgb(r, g, b), ColorDepth::Ansi256) => Color::Indexed(rgb_to_ansi256(r, g, b)),
+        (Color::Rgb(r, g, b), ColorDepth::Ansi16) => rgb_to_ansi16(r, g, b),
+        _ => color,
+    }
+}
+
+pub fn downgrade_style(style: Style, depth: ColorDepth) -> Style {
+    Style {
+        fg: style.fg.map(|color| downgrade_color(color, depth)),
+        bg: style.bg.map(|color| downgrade_color(color, depth)),
+        ..style
+    }
+}
+
+pub fn downgrade_line(line: &Line<'static>, depth: ColorDepth) -> Line<'static> {
+    let spans: Vec<Span<'static>> = line
+        .spans
+        .iter()
+        .map(|span| Span::styled(span.content.clone(), downgrade_style(span.style, depth)))
+        .collect();
+    Line::from(spans).style(downgrade_style(line.style, depth))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_known_rgb_values_to_ansi256() {
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi256(0, 0, 255), 21);
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+        assert_eq!(rgb_to_ansi256(95, 135, 175), 67);
+    }
+
+    #[test]
+    fn converts_rgb_to_nearest_ansi16() {
+        assert_eq!(rgb_to_ansi16(250, 10, 10), Color::LightRed);
+        assert_eq!(rgb_to_ansi16(20, 20, 20), Color::Black);
+        assert_eq!(rgb_to_ansi16(120, 120, 130), Color::DarkGray);
+    }
+
+    #[test]
+    fn detects_depth_from_environment() {
+        assert_eq!(
+            ColorDepth::detect(Some("truecolor"), Some("xterm-256color")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::detect(None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(ColorDepth::detect(None, Some("xterm")), ColorDepth::Ansi256);
+        assert_eq!(
+            ColorDepth::detect(None, Some("xterm-kitty")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::detect(None, Some("screen")),
+            ColorDepth::Ansi256
+        );
+        for term in ["linux", "vt100", "dumb"] {
+            assert_eq!(ColorDepth::detect(None, Some(term)), ColorDepth::Ansi16);
+        }
+    }
+
+    #[test]
+    fn missing_term_falls_back_to_256_colors() {
+        assert_eq!(ColorDepth::detect(None, None), ColorDepth::Ansi256);
+        assert_eq!(
+            ColorDepth::detect(Some("24bit"), None),
+            ColorDepth::TrueColor
+        );
+    }
+
+    #[test]
+    fn truecolor_leaves_rgb_untouched() {
+        let color = Color::Rgb(1, 2, 3);
+        assert_eq!(downgrade_color(color, ColorDepth::TrueColor), color);
+        assert_eq!(
+            downgrade_color(Color::Blue, ColorDepth::Ansi256),
+            Color::Blue
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use crate::background::Background;
+
+/// A command typed at the `:` prompt.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Jump to a 1-based source line (`:goto 42`, or just `:42`).
+    Goto(usize),
+    /// Switch the color palette (`:theme light|dark`).
+    Theme(Background),
+    /// Re-read the current document from disk.
+    Reload,
+    /// Show the table of contents.
+    Toc,
+    /// Write the current document as HTML (`:export html out.html`).
+    ExportHtml(PathBuf),
+    /// Show version and environment details for bug reports.
+    About,
+}
+
+pub fn parse_command(input: &str) -> Result<Command, String> {
+    let mut words = input.split_whitespace();
+    let Some(name) = words.next() else {
+        return Err("Empty command".to_string());
+    };
+    let args: Vec<&str> = words.collect();
+    if name.chars().all(|ch| ch.is_ascii_digit()) && args.is_empty() {
+        return parse_line_number(name).map(Command::Goto);
+    }
+    match (name, args.as_slice()) {
+        ("goto", [line]) => parse_line_number(line).map(Command::Goto),
+        ("goto", _) => Err("Usage: goto <line>".to_string()),
+        ("theme", ["light"]) => Ok(Command::Theme(Background::Light)),
+        ("theme", ["dark"]) => Ok(Command::Theme(Background::Dark)),
+        ("theme", _) => Err("Usage: theme light|dark".to_string()),
+        ("reload", []) => Ok(Command::Reload),
+        ("toc", []) => Ok(Command::Toc),
+        ("about", []) => Ok(Command::About),
+        ("export", ["html", path]) => Ok(Command::ExportHtml(PathBuf::from(path))),
+        ("export", _) => Err("Usage: export html <file>".to_string()),
+        ("reload" | "toc" | "about", _) => Err(format!("{} takes no arguments", name)),
+        _ => Err(format!("Unknown command: {}", name)),
+    }
+}
+
+fn parse_line_number(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(line) if line > 0 => Ok(line),
+        _ => Err(format!("Invalid line number: {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_command_form() {
+        assert_eq!(parse_command("goto 42"), Ok(Command::Goto(42)));
+        assert_eq!(parse_command("42"), Ok(Command::Goto(42)));
+        assert_eq!(
+            parse_command("theme dark"),
+            Ok(Command::Theme(Background::Dark))
+        );
+        assert_eq!(
+            parse_command(" theme  light "),
+            Ok(Command::Theme(Background::Light))
+        );
+        assert_eq!(parse_command("reload"), Ok(Command::Reload));
+        assert_eq!(parse_command("toc"), Ok(Command::Toc));
+        assert_eq!(parse_command("about"), Ok(Command::About));
+        assert_eq!(
+            parse_command("export html out.html"),
+            Ok(Command::ExportHtml(PathBuf::from("out.html")))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_commands_and_bad_arguments() {
+        assert_eq!(
+            parse_command("frobnicate"),
+            Err("Unknown command: frobnicate".to_string())
+        );
+        assert!(parse_command("").is_err());
+        assert!(parse_command("goto").is_err());
+        assert!(parse_command("goto zero").is_err());
+        assert!(parse_command("0").is_err());
+        assert!(parse_command("theme blue").is_err());
+        assert!(parse_command("export pdf out.pdf").is_err());
+        assert!(parse_command("reload now").is_err());
+    }
+}
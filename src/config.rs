@@ -0,0 +1,387 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::background::Background;
+use mdr::color::ColorDepth;
+
+use crate::cli::{CliArgs, parse_colors, parse_tab_width, parse_theme, parse_wpm};
+use crate::keymap::KeyMap;
+use crate::link_scheme::LinkSchemes;
+use crate::markdown::DEFAULT_TAB_WIDTH;
+use crate::session::SavedView;
+use crate::ui::DEFAULT_WORDS_PER_MINUTE;
+
+/// Reader defaults: built-in values, overridden by `config.toml`, overridden
+/// in turn by command-line flags.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub beeline: bool,
+    pub theme: Option<Background>,
+    /// Color depth to use instead of detecting it from the environment.
+    pub colors: Option<ColorDepth>,
+    pub code_theme: Option<String>,
+    pub tab_width: usize,
+    pub words_per_minute: usize,
+    /// Restore the last session's BeeLine, plain-mode, and theme choices.
+    pub remember_view: bool,
+    pub link_schemes: LinkSchemes,
+    pub keymap: KeyMap,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            beeline: true,
+            theme: None,
+            colors: None,
+            code_theme: None,
+            tab_width: DEFAULT_TAB_WIDTH,
+            words_per_minute: DEFAULT_WORDS_PER_MINUTE,
+            remember_view: false,
+            link_schemes: LinkSchemes::default(),
+            keymap: KeyMap::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads `config.toml` from the user config directory. A missing file
+    /// yields the built-in defaults; an invalid one is reported and ignored.
+    pub fn load() -> Config {
+        let Some(path) = config_file() else {
+            return Config::default();
+        };
+        let Ok(text) = fs::read_to_string(&path) else {
+            return Config::default();
+        };
+        Config::parse(&text).unwrap_or_else(|err| {
+            eprintln!("Ignoring {}: {}", path.display(), err);
+            Config::default()
+        })
+    }
+
+    /// Parses the supported subset of TOML: top-level `key = value` pairs
+    /// with strings, integers, and booleans, `#` comments, a
+    /// `[link_schemes]` table mapping URL schemes to open commands, and a
+    /// `[keys]` table rebinding reader actions.
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut table = None;
+        for (idx, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header.split('#').next().unwrap_or_default().trim();
+                match header.strip_suffix(']').map(str::trim) {
+                    Some(name @ ("link_schemes" | "keys")) => table = Some(name),
+                    Some(other) => {
+                        return Err(format!("line {}: unknown table `{}`", idx + 1, other));
+                    }
+                    None => return Err(format!("line {}: expected `[table]`", idx + 1)),
+                }
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", idx + 1))?;
+            let value =
+                parse_value(value.trim()).map_err(|err| format!("line {}: {}", idx + 1, err))?;
+            let value = value.as_str();
+            let name = key.trim().trim_matches('"');
+            let result = match (table, key.trim()) {
+                (Some("link_schemes"), _) => config.link_schemes.insert(name, value),
+                (Some(_keys), _) => config.keymap.bind(name, value),
+                (None, "beeline") => parse_bool(value).map(|beeline| config.beeline = beeline),
+                (None, "theme") => parse_theme(value).map(|theme| config.theme = Some(theme)),
+                (None, "colors") => parse_colors(value).map(|depth| config.colors = Some(depth)),
+                (None, "code_theme") => {
+                    config.code_theme = Some(value.to_string());
+                    Ok(())
+                }
+                (None, "tab_width") => parse_tab_width(value).map(|width| config.tab_width = width),
+                (None, "wpm") => parse_wpm(value).map(|wpm| config.words_per_minute = wpm),
+                (None, "remember_view") => {
+                    parse_bool(value).map(|remember| config.remember_view = remember)
+                }
+                (None, other) => Err(format!("unknown key `{}`", other)),
+            };
+            result.map_err(|err| format!("line {}: {}", idx + 1, err))?;
+        }
+        Ok(config)
+    }
+
+    /// Applies the view saved by the last run on top of the file values.
+    pub fn with_view(mut self, view: &SavedView) -> Config {
+        self.beeline = view.beeline;
+        if view.theme.is_some() {
+            self.theme = view.theme;
+        }
+        self
+    }
+
+    /// Applies command-line flags on top of the file and built-in defaults.
+    pub fn with_cli(mut self, args: &CliArgs) -> Config {
+        if let Some(beeline) = args.enable_beeline {
+            self.beeline = beeline;
+        }
+        if args.theme.is_some() {
+            self.theme = args.theme;
+        }
+        if args.colors.is_some() {
+            self.colors = args.colors;
+        }
+        if args.code_theme.is_some() {
+            self.code_theme = args.code_theme.clone();
+        }
+        if let Some(tab_width) = args.tab_width {
+            self.tab_width = tab_width;
+        }
+        if let Some(wpm) = args.words_per_minute {
+            self.words_per_minute = wpm;
+        }
+        self
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("Invalid boolean value: {}", value)),
+    }
+}
+
+/// Strips any trailing `# comment` from a value and unquotes a basic
+/// string, resolving its `\"`, `\\`, `\t`, and `\n` escapes.
+fn parse_value(value: &str) -> Result<String, String> {
+    let Some(quoted) = value.strip_prefix('"') else {
+        return Ok(value
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string());
+    };
+    let mut unquoted = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '"' => {
+                let rest = quoted[idx + 1..].trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    return Err(format!("unexpected text after string: {}", rest));
+                }
+                return Ok(unquoted);
+            }
+            '\\' => match chars.next().map(|(_, escaped)| escaped) {
+                Some('"') => unquoted.push('"'),
+                Some('\\') => unquoted.push('\\'),
+                Some('t') => unquoted.push('\t'),
+                Some('n') => unquoted.push('\n'),
+                Some(other) => return Err(format!("invalid escape: \\{}", other)),
+                None => break,
+            },
+            _ => unquoted.push(ch),
+        }
+    }
+    Err(format!("unterminated string: {}", value))
+}
+
+fn config_file() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("mdr").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::parse_args;
+
+    #[test]
+    fn file_values_override_built_in_defaults() {
+        let config = Config::parse(
+            "# reader defaults\nbeeline = false\ntheme = \"light\"\ncolors = \"16\"\ncode_theme = \"InspiredGitHub\" # light code\ntab_width = 2\nwpm = 300\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                beeline: false,
+                theme: Some(Background::Light),
+                colors: Some(ColorDepth::Ansi16),
+                code_theme: Some("InspiredGitHub".to_string()),
+                tab_width: 2,
+                words_per_minute: 300,
+                remember_view: false,
+                link_schemes: LinkSchemes::default(),
+                keymap: KeyMap::default(),
+            }
+        );
+    }
+
+    #[test]
+    fn link_schemes_table_registers_handlers() {
+        let config = Config::parse(
+            "wpm = 300
+
+[link_schemes]
+obsidian = \"obsidian-open %s\"
+\"zotero\" = \"zotero-cli\" # select item
+",
+        )
+        .unwrap();
+        assert_eq!(config.words_per_minute, 300);
+        assert_eq!(
+            config
+                .link_schemes
+                .command_for("obsidian://open?vault=notes"),
+            Some("obsidian-open %s")
+        );
+        assert_eq!(
+            config.link_schemes.command_for("zotero://select"),
+            Some("zotero-cli")
+        );
+        assert_eq!(config.link_schemes.command_for("https://example.com"), None);
+
+        assert!(
+            Config::parse("[colors]\nlink = 1")
+                .unwrap_err()
+                .contains("unknown table")
+        );
+        assert!(
+            Config::parse("[link_schemes]\nbad scheme = \"x\"")
+                .unwrap_err()
+                .contains("line 2")
+        );
+    }
+
+    #[test]
+    fn quoted_values_resolve_escapes() {
+        let config = Config::parse(
+            "code_theme = \"Solarized \\\\ \\\"dark\\\"\" # note
+
+[link_schemes]
+browser = \"open -a \\\"Google Chrome\\\" %s\"
+",
+        )
+        .unwrap();
+        assert_eq!(config.code_theme.as_deref(), Some("Solarized \\ \"dark\""));
+        assert_eq!(
+            config.link_schemes.command_for("browser://page"),
+            Some("open -a \"Google Chrome\" %s")
+        );
+    }
+
+    #[test]
+    fn malformed_strings_are_reported() {
+        let err = Config::parse("wpm = 300\ncode_theme = \"base16\n").unwrap_err();
+        assert_eq!(err, "line 2: unterminated string: \"base16");
+        let err = Config::parse("code_theme = \"open -a \"Google Chrome\"\n").unwrap_err();
+        assert_eq!(err, "line 1: unexpected text after string: Google Chrome\"");
+        assert!(
+            Config::parse("code_theme = \"C:\\dir\"")
+                .unwrap_err()
+                .contains("invalid escape")
+        );
+    }
+
+    #[test]
+    fn keys_table_rebinds_actions() {
+        use crate::keymap::Action;
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        let config = Config::parse(
+            "[keys]
+next_document = \"n\"\nquit = \"ctrl-q\"\n",
+        )
+        .unwrap();
+        let keymap = &config.keymap;
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('n'), KeyModifiers::NONE),
+            Some(Action::NextDocument)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('q'), KeyModifiers::CONTROL),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('['), KeyModifiers::NONE),
+            Some(Action::PreviousDocument)
+        );
+        assert!(
+            Config::parse(
+                "[keys]
+fly = \"f\""
+            )
+            .unwrap_err()
+            .contains("line 2")
+        );
+    }
+
+    #[test]
+    fn cli_flags_beat_file_values_which_beat_defaults() {
+        let file =
+            Config::parse("beeline = false\ntab_width = 2\nwpm = 300\ncolors = \"256\"\n").unwrap();
+        let args = parse_args([
+            "mdr",
+            "--beeline",
+            "--tab-width",
+            "8",
+            "--colors",
+            "16",
+            "a.md",
+        ])
+        .unwrap();
+        let config = file.with_cli(&args);
+
+        assert!(config.beeline);
+        assert_eq!(config.tab_width, 8);
+        assert_eq!(config.colors, Some(ColorDepth::Ansi16));
+        assert_eq!(config.words_per_minute, 300);
+        assert_eq!(config.theme, None);
+        assert_eq!(config.code_theme, None);
+    }
+
+    #[test]
+    fn cli_flags_beat_the_saved_view_which_beats_file_values() {
+        let file =
+            Config::parse("remember_view = true\nbeeline = true\ntheme = \"dark\"\n").unwrap();
+        assert!(file.remember_view);
+        let view = SavedView {
+            beeline: false,
+            plain: true,
+            theme: Some(Background::Light),
+        };
+
+        let restored = file.clone().with_view(&view);
+        assert!(!restored.beeline);
+        assert_eq!(restored.theme, Some(Background::Light));
+
+        let args = parse_args(["mdr", "--beeline", "--theme", "dark", "a.md"]).unwrap();
+        let config = restored.with_cli(&args);
+        assert!(config.beeline);
+        assert_eq!(config.theme, Some(Background::Dark));
+
+        let undecided = SavedView {
+            theme: None,
+            ..view
+        };
+        assert_eq!(file.with_view(&undecided).theme, Some(Background::Dark));
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_bad_values() {
+        assert!(Config::parse("colour = 1").unwrap_err().contains("line 1"));
+        assert!(
+            Config::parse("\ntab_width = 99")
+                .unwrap_err()
+                .contains("line 2")
+        );
+    }
+}
@@ -1,21 +1,75 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
 use std::path::{Path, PathBuf};
 
+use mdr::markdown::normalize_line_endings;
+
+/// Reads a document as UTF-8 with `\n` line endings. With `lossy`, invalid
+/// byte sequences are replaced instead of failing the read.
+pub fn read_document(path: &Path, lossy: bool) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(err) if lossy => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+        Err(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "file is not valid UTF-8 (rerun with --lossy to open it anyway)",
+            ));
+        }
+    };
+    Ok(match normalize_line_endings(&content) {
+        Cow::Borrowed(_) => content,
+        Cow::Owned(normalized) => normalized,
+    })
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct QueuedDocument {
     pub path: PathBuf,
+    /// `path` with symlinks and relative components resolved; used to spot
+    /// the same file opened under different names.
+    pub canonical_path: PathBuf,
     pub content: String,
+    /// Hash of `content`, so `--watch` can ignore rewrites that change nothing.
+    pub content_hash: u64,
+    /// Listed first in the go dialog.
+    pub pinned: bool,
 }
 
 impl QueuedDocument {
     pub fn new(path: PathBuf, content: String) -> Self {
-        Self { path, content }
+        Self {
+            canonical_path: canonical_path(&path),
+            content_hash: content_hash(&content),
+            path,
+            content,
+            pinned: false,
+        }
     }
 }
 
+pub fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Best-effort canonical form of `path`; falls back to the path as given
+/// when it cannot be resolved (e.g. it no longer exists).
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
 #[derive(Clone, Debug)]
 pub struct DocumentQueue {
     docs: Vec<QueuedDocument>,
     current: usize,
+    /// Index focused before `current`, for [`focus_alternate`](Self::focus_alternate).
+    previous: Option<usize>,
 }
 
 impl DocumentQueue {
@@ -23,7 +77,31 @@ impl DocumentQueue {
         if docs.is_empty() {
             return Err("Document queue cannot be empty".to_string());
         }
-        Ok(Self { docs, current: 0 })
+        Ok(Self {
+            docs,
+            current: 0,
+            previous: None,
+        })
+    }
+
+    /// Focuses `idx`, remembering the old focus as the alternate document.
+    fn set_current(&mut self, idx: usize) {
+        if idx != self.current {
+            self.previous = Some(self.current);
+            self.current = idx;
+        }
+    }
+
+    /// Swaps focus with the previously focused document, like Vim's
+    /// alternate buffer. Returns false when there is none yet.
+    pub fn focus_alternate(&mut self) -> bool {
+        match self.previous {
+            Some(idx) if idx < self.docs.len() => {
+                self.set_current(idx);
+                true
+            }
+            _ => false,
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -44,37 +122,98 @@ impl DocumentQueue {
 
     pub fn next(&mut self) {
         if self.docs.len() > 1 {
-            self.current = (self.current + 1) % self.docs.len();
+            self.set_current((self.current + 1) % self.docs.len());
         }
     }
 
     pub fn prev(&mut self) {
         if self.docs.len() > 1 {
-            self.current = if self.current == 0 {
+            self.set_current(if self.current == 0 {
                 self.docs.len() - 1
             } else {
                 self.current - 1
-            };
+            });
+        }
+    }
+
+    /// Like [`next`](Self::next), but stays put on the last document.
+    /// Returns whether the focus moved.
+    pub fn next_clamped(&mut self) -> bool {
+        if self.current + 1 < self.docs.len() {
+            self.set_current(self.current + 1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Like [`prev`](Self::prev), but stays put on the first document.
+    /// Returns whether the focus moved.
+    pub fn prev_clamped(&mut self) -> bool {
+        if self.current > 0 {
+            self.set_current(self.current - 1);
+            true
+        } else {
+            false
         }
     }
 
     pub fn push_and_focus(&mut self, doc: QueuedDocument) {
         self.docs.push(doc);
-        self.current = self.docs.len() - 1;
+        self.set_current(self.docs.len() - 1);
     }
 
+    /// Focuses the queued document that refers to the same file as `path`.
     pub fn focus_existing(&mut self, path: &Path) -> bool {
-        if let Some(idx) = self.docs.iter().position(|doc| doc.path == path) {
-            self.current = idx;
+        let canonical = canonical_path(path);
+        if let Some(idx) = self
+            .docs
+            .iter()
+            .position(|doc| doc.canonical_path == canonical)
+        {
+            self.set_current(idx);
             true
         } else {
             false
         }
     }
 
+    /// Replaces the focused document's content, e.g. after it changed on disk.
+    /// Returns false, leaving the document alone, when the content is the same.
+    pub fn replace_current_content(&mut self, content: String) -> bool {
+        let hash = content_hash(&content);
+        let doc = &mut self.docs[self.current];
+        if doc.content_hash == hash && doc.content == content {
+            return false;
+        }
+        doc.content = content;
+        doc.content_hash = hash;
+        true
+    }
+
+    /// Pins or unpins the current document, returning the new state.
+    pub fn toggle_pin(&mut self) -> bool {
+        let doc = &mut self.docs[self.current];
+        doc.pinned = !doc.pinned;
+        doc.pinned
+    }
+
+    /// Moves the document at `from` to position `to`, keeping the same
+    /// document focused. Returns false when either index is out of range.
+    pub fn move_item(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.docs.len() || to >= self.docs.len() {
+            return false;
+        }
+        let doc = self.docs.remove(from);
+        self.docs.insert(to, doc);
+        self.current = moved_index(self.current, from, to);
+        self.previous = self.previous.map(|idx| moved_index(idx, from, to));
+        true
+    }
+
     pub fn focus_index(&mut self, idx: usize) -> bool {
         if idx < self.docs.len() {
-            self.current = idx;
+            self.set_current(idx);
             true
         } else {
             false
@@ -82,10 +221,45 @@ impl DocumentQueue {
     }
 }
 
+/// Where the document at `idx` ends up after moving `from` to `to`.
+fn moved_index(idx: usize, from: usize, to: usize) -> usize {
+    if idx == from {
+        to
+    } else if from < idx && idx <= to {
+        idx - 1
+    } else if to <= idx && idx < from {
+        idx + 1
+    } else {
+        idx
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn read_document_rejects_invalid_utf8_unless_lossy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("latin1.md");
+        fs::write(&path, b"caf\xe9").unwrap();
+
+        let err = read_document(&path, false).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(read_document(&path, true).unwrap(), "caf\u{fffd}");
+    }
+
+    #[test]
+    fn read_document_normalizes_line_endings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("windows.md");
+        fs::write(&path, "# Title\r\n\r\nbody\rmore\n").unwrap();
+        assert_eq!(
+            read_document(&path, false).unwrap(),
+            "# Title\n\nbody\nmore\n"
+        );
+    }
+
     #[test]
     fn next_and_prev_wrap_across_queue() {
         let docs = vec![
@@ -100,4 +274,132 @@ mod tests {
         q.prev();
         assert_eq!(q.current().path, PathBuf::from("b.md"));
     }
+
+    #[test]
+    fn clamped_navigation_stops_at_both_ends() {
+        let docs = vec![
+            QueuedDocument::new("a.md".into(), "a".into()),
+            QueuedDocument::new("b.md".into(), "b".into()),
+        ];
+        let mut q = DocumentQueue::new(docs).unwrap();
+        assert!(!q.prev_clamped());
+        assert_eq!(q.current_index(), 0);
+        assert!(q.next_clamped());
+        assert_eq!(q.current_index(), 1);
+        assert!(!q.next_clamped());
+        assert_eq!(q.current_index(), 1);
+        assert!(q.prev_clamped());
+        assert_eq!(q.current_index(), 0);
+    }
+
+    #[test]
+    fn focus_existing_matches_the_same_file_under_another_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let absolute = dir.path().join("a.md");
+        fs::write(&absolute, "# a").unwrap();
+        fs::write(dir.path().join("b.md"), "# b").unwrap();
+        let docs = vec![
+            QueuedDocument::new(dir.path().join(".").join("a.md"), "# a".into()),
+            QueuedDocument::new(dir.path().join("b.md"), "# b".into()),
+        ];
+        let mut q = DocumentQueue::new(docs).unwrap();
+        q.focus_index(1);
+
+        assert!(q.focus_existing(&absolute));
+        assert_eq!(q.current_index(), 0);
+        assert_eq!(q.len(), 2);
+        assert!(!q.focus_existing(&dir.path().join("missing.md")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn focus_existing_follows_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("a.md");
+        let link = dir.path().join("link.md");
+        fs::write(&target, "# a").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let mut q = DocumentQueue::new(vec![QueuedDocument::new(target, "# a".into())]).unwrap();
+
+        assert!(q.focus_existing(&link));
+        assert_eq!(q.len(), 1);
+    }
+
+    #[test]
+    fn content_hash_changes_only_with_the_content() {
+        let doc = QueuedDocument::new("a.md".into(), "# a\n".into());
+        assert_eq!(doc.content_hash, content_hash("# a\n"));
+        assert_ne!(doc.content_hash, content_hash("# a!\n"));
+
+        let mut q = DocumentQueue::new(vec![doc]).unwrap();
+        assert!(!q.replace_current_content("# a\n".into()));
+        assert!(q.replace_current_content("# b\n".into()));
+        assert_eq!(q.current().content_hash, content_hash("# b\n"));
+    }
+
+    #[test]
+    fn toggle_pin_flips_only_the_current_document() {
+        let docs = vec![
+            QueuedDocument::new("a.md".into(), "a".into()),
+            QueuedDocument::new("b.md".into(), "b".into()),
+        ];
+        let mut queue = DocumentQueue::new(docs).unwrap();
+        queue.next();
+
+        assert!(queue.toggle_pin());
+        let pins: Vec<bool> = queue.documents().iter().map(|doc| doc.pinned).collect();
+        assert_eq!(pins, vec![false, true]);
+
+        assert!(!queue.toggle_pin());
+        assert!(!queue.current().pinned);
+    }
+
+    #[test]
+    fn focus_alternate_toggles_with_the_previous_document() {
+        let docs = ["a.md", "b.md", "c.md", "d.md"]
+            .into_iter()
+            .map(|name| QueuedDocument::new(name.into(), String::new()))
+            .collect();
+        let mut q = DocumentQueue::new(docs).unwrap();
+        assert!(!q.focus_alternate());
+
+        q.next();
+        q.focus_index(3);
+        assert!(q.focus_alternate());
+        assert_eq!(q.current_index(), 1);
+        assert!(q.focus_alternate());
+        assert_eq!(q.current_index(), 3);
+
+        // Refocusing the current document keeps the alternate.
+        q.focus_index(3);
+        q.prev();
+        assert!(q.focus_alternate());
+        assert_eq!(q.current_index(), 3);
+
+        // Reordering keeps the alternate pointing at the same document.
+        q.move_item(2, 0);
+        assert!(q.focus_alternate());
+        assert_eq!(q.current().path, PathBuf::from("c.md"));
+    }
+
+    #[test]
+    fn move_item_reorders_and_keeps_focus() {
+        let docs = ["a.md", "b.md", "c.md"]
+            .into_iter()
+            .map(|name| QueuedDocument::new(name.into(), String::new()))
+            .collect();
+        let mut q = DocumentQueue::new(docs).unwrap();
+        q.focus_index(1);
+
+        assert!(q.move_item(0, 2));
+        let order: Vec<_> = q.documents().iter().map(|doc| doc.path.clone()).collect();
+        assert_eq!(order, ["b.md", "c.md", "a.md"].map(PathBuf::from));
+        assert_eq!(q.current().path, PathBuf::from("b.md"));
+
+        assert!(q.move_item(0, 1));
+        assert_eq!(q.current_index(), 1);
+        assert_eq!(q.current().path, PathBuf::from("b.md"));
+
+        assert!(!q.move_item(0, 3));
+    }
 }
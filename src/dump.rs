@@ -0,0 +1,171 @@
+use std::io::{self, Write};
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+
+use crate::beeline::apply_beeline;
+use crate::color::{ColorDepth, downgrade_line};
+use crate::markdown::{RenderOptions, render_markdown_with_links};
+use crate::theme::Theme;
+
+const DEFAULT_WIDTH: u16 = 80;
+
+pub fn dump_markdown<W: Write>(
+    out: &mut W,
+    markdown: &str,
+    width: Option<u16>,
+    enable_beeline: bool,
+    theme: &Theme,
+    options: &RenderOptions,
+    color_depth: ColorDepth,
+) -> io::Result<()> {
+    let width = width.unwrap_or_else(terminal_width);
+    let rendered = render_markdown_with_links(markdown, width, theme, options);
+    let lines = if enable_beeline {
+        apply_beeline(&rendered.lines, &rendered.kinds, theme, 1.0)
+    } else {
+        rendered.lines
+    };
+    for line in &lines {
+        let line = downgrade_line(line, color_depth);
+        writeln!(out, "{}", line_to_ansi(&line))?;
+    }
+    Ok(())
+}
+
+/// The rendered document as plain text: each line's span contents without
+/// styling or trailing spaces. Wraps at `width`, or 80 columns.
+pub fn render_text(
+    markdown: &str,
+    width: Option<u16>,
+    theme: &Theme,
+    options: &RenderOptions,
+) -> String {
+    let rendered =
+        render_markdown_with_links(markdown, width.unwrap_or(DEFAULT_WIDTH), theme, options);
+    let mut out = String::new();
+    for line in &rendered.lines {
+        let text: String = line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        out.push_str(text.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn terminal_width() -> u16 {
+    crossterm::terminal::size()
+        .map(|(cols, _)| cols)
+        .ok()
+        .filter(|cols| *cols > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+pub fn line_to_ansi(line: &Line<'_>) -> String {
+    let mut out = String::new();
+    for span in &line.spans {
+        let style = line.style.patch(span.style);
+        let codes = style_codes(style);
+        if codes.is_empty() {
+            out.push_str(&span.content);
+        } else {
+            out.push_str(&format!("\x1b[{}m{}\x1b[0m", codes.join(";"), span.content));
+        }
+    }
+    out
+}
+
+fn style_codes(style: Style) -> Vec<String> {
+    let mut codes = Vec::new();
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, code) in modifiers {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    if let Some(fg) = style.fg.and_then(|color| color_code(color, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|color| color_code(color, true)) {
+        codes.push(bg);
+    }
+    codes
+}
+
+fn color_code(color: Color, background: bool) -> Option<String> {
+    let base: u8 = if background { 40 } else { 30 };
+    let offset = match color {
+        Color::Reset => return None,
+        Color::Indexed(idx) => return Some(format!("{};5;{}", base + 8, idx)),
+        Color::Rgb(r, g, b) => return Some(format!("{};2;{};{};{}", base + 8, r, g, b)),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 60,
+        Color::LightRed => 61,
+        Color::LightGreen => 62,
+        Color::LightYellow => 63,
+        Color::LightBlue => 64,
+        Color::LightMagenta => 65,
+        Color::LightCyan => 66,
+        Color::White => 67,
+    };
+    Some((base + offset).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::text::Span;
+
+    use super::*;
+
+    #[test]
+    fn bold_span_is_wrapped_in_bold_escape() {
+        let line = Line::from(vec![
+            Span::raw("plain "),
+            Span::styled("bold", Style::new().add_modifier(Modifier::BOLD)),
+        ]);
+        assert_eq!(line_to_ansi(&line), "plain \x1b[1mbold\x1b[0m");
+    }
+
+    #[test]
+    fn text_export_keeps_headings_tables_and_code_without_escapes() {
+        let markdown = "# Guide\n\n| Name | Size |\n|------|------|\n| a.md | 12 |\n\n```\nfn main() {\n    run();\n}\n```\n";
+        let text = render_text(
+            markdown,
+            Some(60),
+            &Theme::pastel(),
+            &RenderOptions::default(),
+        );
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(!text.contains('\x1b'));
+        assert!(lines.contains(&"Guide"));
+        assert!(lines.contains(&"| Name | Size |"));
+        assert!(lines.contains(&"| ---- | ---- |"));
+        assert!(lines.contains(&"| a.md | 12   |"));
+        assert!(lines.contains(&"│     run();"));
+        assert!(lines.iter().all(|line| !line.ends_with(' ')));
+    }
+
+    #[test]
+    fn colored_span_uses_truecolor_escape() {
+        let line = Line::from(Span::styled("hi", Style::new().fg(Color::Rgb(1, 2, 3))));
+        assert_eq!(line_to_ansi(&line), "\x1b[38;2;1;2;3mhi\x1b[0m");
+    }
+}
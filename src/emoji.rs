@@ -0,0 +1,140 @@
+//! GitHub-style `:shortcode:` emoji.
+
+/// Common shortcodes, sorted by name for binary search.
+const SHORTCODES: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("bangbang", "‼️"),
+    ("bell", "🔔"),
+    ("book", "📖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("calendar", "📆"),
+    ("check", "✔️"),
+    ("clap", "👏"),
+    ("clipboard", "📋"),
+    ("coffee", "☕"),
+    ("confused", "😕"),
+    ("construction", "🚧"),
+    ("cry", "😢"),
+    ("dart", "🎯"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("gear", "⚙️"),
+    ("gift", "🎁"),
+    ("grin", "😁"),
+    ("hammer", "🔨"),
+    ("heart", "❤️"),
+    ("heavy_check_mark", "✔️"),
+    ("hourglass", "⌛"),
+    ("information_source", "ℹ️"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("laughing", "😆"),
+    ("link", "🔗"),
+    ("lock", "🔒"),
+    ("mag", "🔍"),
+    ("memo", "📝"),
+    ("no_entry", "⛔"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("pencil", "📝"),
+    ("pencil2", "✏️"),
+    ("point_right", "👉"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("raised_hands", "🙌"),
+    ("recycle", "♻️"),
+    ("rocket", "🚀"),
+    ("rotating_light", "🚨"),
+    ("see_no_evil", "🙈"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("tools", "🛠️"),
+    ("trophy", "🏆"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("zap", "⚡"),
+];
+
+pub fn lookup(name: &str) -> Option<&'static str> {
+    SHORTCODES
+        .binary_search_by(|(code, _)| (*code).cmp(name))
+        .ok()
+        .map(|idx| SHORTCODES[idx].1)
+}
+
+/// Replaces known `:name:` shortcodes with their emoji; unknown ones are
+/// left as typed.
+pub fn replace_shortcodes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find(':') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let emoji = after
+            .find(':')
+            .filter(|&close| close > 0 && after[..close].chars().all(is_shortcode_char))
+            .and_then(|close| Some((close, lookup(&after[..close])?)));
+        match emoji {
+            Some((close, emoji)) => {
+                out.push_str(emoji);
+                rest = &after[close + 1..];
+            }
+            None => {
+                // The closing colon may open the next shortcode.
+                out.push(':');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_shortcode_char(ch: char) -> bool {
+    ch.is_ascii_lowercase() || ch.is_ascii_digit() || matches!(ch, '_' | '+' | '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcode_table_is_sorted() {
+        assert!(SHORTCODES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn replaces_known_shortcodes() {
+        assert_eq!(replace_shortcodes("ship it :rocket:"), "ship it 🚀");
+        assert_eq!(replace_shortcodes(":+1: thanks"), "👍 thanks");
+    }
+
+    #[test]
+    fn leaves_unknown_shortcodes_and_plain_colons() {
+        assert_eq!(replace_shortcodes(":foo: bar"), ":foo: bar");
+        assert_eq!(replace_shortcodes("time 10:30: ok"), "time 10:30: ok");
+        assert_eq!(replace_shortcodes("a: b"), "a: b");
+        assert_eq!(replace_shortcodes("::"), "::");
+    }
+
+    #[test]
+    fn replaces_adjacent_shortcodes() {
+        assert_eq!(replace_shortcodes(":tada::tada:"), "🎉🎉");
+        assert_eq!(replace_shortcodes(":foo:rocket:"), ":foo🚀");
+    }
+}
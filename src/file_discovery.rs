@@ -1,17 +1,67 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-pub fn discover_markdown_paths(inputs: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+use crate::file_kind::is_openable;
+
+/// Queue order chosen with `--sort`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Discovery order: explicit inputs as given, directories alphabetically.
+    #[default]
+    Name,
+    /// Most recently modified first.
+    Mtime,
+    /// Largest first.
+    Size,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    size: u64,
+}
+
+/// Expands `inputs` into markdown file paths. Explicitly listed files keep
+/// their command-line order; files found inside a directory input or matched
+/// by a glob pattern are sorted and inserted where that input appeared.
+/// Duplicates keep their first position. With `any_text`, `.txt` and
+/// extensionless files are picked up too.
+pub fn discover_markdown_paths(inputs: &[PathBuf], any_text: bool) -> io::Result<Vec<PathBuf>> {
     let mut discovered = Vec::new();
 
     for input in inputs {
         if input.is_dir() {
-            walk_dir(input, &mut discovered)?;
+            let mut found = Vec::new();
+            walk_dir(input, any_text, &mut found)?;
+            found.sort();
+            discovered.extend(found);
             continue;
         }
 
-        if input.is_file() && is_markdown(input) {
+        if !input.exists()
+            && let Some(pattern) = input.to_str().filter(|pattern| has_glob_meta(pattern))
+        {
+            let mut found: Vec<PathBuf> = expand_glob(pattern)?
+                .into_iter()
+                .filter(|path| path.is_file() && is_openable(path, any_text))
+                .map(|path| fs::canonicalize(&path).unwrap_or(path))
+                .collect();
+            if found.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No markdown files match pattern: {}", pattern),
+                ));
+            }
+            found.sort();
+            discovered.extend(found);
+            continue;
+        }
+
+        if input.is_file() && is_openable(input, any_text) {
             let absolute = fs::canonicalize(input).unwrap_or_else(|_| input.clone());
             discovered.push(absolute);
             continue;
@@ -23,20 +73,171 @@ pub fn discover_markdown_paths(inputs: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
         ));
     }
 
-    discovered.sort();
-    discovered.dedup();
+    let mut seen = HashSet::new();
+    discovered.retain(|path| seen.insert(path.clone()));
     Ok(discovered)
 }
 
-fn walk_dir(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+/// Reorders discovered `paths` by `order`. Ties, and files that cannot be
+/// stat'd (which go last), keep their discovery order.
+pub fn sort_paths(paths: &mut Vec<PathBuf>, order: SortOrder) {
+    if order == SortOrder::Name {
+        return;
+    }
+    let mut stamped: Vec<(Option<FileStamp>, PathBuf)> = paths
+        .drain(..)
+        .map(|path| {
+            let stamp = fs::metadata(&path).ok().map(|meta| FileStamp {
+                modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                size: meta.len(),
+            });
+            (stamp, path)
+        })
+        .collect();
+    stamped.sort_by(|(a, _), (b, _)| compare_stamps(order, a.as_ref(), b.as_ref()));
+    paths.extend(stamped.into_iter().map(|(_, path)| path));
+}
+
+fn compare_stamps(order: SortOrder, a: Option<&FileStamp>, b: Option<&FileStamp>) -> Ordering {
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (Some(_), None) => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        (None, None) => return Ordering::Equal,
+    };
+    match order {
+        SortOrder::Name => Ordering::Equal,
+        SortOrder::Mtime => b.modified.cmp(&a.modified),
+        SortOrder::Size => b.size.cmp(&a.size),
+    }
+}
+
+/// True when `input` contains `*`, `?`, or `[`, i.e. should be expanded
+/// rather than opened literally.
+fn has_glob_meta(input: &str) -> bool {
+    input.contains(['*', '?', '['])
+}
+
+/// Expands a glob pattern into matching paths. `*` and `?` match within one
+/// path component (not a leading `.`), `[...]` matches a character class, and
+/// a `**` component matches any number of directories.
+fn expand_glob(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let mut base = PathBuf::new();
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(pattern).components() {
+        let text = component.as_os_str().to_string_lossy();
+        if parts.is_empty() && !has_glob_meta(&text) {
+            base.push(component);
+        } else {
+            parts.push(text.into_owned());
+        }
+    }
+    let base = if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    };
+
+    let mut matches = Vec::new();
+    expand_glob_parts(&base, &parts, &mut matches)?;
+    Ok(matches)
+}
+
+fn expand_glob_parts(dir: &Path, parts: &[String], out: &mut Vec<PathBuf>) -> io::Result<()> {
+    let Some((part, rest)) = parts.split_first() else {
+        out.push(dir.to_path_buf());
+        return Ok(());
+    };
+    if !dir.is_dir() {
+        return Ok(());
+    }
+
+    if part == "**" {
+        expand_glob_parts(dir, rest, out)?;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() && !is_hidden(&path) {
+                expand_glob_parts(&path, parts, out)?;
+            }
+        }
+        return Ok(());
+    }
+
+    let pattern: Vec<char> = part.chars().collect();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') && !part.starts_with('.') {
+            continue;
+        }
+        let name: Vec<char> = name.chars().collect();
+        if wildcard_match(&pattern, &name) {
+            expand_glob_parts(&entry.path(), rest, out)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+/// Matches one path component against a pattern of literals, `*`, `?`, and
+/// `[...]` classes (with `!` or `^` negation and `a-z` ranges).
+fn wildcard_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| wildcard_match(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && wildcard_match(rest, &name[1..]),
+        Some(('[', rest)) => {
+            let Some(close) = rest
+                .iter()
+                .skip(1)
+                .position(|&ch| ch == ']')
+                .map(|idx| idx + 1)
+            else {
+                return name.first() == Some(&'[') && wildcard_match(rest, &name[1..]);
+            };
+            let Some(&ch) = name.first() else {
+                return false;
+            };
+            let (negated, class) = match rest[..close].split_first() {
+                Some(('!' | '^', class)) => (true, class),
+                _ => (false, &rest[..close]),
+            };
+            let mut matched = false;
+            let mut idx = 0;
+            while idx < class.len() {
+                if idx + 2 < class.len() && class[idx + 1] == '-' {
+                    matched |= (class[idx]..=class[idx + 2]).contains(&ch);
+                    idx += 3;
+                } else {
+                    matched |= class[idx] == ch;
+                    idx += 1;
+                }
+            }
+            matched != negated && wildcard_match(&rest[close + 1..], &name[1..])
+        }
+        Some((&literal, rest)) => {
+            name.first() == Some(&literal) && wildcard_match(rest, &name[1..])
+        }
+    }
+}
+
+fn walk_dir(dir: &Path, any_text: bool, out: &mut Vec<PathBuf>) -> io::Result<()> {
     let mut entries = fs::read_dir(dir)?.collect::<Result<Vec<_>, io::Error>>()?;
     entries.sort_by_key(|entry| entry.path());
 
     for entry in entries {
         let path = entry.path();
         if path.is_dir() {
-            walk_dir(&path, out)?;
-        } else if path.is_file() && is_markdown(&path) {
+            // Extensionless files inside `.git` and friends are never wanted.
+            if !(any_text && is_hidden(&path)) {
+                walk_dir(&path, any_text, out)?;
+            }
+        } else if path.is_file() && is_openable(&path, any_text) {
             let absolute = fs::canonicalize(&path).unwrap_or(path.clone());
             out.push(absolute);
         }
@@ -45,18 +246,10 @@ fn walk_dir(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
     Ok(())
 }
 
-fn is_markdown(path: &Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| {
-            let ext = ext.to_ascii_lowercase();
-            matches!(ext.as_str(), "md" | "markdown" | "mdown" | "mdx")
-        })
-        .unwrap_or(false)
-}
-
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
     #[test]
@@ -67,7 +260,145 @@ mod tests {
         fs::write(dir.path().join("nested/b.markdown"), "# b").unwrap();
         fs::write(dir.path().join("nested/c.txt"), "nope").unwrap();
 
-        let found = discover_markdown_paths(&[dir.path().to_path_buf()]).unwrap();
+        let found = discover_markdown_paths(&[dir.path().to_path_buf()], false).unwrap();
         assert_eq!(found.len(), 2);
     }
+
+    #[test]
+    fn any_text_adds_text_files_but_skips_hidden_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join(".git/HEAD"), "ref").unwrap();
+        fs::write(dir.path().join("README"), "readme").unwrap();
+        fs::write(dir.path().join("notes.txt"), "notes").unwrap();
+        fs::write(dir.path().join("a.md"), "# a").unwrap();
+        let names = |any_text| -> Vec<String> {
+            discover_markdown_paths(&[dir.path().to_path_buf()], any_text)
+                .unwrap()
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+
+        assert_eq!(names(false), ["a.md"]);
+        assert_eq!(names(true), ["README", "a.md", "notes.txt"]);
+    }
+
+    #[test]
+    fn detects_glob_metacharacters() {
+        assert!(has_glob_meta("docs/*.md"));
+        assert!(has_glob_meta("docs/ch?.md"));
+        assert!(has_glob_meta("docs/[ab].md"));
+        assert!(!has_glob_meta("docs/readme.md"));
+    }
+
+    #[test]
+    fn matches_wildcards_within_a_component() {
+        let matches = |pattern: &str, name: &str| {
+            wildcard_match(
+                &pattern.chars().collect::<Vec<_>>(),
+                &name.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert!(matches("*.md", "intro.md"));
+        assert!(!matches("*.md", "intro.txt"));
+        assert!(matches("ch?.md", "ch1.md"));
+        assert!(matches("[a-c]*.md", "b.md"));
+        assert!(!matches("[!a-c]*.md", "b.md"));
+    }
+
+    #[test]
+    fn expands_glob_inputs_against_a_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let docs = dir.path().join("docs");
+        fs::create_dir_all(docs.join("sub")).unwrap();
+        fs::write(docs.join("b.md"), "# b").unwrap();
+        fs::write(docs.join("a.md"), "# a").unwrap();
+        fs::write(docs.join("notes.txt"), "nope").unwrap();
+        fs::write(docs.join("sub/c.md"), "# c").unwrap();
+        let names = |found: Vec<PathBuf>| -> Vec<String> {
+            found
+                .iter()
+                .map(|path| path.strip_prefix(fs::canonicalize(&docs).unwrap()).unwrap())
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+                .collect()
+        };
+
+        let flat = discover_markdown_paths(&[docs.join("*.md")], false).unwrap();
+        assert_eq!(names(flat), ["a.md", "b.md"]);
+
+        let deep = discover_markdown_paths(&[docs.join("**").join("*.md")], false).unwrap();
+        assert_eq!(names(deep), ["a.md", "b.md", "sub/c.md"]);
+
+        assert!(discover_markdown_paths(&[docs.join("*.rst")], false).is_err());
+    }
+
+    #[test]
+    fn compares_stamps_newest_and_largest_first() {
+        let old = FileStamp {
+            modified: SystemTime::UNIX_EPOCH,
+            size: 900,
+        };
+        let new = FileStamp {
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(60),
+            size: 10,
+        };
+        let cmp = |order, a, b| compare_stamps(order, Some(a), Some(b));
+        assert_eq!(cmp(SortOrder::Mtime, &new, &old), Ordering::Less);
+        assert_eq!(cmp(SortOrder::Size, &new, &old), Ordering::Greater);
+        assert_eq!(cmp(SortOrder::Name, &new, &old), Ordering::Equal);
+        assert_eq!(
+            compare_stamps(SortOrder::Mtime, None, Some(&old)),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn mtime_sort_puts_newer_file_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let older = dir.path().join("a.md");
+        let newer = dir.path().join("b.md");
+        fs::write(&older, "# old").unwrap();
+        fs::write(&newer, "# new").unwrap();
+        let base = SystemTime::now() - Duration::from_secs(3600);
+        for (path, age) in [(&older, 0), (&newer, 60)] {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(base + Duration::from_secs(age))
+                .unwrap();
+        }
+
+        let mut found = discover_markdown_paths(&[dir.path().to_path_buf()], false).unwrap();
+        let names = |found: &[PathBuf]| -> Vec<String> {
+            found
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(names(&found), ["a.md", "b.md"]);
+        sort_paths(&mut found, SortOrder::Mtime);
+        assert_eq!(names(&found), ["b.md", "a.md"]);
+    }
+
+    #[test]
+    fn preserves_order_of_explicit_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let intro = dir.path().join("intro.md");
+        let setup = dir.path().join("setup.md");
+        let api = dir.path().join("api.md");
+        for path in [&intro, &setup, &api] {
+            fs::write(path, "# doc").unwrap();
+        }
+
+        let found =
+            discover_markdown_paths(&[intro.clone(), setup.clone(), api.clone(), intro], false)
+                .unwrap();
+        let names: Vec<_> = found
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["intro.md", "setup.md", "api.md"]);
+    }
 }
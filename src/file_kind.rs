@@ -0,0 +1,59 @@
+use std::path::Path;
+
+/// True for the markdown extensions mdr renders: `.md`, `.markdown`,
+/// `.mdown` and `.mdx` (case-insensitive).
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            let ext = ext.to_ascii_lowercase();
+            matches!(ext.as_str(), "md" | "markdown" | "mdown" | "mdx")
+        })
+        .unwrap_or(false)
+}
+
+/// True for `.txt` files and extensionless, non-hidden names such as
+/// `README` or `LICENSE`.
+pub fn is_plain_text(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if name.starts_with('.') {
+        return false;
+    }
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => ext.eq_ignore_ascii_case("txt"),
+        None => true,
+    }
+}
+
+/// Whether discovery and the picker should list `path`; plain text files
+/// only count with `--any-text`.
+pub fn is_openable(path: &Path, any_text: bool) -> bool {
+    is_markdown(path) || (any_text && is_plain_text(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_extensions_are_accepted_by_default() {
+        for name in ["a.md", "b.MARKDOWN", "c.mdown", "d.mdx"] {
+            assert!(is_openable(Path::new(name), false), "{name}");
+        }
+        for name in ["README", "notes.txt", "x.rst", ".hidden"] {
+            assert!(!is_openable(Path::new(name), false), "{name}");
+        }
+    }
+
+    #[test]
+    fn any_text_also_accepts_txt_and_extensionless_files() {
+        for name in ["README", "docs/LICENSE", "notes.TXT", "a.md"] {
+            assert!(is_openable(Path::new(name), true), "{name}");
+        }
+        for name in ["x.rst", "image.png", ".gitignore"] {
+            assert!(!is_openable(Path::new(name), true), "{name}");
+        }
+    }
+}
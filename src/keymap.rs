@@ -0,0 +1,334 @@
+use crossterm::event::{KeyCode, KeyModifiers};
+
+/// A reader command that can be bound to a different key in the `[keys]`
+/// table of the config file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    NextDocument,
+    PreviousDocument,
+    AlternateDocument,
+    NextHeading,
+    PreviousHeading,
+    GoDialog,
+    TogglePin,
+    OpenPicker,
+    OpenPickerHere,
+    CopyPath,
+    GlobalSearch,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::Quit,
+        Action::NextDocument,
+        Action::PreviousDocument,
+        Action::AlternateDocument,
+        Action::NextHeading,
+        Action::PreviousHeading,
+        Action::GoDialog,
+        Action::TogglePin,
+        Action::OpenPicker,
+        Action::OpenPickerHere,
+        Action::CopyPath,
+        Action::GlobalSearch,
+    ];
+
+    /// The name used for this action in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::NextDocument => "next_document",
+            Action::PreviousDocument => "previous_document",
+            Action::AlternateDocument => "alternate_document",
+            Action::NextHeading => "next_heading",
+            Action::PreviousHeading => "previous_heading",
+            Action::GoDialog => "go_dialog",
+            Action::TogglePin => "toggle_pin",
+            Action::OpenPicker => "open_picker",
+            Action::OpenPickerHere => "open_picker_here",
+            Action::CopyPath => "copy_path",
+            Action::GlobalSearch => "global_search",
+        }
+    }
+
+    fn default_key(self) -> KeyBinding {
+        let ch = match self {
+            Action::Quit => 'q',
+            Action::NextDocument => ']',
+            Action::PreviousDocument => '[',
+            Action::AlternateDocument => '\'',
+            Action::NextHeading => '}',
+            Action::PreviousHeading => '{',
+            Action::GoDialog => 'g',
+            Action::TogglePin => 'p',
+            Action::OpenPicker => 'o',
+            Action::OpenPickerHere => 'O',
+            Action::CopyPath => 'Y',
+            Action::GlobalSearch => '?',
+        };
+        KeyBinding::plain(KeyCode::Char(ch))
+    }
+}
+
+/// A key with the Ctrl/Alt modifiers it must be pressed with. Shift is part
+/// of the character itself (`O`, `?`), so it is not tracked separately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self {
+            code,
+            modifiers: modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+
+    pub fn plain(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Binding {
+    action: Action,
+    key: Option<KeyBinding>,
+    custom: bool,
+}
+
+/// Keys for each [`Action`]: the built-in bindings, with any set in the
+/// config file replacing them. A custom binding takes its key away from the
+/// default action that had it, so `previous_document = "p"` leaves pinning
+/// unbound unless `toggle_pin` is given a key too.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: Vec<Binding>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: Action::ALL
+                .iter()
+                .map(|&action| Binding {
+                    action,
+                    key: Some(action.default_key()),
+                    custom: false,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Binds the action called `action` to `key` (`n`, `ctrl-n`, `pagedown`).
+    pub fn bind(&mut self, action: &str, key: &str) -> Result<(), String> {
+        let action = Action::ALL
+            .into_iter()
+            .find(|candidate| candidate.name() == action)
+            .ok_or_else(|| format!("Unknown key action: {}", action))?;
+        let binding = parse_key(key)?;
+        if let Some(command) = fixed_key_command(binding) {
+            return Err(format!(
+                "Key {} is bound to both {} and {}",
+                key,
+                command,
+                action.name()
+            ));
+        }
+        for other in &mut self.bindings {
+            if other.action == action || other.key != Some(binding) {
+                continue;
+            }
+            if other.custom {
+                return Err(format!(
+                    "Key {} is bound to both {} and {}",
+                    key,
+                    other.action.name(),
+                    action.name()
+                ));
+            }
+            other.key = None;
+        }
+        let entry = self
+            .bindings
+            .iter_mut()
+            .find(|entry| entry.action == action)
+            .expect("every action has an entry");
+        entry.key = Some(binding);
+        entry.custom = true;
+        Ok(())
+    }
+
+    /// The action bound to `code` pressed with `modifiers`, if any.
+    pub fn action_for(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<Action> {
+        let pressed = KeyBinding::new(code, modifiers);
+        self.bindings
+            .iter()
+            .find(|entry| entry.key == Some(pressed))
+            .map(|entry| entry.action)
+    }
+}
+
+/// What a key that cannot be rebound does. Any other key may be bound,
+/// and the binding wins over the reader's built-in use of that key.
+fn fixed_key_command(key: KeyBinding) -> Option<&'static str> {
+    match (key.code, key.modifiers) {
+        (KeyCode::Char('c'), KeyModifiers::CONTROL) => Some("quit"),
+        (KeyCode::Esc, KeyModifiers::NONE) => Some("cancel"),
+        (KeyCode::Char('/'), KeyModifiers::NONE) => Some("search"),
+        (KeyCode::Char(':'), KeyModifiers::NONE) => Some("the command prompt"),
+        _ => None,
+    }
+}
+
+/// Parses a key name: a single character, a named key (`space`, `tab`,
+/// `enter`, `pageup`, `f5`, ...), optionally prefixed by `ctrl-` or `alt-`.
+pub fn parse_key(name: &str) -> Result<KeyBinding, String> {
+    let invalid = || format!("Invalid key: {}", name);
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name.trim();
+    loop {
+        let lower = rest.to_ascii_lowercase();
+        if rest.chars().count() > 1 && lower.starts_with("ctrl-") {
+            modifiers |= KeyModifiers::CONTROL;
+            rest = &rest["ctrl-".len()..];
+        } else if rest.chars().count() > 1 && lower.starts_with("alt-") {
+            modifiers |= KeyModifiers::ALT;
+            rest = &rest["alt-".len()..];
+        } else {
+            break;
+        }
+    }
+    let mut chars = rest.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(ch), None) => KeyCode::Char(ch),
+        (None, _) => return Err(invalid()),
+        _ => match rest.to_ascii_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "tab" => KeyCode::Tab,
+            "enter" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "insert" => KeyCode::Insert,
+            "delete" => KeyCode::Delete,
+            other => match other.strip_prefix('f').map(str::parse::<u8>) {
+                Some(Ok(n @ 1..=12)) => KeyCode::F(n),
+                _ => return Err(invalid()),
+            },
+        },
+    };
+    Ok(KeyBinding::new(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_the_built_in_keys() {
+        let keymap = KeyMap::default();
+        let plain = KeyModifiers::NONE;
+        assert_eq!(
+            keymap.action_for(KeyCode::Char(']'), plain),
+            Some(Action::NextDocument)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('O'), KeyModifiers::SHIFT),
+            Some(Action::OpenPickerHere)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('q'), plain),
+            Some(Action::Quit)
+        );
+        assert_eq!(keymap.action_for(KeyCode::Char('x'), plain), None);
+        assert_eq!(
+            keymap.action_for(KeyCode::Char(']'), KeyModifiers::CONTROL),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_key_names() {
+        assert_eq!(parse_key("n"), Ok(KeyBinding::plain(KeyCode::Char('n'))));
+        assert_eq!(
+            parse_key("ctrl-n"),
+            Ok(KeyBinding::new(KeyCode::Char('n'), KeyModifiers::CONTROL))
+        );
+        assert_eq!(
+            parse_key("Alt-PageDown"),
+            Ok(KeyBinding::new(KeyCode::PageDown, KeyModifiers::ALT))
+        );
+        assert_eq!(
+            parse_key("space"),
+            Ok(KeyBinding::plain(KeyCode::Char(' ')))
+        );
+        assert_eq!(parse_key("f5"), Ok(KeyBinding::plain(KeyCode::F(5))));
+        assert_eq!(parse_key("-"), Ok(KeyBinding::plain(KeyCode::Char('-'))));
+        assert!(parse_key("").is_err());
+        assert!(parse_key("ctrl-").is_err());
+        assert!(parse_key("hyper-x").is_err());
+        assert!(parse_key("f13").is_err());
+    }
+
+    #[test]
+    fn custom_bindings_replace_defaults() {
+        let mut keymap = KeyMap::default();
+        keymap.bind("next_document", "n").unwrap();
+        keymap.bind("previous_document", "p").unwrap();
+        let plain = KeyModifiers::NONE;
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('n'), plain),
+            Some(Action::NextDocument)
+        );
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('p'), plain),
+            Some(Action::PreviousDocument)
+        );
+        assert_eq!(keymap.action_for(KeyCode::Char(']'), plain), None);
+        assert_eq!(
+            keymap.action_for(KeyCode::Char('g'), plain),
+            Some(Action::GoDialog)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_actions_and_clashing_bindings() {
+        let mut keymap = KeyMap::default();
+        assert!(keymap.bind("launch_rockets", "x").is_err());
+        assert!(keymap.bind("quit", "ctrl-").is_err());
+        keymap.bind("next_document", "n").unwrap();
+        assert_eq!(
+            keymap.bind("go_dialog", "n"),
+            Err("Key n is bound to both next_document and go_dialog".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_keys_that_must_stay_fixed() {
+        let mut keymap = KeyMap::default();
+        assert_eq!(
+            keymap.bind("next_document", "/"),
+            Err("Key / is bound to both search and next_document".to_string())
+        );
+        for key in [":", "esc", "ctrl-c"] {
+            assert!(keymap.bind("next_document", key).is_err(), "{}", key);
+        }
+        assert_eq!(
+            keymap.action_for(KeyCode::Char(']'), KeyModifiers::NONE),
+            Some(Action::NextDocument)
+        );
+        keymap.bind("next_document", "ctrl-/").unwrap();
+    }
+}
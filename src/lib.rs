@@ -0,0 +1,20 @@
+//! Markdown rendering pipeline behind the `mdr` reader.
+//!
+//! [`render_markdown_with_links`] turns markdown into styled ratatui lines,
+//! [`render_plain_lines`] produces the unstyled fallback, and
+//! [`apply_beeline`] tints prose lines with the reading gradient from a
+//! [`Theme`].
+
+pub mod beeline;
+pub mod color;
+pub mod emoji;
+pub mod markdown;
+pub mod theme;
+pub mod wrap;
+
+pub use beeline::apply_beeline;
+pub use markdown::{
+    Heading, LineKind, LinkTarget, RenderOptions, RenderedMarkdown, render_markdown_with_links,
+    render_plain_lines,
+};
+pub use theme::Theme;
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+/// Commands for opening links with custom URL schemes (`obsidian://`,
+/// `zotero://`, ...), set in the `[link_schemes]` table of the config file.
+/// Templates use the same `%s` placeholder as `--browser`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkSchemes {
+    commands: BTreeMap<String, String>,
+}
+
+impl LinkSchemes {
+    pub fn insert(&mut self, scheme: &str, template: &str) -> Result<(), String> {
+        if !is_valid_scheme(scheme) {
+            return Err(format!("Invalid link scheme: {}", scheme));
+        }
+        if template.trim().is_empty() {
+            return Err(format!("Empty command for link scheme {}", scheme));
+        }
+        self.commands
+            .insert(scheme.to_ascii_lowercase(), template.to_string());
+        Ok(())
+    }
+
+    /// Command template registered for the scheme of `url`, if any.
+    pub fn command_for(&self, url: &str) -> Option<&str> {
+        let scheme = url_scheme(url)?.to_ascii_lowercase();
+        self.commands.get(&scheme).map(String::as_str)
+    }
+
+    /// Template to open `url` with: its scheme's handler, else `browser`.
+    pub fn resolve<'a>(&'a self, url: &str, browser: Option<&'a str>) -> Option<&'a str> {
+        self.command_for(url).or(browser)
+    }
+}
+
+/// The scheme of an absolute URL (`https` in `https://example.com`).
+pub fn url_scheme(url: &str) -> Option<&str> {
+    let (scheme, _) = url.split_once(':')?;
+    is_valid_scheme(scheme).then_some(scheme)
+}
+
+/// RFC 3986: a letter followed by letters, digits, `+`, `-`, or `.`.
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_alphabetic())
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schemes() -> LinkSchemes {
+        let mut schemes = LinkSchemes::default();
+        schemes.insert("obsidian", "obsidian-open %s").unwrap();
+        schemes.insert("Zotero", "zotero-cli").unwrap();
+        schemes
+    }
+
+    #[test]
+    fn extracts_url_schemes() {
+        assert_eq!(url_scheme("https://example.com"), Some("https"));
+        assert_eq!(url_scheme("obsidian://open?vault=x"), Some("obsidian"));
+        assert_eq!(url_scheme("mailto:me@example.com"), Some("mailto"));
+        assert_eq!(url_scheme("docs/guide.md"), None);
+        assert_eq!(url_scheme("1http://x"), None);
+        assert_eq!(url_scheme("#section"), None);
+    }
+
+    #[test]
+    fn custom_schemes_use_their_handler() {
+        let schemes = schemes();
+        assert_eq!(
+            schemes.resolve("obsidian://open?vault=notes", Some("firefox")),
+            Some("obsidian-open %s")
+        );
+        assert_eq!(
+            schemes.resolve("ZOTERO://select/items/1", None),
+            Some("zotero-cli")
+        );
+    }
+
+    #[test]
+    fn other_schemes_fall_back_to_the_browser() {
+        let schemes = schemes();
+        assert_eq!(
+            schemes.resolve("https://example.com", Some("firefox")),
+            Some("firefox")
+        );
+        assert_eq!(schemes.resolve("https://example.com", None), None);
+    }
+
+    #[test]
+    fn rejects_invalid_registrations() {
+        let mut schemes = LinkSchemes::default();
+        assert!(schemes.insert("not a scheme", "open %s").is_err());
+        assert!(schemes.insert("x", "  ").is_err());
+    }
+}
@@ -1,32 +1,99 @@
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::process;
 
-mod beeline;
+mod background;
 mod cli;
+mod command;
+mod config;
 mod document_queue;
+mod dump;
 mod file_discovery;
-mod markdown;
+mod file_kind;
+mod keymap;
+mod link_scheme;
 mod picker;
-mod theme;
+mod session;
 mod ui;
 
-use cli::parse_args;
-use document_queue::{DocumentQueue, QueuedDocument};
-use file_discovery::discover_markdown_paths;
+use cli::{CliCommand, parse_command};
+use document_queue::{DocumentQueue, QueuedDocument, read_document};
+use file_discovery::{SortOrder, discover_markdown_paths, sort_paths};
+use mdr::{beeline, color, markdown, theme, wrap};
 
 fn main() {
-    let args = match parse_args(env::args()) {
-        Ok(args) => args,
+    let args = match parse_command(env::args()) {
+        Ok(CliCommand::Run(args)) => *args,
+        Ok(CliCommand::Help) => {
+            println!("{}", cli::help_text());
+            return;
+        }
+        Ok(CliCommand::Version) => {
+            println!("{}", cli::version_text());
+            return;
+        }
         Err(err) => {
             eprintln!("{}", err);
             process::exit(2);
         }
     };
 
-    let enable_beeline = args.enable_beeline;
+    let mut config = config::Config::load();
+    let saved_view = if config.remember_view {
+        load_saved_view()
+    } else {
+        None
+    };
+    if let Some(view) = &saved_view {
+        config = config.with_view(view);
+    }
+    let config = config.with_cli(&args);
+    let enable_beeline = config.beeline;
+    let background = config.theme.unwrap_or_else(background::detect_background);
+    let mut theme = match background {
+        background::Background::Light => theme::Theme::light(),
+        background::Background::Dark | background::Background::Unknown => theme::Theme::dark(),
+    };
+    // Validating the name would load the themes `--no-highlight` skips.
+    if let Some(name) = config.code_theme.as_ref().filter(|_| args.highlight) {
+        match markdown::resolve_code_theme(name) {
+            Ok(code_theme) => theme.code_theme = code_theme,
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(2);
+            }
+        }
+    }
     let picker_root = env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
-    let queue = match load_initial_queue(&args.inputs) {
+    if args.list {
+        let paths = if args.inputs.is_empty() {
+            load_resumed_queue(args.lossy).map(|queue| {
+                queue
+                    .documents()
+                    .iter()
+                    .map(|doc| doc.path.clone())
+                    .collect()
+            })
+        } else {
+            discover_queue_paths(&args.inputs, args.sort, args.any_text)
+        };
+        let result = paths.and_then(|paths| {
+            write_path_list(&mut std::io::stdout().lock(), &paths)
+                .map_err(|err| format!("Failed to write output: {}", err))
+        });
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+        return;
+    }
+    let loaded = if args.inputs.is_empty() {
+        load_resumed_queue(args.lossy)
+    } else {
+        load_initial_queue(&args.inputs, args.lossy, args.sort, args.any_text)
+    };
+    let mut queue = match loaded {
         Ok(queue) => queue,
         Err(err) => {
             eprintln!("{}", err);
@@ -34,23 +101,190 @@ fn main() {
         }
     };
 
-    if let Err(err) = ui::run_tui(queue, picker_root, enable_beeline) {
-        eprintln!("TUI error: {}", err);
-        process::exit(1);
+    let color_depth = config.colors.unwrap_or_else(|| {
+        color::ColorDepth::detect(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    });
+    let render_options = markdown::RenderOptions {
+        tab_width: config.tab_width,
+        emoji_shortcodes: args.emoji,
+        reformat_json: args.reformat_json,
+        highlight: args.highlight,
+        // There is no key to reveal diagram source in `--dump` output.
+        diagram_source: args.dump || args.export_text.is_some(),
+    };
+
+    if let Some(out) = &args.export_html {
+        let doc = queue.current();
+        let title = doc
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| doc.path.display().to_string());
+        let html = markdown::render_html_document(&title, &doc.content);
+        if let Err(err) = fs::write(out, html) {
+            eprintln!("Failed to write {}: {}", out.display(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(out) = &args.export_text {
+        let text = dump::render_text(
+            &queue.current().content,
+            args.width,
+            &theme,
+            &render_options,
+        );
+        if let Err(err) = fs::write(out, text) {
+            eprintln!("Failed to write {}: {}", out.display(), err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args.dump {
+        let mut stdout = std::io::stdout().lock();
+        for (idx, doc) in queue.documents().iter().enumerate() {
+            // Rendered documents end without a blank line; separate them.
+            if idx > 0
+                && let Err(err) = writeln!(stdout)
+            {
+                eprintln!("Failed to write output: {}", err);
+                process::exit(1);
+            }
+            if let Err(err) = dump::dump_markdown(
+                &mut stdout,
+                &doc.content,
+                args.width,
+                enable_beeline,
+                &theme,
+                &render_options,
+                color_depth,
+            ) {
+                eprintln!("Failed to write output: {}", err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let start_heading = args
+        .start_heading
+        .as_ref()
+        .and_then(|(path, fragment)| queue.focus_existing(path).then(|| fragment.clone()));
+
+    match ui::run_tui(
+        queue,
+        picker_root,
+        ui::TuiOptions {
+            enable_beeline,
+            force_full_render: args.full_render,
+            words_per_minute: config.words_per_minute,
+            center_search_matches: !args.top_align_matches,
+            render_options,
+            browser: args.browser.clone().or_else(|| {
+                env::var("BROWSER")
+                    .ok()
+                    .and_then(|value| ui::browser_from_env(&value))
+            }),
+            link_schemes: config.link_schemes.clone(),
+            keymap: config.keymap.clone(),
+            lossy_utf8: args.lossy,
+            color_depth,
+            watch: args.watch || args.follow,
+            follow: args.follow,
+            smooth_scroll: args.smooth_scroll,
+            mouse: args.mouse,
+            any_text: args.any_text,
+            sticky_search: args.sticky_search,
+            line_numbers: args.line_numbers,
+            wrap_queue: args.wrap_queue,
+            padding: args.padding,
+            max_text_width: args.max_text_width,
+            start_heading,
+            start_line: args.start_line,
+            plain_mode: saved_view.is_some_and(|view| view.plain),
+            theme_choice: config.theme,
+        },
+        theme,
+    ) {
+        Ok((queue, view)) => {
+            if config.remember_view
+                && let Some(path) = session::view_file()
+                && let Err(err) = session::save_view(&path, &view)
+            {
+                eprintln!("{}", err);
+            }
+            if args.resume
+                && let Some(path) = session::session_file()
+                && let Err(err) =
+                    session::save_session(&path, &session::SavedSession::from_queue(&queue))
+            {
+                eprintln!("{}", err);
+            }
+        }
+        Err(err) => {
+            eprintln!("TUI error: {}", err);
+            process::exit(1);
+        }
     }
 }
 
-fn load_initial_queue(inputs: &[std::path::PathBuf]) -> Result<DocumentQueue, String> {
-    let paths = discover_markdown_paths(inputs)
+/// The view saved by the last run; unreadable files are reported and ignored.
+fn load_saved_view() -> Option<session::SavedView> {
+    let path = session::view_file()?;
+    session::load_view(&path).unwrap_or_else(|err| {
+        eprintln!("Ignoring {}: {}", path.display(), err);
+        None
+    })
+}
+
+fn load_resumed_queue(lossy: bool) -> Result<DocumentQueue, String> {
+    let path = session::session_file()
+        .ok_or_else(|| "Cannot locate a cache directory for the saved session".to_string())?;
+    let text = fs::read_to_string(&path)
+        .map_err(|err| format!("No saved session at {}: {}", path.display(), err))?;
+    let saved = session::deserialize_session(&text)?;
+    session::restore_queue(&saved, lossy)
+}
+
+/// The documents `inputs` expand to, in queue order.
+fn discover_queue_paths(
+    inputs: &[std::path::PathBuf],
+    sort: SortOrder,
+    any_text: bool,
+) -> Result<Vec<std::path::PathBuf>, String> {
+    let mut paths = discover_markdown_paths(inputs, any_text)
         .map_err(|err| format!("Failed to discover markdown files: {}", err))?;
+    sort_paths(&mut paths, sort);
 
     if paths.is_empty() {
         return Err("No markdown files found from provided inputs".to_string());
     }
+    Ok(paths)
+}
+
+fn write_path_list(out: &mut impl Write, paths: &[std::path::PathBuf]) -> std::io::Result<()> {
+    for path in paths {
+        writeln!(out, "{}", path.display())?;
+    }
+    Ok(())
+}
+
+fn load_initial_queue(
+    inputs: &[std::path::PathBuf],
+    lossy: bool,
+    sort: SortOrder,
+    any_text: bool,
+) -> Result<DocumentQueue, String> {
+    let paths = discover_queue_paths(inputs, sort, any_text)?;
 
     let mut docs = Vec::with_capacity(paths.len());
     for path in paths {
-        let content = fs::read_to_string(&path)
+        let content = read_document(&path, lossy)
             .map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
         docs.push(QueuedDocument::new(path, content));
     }
@@ -77,9 +311,36 @@ mod tests {
         fs::write(&ignored, "not markdown").expect("write ignored");
 
         let inputs = vec![single.clone(), nested_dir.clone()];
-        let queue = load_initial_queue(&inputs).expect("queue should load");
+        let queue =
+            load_initial_queue(&inputs, false, SortOrder::Name, false).expect("queue should load");
         assert_eq!(queue.len(), 2);
-        let first = queue.current().path.clone();
-        assert!(first.ends_with("a_nested.markdown"));
+        let order: Vec<_> = queue
+            .documents()
+            .iter()
+            .map(|doc| doc.path.clone())
+            .collect();
+        assert!(order[0].ends_with("z_single.md"));
+        assert!(order[1].ends_with("a_nested.markdown"));
+    }
+
+    #[test]
+    fn list_prints_discovered_paths_in_queue_order() {
+        let root = tempfile::tempdir().expect("create tempdir");
+        let docs = root.path().join("docs");
+        fs::create_dir_all(docs.join("guide")).expect("create dirs");
+        for name in ["b.md", "a.md", "guide/intro.md", "notes.txt"] {
+            fs::write(docs.join(name), "# doc").expect("write doc");
+        }
+
+        let paths = discover_queue_paths(std::slice::from_ref(&docs), SortOrder::Name, false)
+            .expect("paths should be discovered");
+        let mut out = Vec::new();
+        write_path_list(&mut out, &paths).expect("write list");
+
+        let expected: String = ["a.md", "b.md", "guide/intro.md"]
+            .iter()
+            .map(|name| format!("{}\n", docs.join(name).display()))
+            .collect();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
     }
 }
@@ -1,13 +1,20 @@
+use std::borrow::Cow;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
 use once_cell::sync::Lazy;
-use pulldown_cmark::{CodeBlockKind, Event as MdEvent, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{CodeBlockKind, Event as MdEvent, Options, Parser, Tag, TagEnd, html};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
 use syntect::easy::HighlightLines;
 use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 use syntect::util::LinesWithEndings;
+use unicode_width::UnicodeWidthStr;
 
-use crate::theme::Theme;
+use crate::emoji::replace_shortcodes;
+use crate::theme::{DEFAULT_CODE_THEME, Theme};
+use crate::wrap::wrap_line_ranges;
 
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
 static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
@@ -18,30 +25,147 @@ pub struct LinkTarget {
     pub start_char: usize,
     pub end_char: usize,
     pub url: String,
+    /// False for a local link whose target file is missing; see
+    /// [`mark_broken_links`].
+    pub exists: bool,
 }
 
-pub fn render_markdown_with_links(
-    markdown: &str,
-    table_width: u16,
-    theme: &Theme,
-) -> (Vec<Line<'static>>, Vec<LinkTarget>) {
+pub fn resolve_code_theme(name: &str) -> Result<&'static str, String> {
+    if let Some((key, _)) = THEME_SET.themes.get_key_value(name) {
+        return Ok(key.as_str());
+    }
+    let valid: Vec<&str> = THEME_SET.themes.keys().map(String::as_str).collect();
+    Err(format!(
+        "Unknown code theme: {} (valid themes: {})",
+        name,
+        valid.join(", ")
+    ))
+}
+
+fn markdown_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options
+}
+
+pub fn render_html_document(title: &str, markdown: &str) -> String {
+    let mut body = String::new();
+    html::push_html(&mut body, Parser::new_ext(markdown, markdown_options()));
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape_html(title),
+        body
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineKind {
+    #[default]
+    Prose,
+    Code,
+    Table,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heading {
+    /// 1 for `#` through 6 for `######`.
+    pub level: u8,
+    pub text: String,
+    /// Index into [`RenderedMarkdown::lines`] of the heading's line.
+    pub line_idx: usize,
+}
+
+/// Output of [`render_markdown_with_links`].
+pub struct RenderedMarkdown {
+    pub lines: Vec<Line<'static>>,
+    pub links: Vec<LinkTarget>,
+    /// Parallel to `lines`, marking which lines came from code blocks or tables.
+    pub kinds: Vec<LineKind>,
+    /// Parallel to `lines`: the 0-based source line of the block each
+    /// rendered line belongs to. Non-decreasing.
+    pub source_lines: Vec<usize>,
+    /// Headings in document order.
+    pub headings: Vec<Heading>,
+}
+
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// Columns per tab stop when expanding hard tabs.
+    pub tab_width: usize,
+    /// Replace `:shortcode:` text with emoji.
+    pub emoji_shortcodes: bool,
+    /// Show mermaid/plantuml source instead of a one-line notice.
+    pub diagram_source: bool,
+    /// Reindent one-line (minified) ```json blocks.
+    pub reformat_json: bool,
+    /// Color code blocks with syntect; off draws them in `theme.code`.
+    pub highlight: bool,
+}
 
-    let parser = Parser::new_ext(markdown, options);
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            tab_width: DEFAULT_TAB_WIDTH,
+            emoji_shortcodes: true,
+            diagram_source: false,
+            reformat_json: true,
+            highlight: true,
+        }
+    }
+}
+
+/// Renders markdown into styled lines. Tables and list items are fitted to
+/// `table_width` columns (0 leaves them unwrapped); links are returned with
+/// their line and column ranges.
+pub fn render_markdown_with_links(
+    markdown: &str,
+    table_width: u16,
+    theme: &Theme,
+    options: &RenderOptions,
+) -> RenderedMarkdown {
+    let parser = Parser::new_ext(markdown, markdown_options());
     let mut lines: Vec<Line<'static>> = Vec::new();
     let mut links: Vec<LinkTarget> = Vec::new();
     let mut current: Vec<Span<'static>> = Vec::new();
     let mut current_line_chars: usize = 0;
     let mut list_depth = 0usize;
+    // (prefix width, bullet width) for each open list item, innermost last.
+    let mut item_prefixes: Vec<(usize, usize)> = Vec::new();
+    // Next number for each open list; `None` for bullet lists.
+    let mut list_numbers: Vec<Option<u64>> = Vec::new();
+    // Whether each open list is loose (its items hold paragraphs).
+    let mut list_loose: Vec<bool> = Vec::new();
     let mut in_code_block = false;
-    let mut in_blockquote = false;
+    let mut blockquote_depth = 0usize;
+    // Callout kind of each open blockquote, innermost last.
+    let mut quote_kinds: Vec<Option<AdmonitionKind>> = Vec::new();
+    // Set while dropping the `[!NOTE]` marker line of a callout.
+    let mut skip_admonition_marker = false;
     let mut style_stack: Vec<Style> = Vec::new();
     let mut current_style = Style::default();
     let mut heading_level: Option<u32> = None;
+    let mut headings: Vec<Heading> = Vec::new();
     let code_style = Style::new().fg(theme.code).add_modifier(Modifier::DIM);
     let quote_style = Style::new().fg(theme.quote);
+    let html_style = Style::new().fg(theme.html).add_modifier(Modifier::DIM);
     let mut in_table = false;
     let mut in_table_head = false;
     let mut table_columns: usize = 0;
@@ -58,6 +182,15 @@ pub fn render_markdown_with_links(
         .add_modifier(Modifier::UNDERLINED);
     let mut code_block_language: Option<String> = None;
     let mut code_block_text = String::new();
+    let footnote_style = Style::new().fg(theme.link);
+    let mut footnote_numbers: Vec<String> = Vec::new();
+    let mut footnote_defs: Vec<FootnoteDefinition> = Vec::new();
+    let mut footnote_outer: Option<(String, Vec<Line<'static>>, usize, usize)> = None;
+    let mut kind_ranges: Vec<(Range<usize>, LineKind)> = Vec::new();
+    let line_starts = source_line_starts(markdown);
+    // (source line, first rendered line) for each block start.
+    let mut source_anchors: Vec<(usize, usize)> = Vec::new();
+    let mut pending_anchor: Option<usize> = None;
 
     let flush_line =
         |lines: &mut Vec<Line<'static>>, current: &mut Vec<Span<'static>>, count: &mut usize| {
@@ -82,7 +215,25 @@ pub fn render_markdown_with_links(
         current.push(span);
     };
 
-    for event in parser {
+    for (event, range) in parser.into_offset_iter() {
+        if let Some(source_line) = pending_anchor.take() {
+            source_anchors.push((source_line, lines.len()));
+        }
+        if footnote_outer.is_none()
+            && matches!(
+                event,
+                MdEvent::Start(
+                    Tag::Paragraph
+                        | Tag::Heading { .. }
+                        | Tag::BlockQuote
+                        | Tag::CodeBlock(_)
+                        | Tag::Item
+                        | Tag::Table(_)
+                ) | MdEvent::Rule
+            )
+        {
+            pending_anchor = Some(line_starts.partition_point(|&start| start <= range.start) - 1);
+        }
         if in_table {
             match event {
                 MdEvent::Start(Tag::Table(alignments)) => {
@@ -90,6 +241,7 @@ pub fn render_markdown_with_links(
                 }
                 MdEvent::End(TagEnd::Table) => {
                     flush_line(&mut lines, &mut current, &mut current_line_chars);
+                    let table_start = lines.len();
                     render_table(
                         &mut lines,
                         &table_header,
@@ -97,6 +249,7 @@ pub fn render_markdown_with_links(
                         table_width,
                         table_columns,
                     );
+                    kind_ranges.push((table_start..lines.len(), LineKind::Table));
                     lines.push(Line::raw(""));
                     in_table = false;
                     in_table_head = false;
@@ -151,7 +304,9 @@ pub fn render_markdown_with_links(
                     current_cell.clear();
                 }
                 MdEvent::Text(text) => {
-                    current_cell.push_str(&text);
+                    let column = current_cell.chars().count();
+                    let text = expand_tabs(&text, column, options.tab_width);
+                    current_cell.push_str(&prose_text(text, options));
                 }
                 MdEvent::Code(code) => {
                     if !current_cell.is_empty() {
@@ -171,6 +326,12 @@ pub fn render_markdown_with_links(
         }
 
         match event {
+            MdEvent::Text(_) if skip_admonition_marker => {}
+            MdEvent::SoftBreak | MdEvent::HardBreak | MdEvent::End(TagEnd::Paragraph)
+                if skip_admonition_marker =>
+            {
+                skip_admonition_marker = false;
+            }
             MdEvent::Start(Tag::Table(alignments)) => {
                 in_table = true;
                 in_table_head = false;
@@ -195,6 +356,7 @@ pub fn render_markdown_with_links(
                         start_char: current_link_start,
                         end_char: current_line_chars,
                         url,
+                        exists: true,
                     });
                 }
                 current_link_has_text = false;
@@ -204,48 +366,159 @@ pub fn render_markdown_with_links(
                 heading_level = Some(level as u32);
             }
             MdEvent::End(TagEnd::Heading(_)) => {
+                if let Some(level) = heading_level
+                    && footnote_outer.is_none()
+                    && !current.is_empty()
+                {
+                    headings.push(Heading {
+                        level: level as u8,
+                        text: current
+                            .iter()
+                            .map(|span| span.content.as_ref())
+                            .collect::<String>()
+                            .trim()
+                            .to_string(),
+                        line_idx: lines.len(),
+                    });
+                }
                 flush_line(&mut lines, &mut current, &mut current_line_chars);
                 lines.push(Line::raw(""));
                 heading_level = None;
             }
-            MdEvent::Start(Tag::Paragraph) => {}
+            MdEvent::Start(Tag::Paragraph) => {
+                // Parsers only emit item paragraphs for loose lists.
+                if let Some(loose) = list_loose.last_mut() {
+                    *loose = true;
+                }
+            }
             MdEvent::End(TagEnd::Paragraph) => {
-                flush_line(&mut lines, &mut current, &mut current_line_chars);
+                match item_prefixes.last() {
+                    Some(&(prefix_width, bullet_width)) => flush_list_line(
+                        &mut lines,
+                        &mut current,
+                        &mut current_line_chars,
+                        &mut links,
+                        prefix_width,
+                        bullet_width,
+                        table_width as usize,
+                    ),
+                    // Quoted text wraps under its quote marker, which is
+                    // repeated on every row.
+                    None if blockquote_depth > 0 => flush_list_line(
+                        &mut lines,
+                        &mut current,
+                        &mut current_line_chars,
+                        &mut links,
+                        quote_prefix_width(&quote_kinds),
+                        0,
+                        table_width as usize,
+                    ),
+                    None => flush_line(&mut lines, &mut current, &mut current_line_chars),
+                }
                 lines.push(Line::raw(""));
             }
-            MdEvent::Start(Tag::List(_)) => {
-                push_blank(&mut lines, &mut current_line_chars);
+            MdEvent::Start(Tag::List(first_number)) => {
+                // A nested list continues its item without a gap.
+                if list_depth == 0 {
+                    push_blank(&mut lines, &mut current_line_chars);
+                }
                 list_depth += 1;
+                list_numbers.push(first_number);
+                list_loose.push(false);
             }
             MdEvent::End(TagEnd::List(_)) => {
                 list_depth = list_depth.saturating_sub(1);
-                lines.push(Line::raw(""));
+                list_numbers.pop();
+                list_loose.pop();
+                if list_depth == 0 {
+                    lines.push(Line::raw(""));
+                }
             }
             MdEvent::Start(Tag::Item) => {
-                flush_line(&mut lines, &mut current, &mut current_line_chars);
-                if list_depth > 0 {
+                match item_prefixes.last() {
+                    Some(&(prefix_width, bullet_width)) => flush_list_line(
+                        &mut lines,
+                        &mut current,
+                        &mut current_line_chars,
+                        &mut links,
+                        prefix_width,
+                        bullet_width,
+                        table_width as usize,
+                    ),
+                    None => flush_line(&mut lines, &mut current, &mut current_line_chars),
+                }
+                // Loose items are separated by a blank line, tight ones are not.
+                if list_loose.last() == Some(&true) {
+                    push_blank(&mut lines, &mut current_line_chars);
+                }
+                if blockquote_depth > 0 {
+                    for span in quote_prefix_spans(&quote_kinds, quote_style, theme) {
+                        push_span(&mut current, &mut current_line_chars, span);
+                    }
+                }
+                if list_depth > 1 {
                     push_span(
                         &mut current,
                         &mut current_line_chars,
-                        Span::raw("  ".repeat(list_depth.saturating_sub(1))),
+                        Span::styled(
+                            LIST_GUIDE.repeat(list_depth - 1),
+                            Style::new().fg(theme.list_guide),
+                        ),
                     );
                 }
+                // Ordered lists count up from their source start number.
+                let bullet = match list_numbers.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "- ".to_string(),
+                };
+                let bullet_width = bullet.chars().count();
                 push_span(
                     &mut current,
                     &mut current_line_chars,
-                    Span::styled("- ", Style::new().fg(theme.list_bullet)),
+                    Span::styled(bullet, Style::new().fg(theme.list_bullet)),
                 );
+                item_prefixes.push((current_line_chars, bullet_width));
             }
             MdEvent::End(TagEnd::Item) => {
-                flush_line(&mut lines, &mut current, &mut current_line_chars);
+                let (prefix_width, bullet_width) = item_prefixes.pop().unwrap_or((0, 0));
+                flush_list_line(
+                    &mut lines,
+                    &mut current,
+                    &mut current_line_chars,
+                    &mut links,
+                    prefix_width,
+                    bullet_width,
+                    table_width as usize,
+                );
             }
             MdEvent::Start(Tag::BlockQuote) => {
-                in_blockquote = true;
+                flush_line(&mut lines, &mut current, &mut current_line_chars);
+                blockquote_depth += 1;
+                let kind = markdown[range.clone()]
+                    .lines()
+                    .next()
+                    .and_then(parse_admonition);
+                quote_kinds.push(kind);
+                if let Some(kind) = kind {
+                    skip_admonition_marker = true;
+                    let mut title = quote_prefix_spans(&quote_kinds, quote_style, theme);
+                    title.push(Span::styled(
+                        format!("{} {}", kind.icon(), kind.label()),
+                        Style::new()
+                            .fg(kind.color(theme))
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                    lines.push(Line::from(title));
+                }
             }
             MdEvent::End(TagEnd::BlockQuote) => {
-                in_blockquote = false;
+                blockquote_depth = blockquote_depth.saturating_sub(1);
+                quote_kinds.pop();
                 flush_line(&mut lines, &mut current, &mut current_line_chars);
-                lines.push(Line::raw(""));
+                push_blank(&mut lines, &mut current_line_chars);
             }
             MdEvent::Start(Tag::Emphasis) => {
                 style_stack.push(current_style);
@@ -274,8 +547,8 @@ pub fn render_markdown_with_links(
                 in_code_block = true;
                 code_block_text.clear();
                 code_block_language = match kind {
-                    CodeBlockKind::Fenced(lang) => {
-                        let lang = lang.trim();
+                    CodeBlockKind::Fenced(info) => {
+                        let (lang, _attributes) = split_code_info(&info);
                         if lang.is_empty() {
                             None
                         } else {
@@ -287,12 +560,15 @@ pub fn render_markdown_with_links(
             }
             MdEvent::End(TagEnd::CodeBlock) => {
                 in_code_block = false;
+                let code_start = lines.len();
                 render_code_block(
                     &mut lines,
-                    &code_block_text,
+                    &expand_tabs(&code_block_text, 0, options.tab_width),
                     code_block_language.as_deref(),
                     theme,
+                    options,
                 );
+                kind_ranges.push((code_start..lines.len(), LineKind::Code));
                 code_block_text.clear();
                 code_block_language = None;
                 lines.push(Line::raw(""));
@@ -301,13 +577,15 @@ pub fn render_markdown_with_links(
                 if in_code_block {
                     code_block_text.push_str(&text);
                 } else {
-                    if in_blockquote && current.is_empty() {
-                        push_span(
-                            &mut current,
-                            &mut current_line_chars,
-                            Span::styled("> ", quote_style),
-                        );
+                    if blockquote_depth > 0 && current.is_empty() {
+                        for span in quote_prefix_spans(&quote_kinds, quote_style, theme) {
+                            push_span(&mut current, &mut current_line_chars, span);
+                        }
                     }
+                    let text = prose_text(
+                        expand_tabs(&text, current_line_chars, options.tab_width),
+                        options,
+                    );
                     let mut style = current_style;
                     if let Some(level) = heading_level {
                         style = style.add_modifier(Modifier::BOLD);
@@ -321,33 +599,74 @@ pub fn render_markdown_with_links(
                     }
                     if current_link.is_some() {
                         style = style.patch(link_style);
+                        // A segment starts at its first text, after any
+                        // quote prefix on the line.
+                        if !current_link_has_text {
+                            current_link_line = lines.len();
+                            current_link_start = current_line_chars;
+                        }
                         current_link_has_text = true;
+                        push_span(
+                            &mut current,
+                            &mut current_line_chars,
+                            Span::styled(text.to_string(), style),
+                        );
+                    } else {
+                        let mut last = 0usize;
+                        for (start, end, url) in find_autolinks(&text) {
+                            if start > last {
+                                push_span(
+                                    &mut current,
+                                    &mut current_line_chars,
+                                    Span::styled(text[last..start].to_string(), style),
+                                );
+                            }
+                            let start_char = current_line_chars;
+                            push_span(
+                                &mut current,
+                                &mut current_line_chars,
+                                Span::styled(text[start..end].to_string(), style.patch(link_style)),
+                            );
+                            links.push(LinkTarget {
+                                line_idx: lines.len(),
+                                start_char,
+                                end_char: current_line_chars,
+                                url,
+                                exists: true,
+                            });
+                            last = end;
+                        }
+                        if last < text.len() {
+                            push_span(
+                                &mut current,
+                                &mut current_line_chars,
+                                Span::styled(text[last..].to_string(), style),
+                            );
+                        }
                     }
-                    push_span(
-                        &mut current,
-                        &mut current_line_chars,
-                        Span::styled(text.to_string(), style),
-                    );
                 }
             }
             MdEvent::Code(code) => {
-                if in_blockquote && current.is_empty() {
-                    push_span(
-                        &mut current,
-                        &mut current_line_chars,
-                        Span::styled("> ", quote_style),
-                    );
+                if blockquote_depth > 0 && current.is_empty() {
+                    for span in quote_prefix_spans(&quote_kinds, quote_style, theme) {
+                        push_span(&mut current, &mut current_line_chars, span);
+                    }
                 }
-                let mut style = code_style;
-                if current_link.is_some() {
-                    style = style.patch(link_style);
+                // Code keeps any enclosing emphasis. Inside a link it also keeps
+                // its code color, gains the link underline, and drops the
+                // backticks so the link reads cleanly.
+                let style = current_style.patch(code_style);
+                let span = if current_link.is_some() {
+                    if !current_link_has_text {
+                        current_link_line = lines.len();
+                        current_link_start = current_line_chars;
+                    }
                     current_link_has_text = true;
-                }
-                push_span(
-                    &mut current,
-                    &mut current_line_chars,
-                    Span::styled(format!("`{}`", code), style),
-                );
+                    Span::styled(code.to_string(), style.add_modifier(Modifier::UNDERLINED))
+                } else {
+                    Span::styled(format!("`{}`", code), style)
+                };
+                push_span(&mut current, &mut current_line_chars, span);
             }
             MdEvent::SoftBreak => {
                 if in_code_block {
@@ -360,9 +679,99 @@ pub fn render_markdown_with_links(
                 if in_code_block {
                     code_block_text.push('\n');
                 } else {
+                    // Close the link's segment on this line; the text after
+                    // the break starts a new one.
+                    if let Some(url) = &current_link
+                        && current_link_has_text
+                    {
+                        links.push(LinkTarget {
+                            line_idx: current_link_line,
+                            start_char: current_link_start,
+                            end_char: current_line_chars,
+                            url: url.clone(),
+                            exists: true,
+                        });
+                        current_link_has_text = false;
+                    }
+                    if blockquote_depth > 0 && item_prefixes.is_empty() {
+                        flush_list_line(
+                            &mut lines,
+                            &mut current,
+                            &mut current_line_chars,
+                            &mut links,
+                            quote_prefix_width(&quote_kinds),
+                            0,
+                            table_width as usize,
+                        );
+                    } else {
+                        flush_line(&mut lines, &mut current, &mut current_line_chars);
+                    }
+                }
+            }
+            MdEvent::FootnoteReference(label) => {
+                let number = footnote_number(&mut footnote_numbers, &label);
+                push_span(
+                    &mut current,
+                    &mut current_line_chars,
+                    Span::styled(format!("[{}]", number), footnote_style),
+                );
+            }
+            MdEvent::Start(Tag::FootnoteDefinition(label)) => {
+                flush_line(&mut lines, &mut current, &mut current_line_chars);
+                let outer = std::mem::take(&mut lines);
+                footnote_outer = Some((label.to_string(), outer, links.len(), kind_ranges.len()));
+            }
+            MdEvent::End(TagEnd::FootnoteDefinition) => {
+                flush_line(&mut lines, &mut current, &mut current_line_chars);
+                if let Some((label, outer, first_link, first_kind)) = footnote_outer.take() {
+                    let mut body = std::mem::replace(&mut lines, outer);
+                    while body.last().is_some_and(|line| line.spans.is_empty()) {
+                        body.pop();
+                    }
+                    footnote_defs.push(FootnoteDefinition {
+                        label,
+                        lines: body,
+                        links: links.split_off(first_link),
+                        kinds: kind_ranges.split_off(first_kind),
+                    });
+                }
+            }
+            MdEvent::Start(Tag::HtmlBlock) => {
+                flush_line(&mut lines, &mut current, &mut current_line_chars);
+                push_blank(&mut lines, &mut current_line_chars);
+            }
+            MdEvent::End(TagEnd::HtmlBlock) => {
+                flush_line(&mut lines, &mut current, &mut current_line_chars);
+                push_blank(&mut lines, &mut current_line_chars);
+            }
+            MdEvent::Html(html) => {
+                for raw in html.lines() {
+                    push_span(
+                        &mut current,
+                        &mut current_line_chars,
+                        Span::styled(raw.to_string(), html_style),
+                    );
                     flush_line(&mut lines, &mut current, &mut current_line_chars);
                 }
             }
+            MdEvent::InlineHtml(html) => match html_tag(&html)
+                .as_ref()
+                .map(|(closing, name)| (*closing, name.as_str()))
+            {
+                Some((_, "br")) => flush_line(&mut lines, &mut current, &mut current_line_chars),
+                Some((false, "kbd")) => {
+                    style_stack.push(current_style);
+                    current_style = current_style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                }
+                Some((true, "kbd")) => {
+                    current_style = style_stack.pop().unwrap_or_default();
+                }
+                _ => push_span(
+                    &mut current,
+                    &mut current_line_chars,
+                    Span::styled(html.to_string(), html_style),
+                ),
+            },
             MdEvent::Rule => {
                 push_blank(&mut lines, &mut current_line_chars);
                 lines.push(Line::from(Span::styled(
@@ -376,16 +785,595 @@ pub fn render_markdown_with_links(
     }
 
     flush_line(&mut lines, &mut current, &mut current_line_chars);
-    (lines, links)
+    render_footnotes(
+        &mut lines,
+        &mut links,
+        &mut kind_ranges,
+        footnote_defs,
+        &mut footnote_numbers,
+        theme,
+    );
+
+    let mut kinds = vec![LineKind::Prose; lines.len()];
+    for (range, kind) in kind_ranges {
+        for slot in kinds.iter_mut().take(range.end).skip(range.start) {
+            *slot = kind;
+        }
+    }
+    let mut source_lines = Vec::with_capacity(lines.len());
+    let mut anchors = source_anchors.into_iter().peekable();
+    let mut source_line = 0;
+    for idx in 0..lines.len() {
+        while let Some((line, _)) = anchors.next_if(|&(_, first)| first <= idx) {
+            source_line = line;
+        }
+        source_lines.push(source_line);
+    }
+    let mut rendered = RenderedMarkdown {
+        lines,
+        links,
+        kinds,
+        source_lines,
+        headings,
+    };
+    collapse_blank_lines(&mut rendered);
+    rendered
 }
 
-pub fn render_plain_lines(markdown: &str) -> Vec<Line<'static>> {
-    markdown
+/// Collapses runs of blank lines to one and drops trailing blank lines,
+/// moving links and headings to the shifted line indices.
+fn collapse_blank_lines(rendered: &mut RenderedMarkdown) {
+    let is_blank = |line: &Line| line.spans.iter().all(|span| span.content.is_empty());
+    let mut keep: Vec<bool> = Vec::with_capacity(rendered.lines.len());
+    let mut previous_blank = false;
+    for line in &rendered.lines {
+        let blank = is_blank(line);
+        keep.push(!(blank && previous_blank));
+        previous_blank = blank;
+    }
+    for (line, kept) in rendered.lines.iter().zip(keep.iter_mut()).rev() {
+        if !is_blank(line) {
+            break;
+        }
+        *kept = false;
+    }
+    if keep.iter().all(|&kept| kept) {
+        return;
+    }
+
+    // `new_index[old]` is the number of kept lines before `old`, which is
+    // also where a removed line's content would have landed.
+    let mut new_index = Vec::with_capacity(keep.len());
+    let mut kept = 0;
+    for &kept_line in &keep {
+        new_index.push(kept);
+        kept += usize::from(kept_line);
+    }
+    let remap = |idx: usize| new_index.get(idx).copied().unwrap_or(kept);
+    for link in &mut rendered.links {
+        link.line_idx = remap(link.line_idx);
+    }
+    for heading in &mut rendered.headings {
+        heading.line_idx = remap(heading.line_idx);
+    }
+    let mut flags = keep.iter();
+    rendered.lines.retain(|_| *flags.next().unwrap_or(&true));
+    let mut flags = keep.iter();
+    rendered.kinds.retain(|_| *flags.next().unwrap_or(&true));
+    let mut flags = keep.iter();
+    rendered
+        .source_lines
+        .retain(|_| *flags.next().unwrap_or(&true));
+}
+
+/// Drawn once per enclosing list level ahead of a nested item's bullet.
+const LIST_GUIDE: &str = "│  ";
+
+/// Narrowest text column worth hanging list continuation rows under; below
+/// this the item is left for the viewport to wrap.
+const MIN_LIST_BODY_WIDTH: usize = 10;
+
+/// Flushes a list item line, pre-wrapping it to `width` so continuation rows
+/// are indented by `prefix_width` and hang under the item text instead of
+/// restarting at column 0. Links on the line are moved to their new rows.
+/// Quoted lines pass a `bullet_width` of 0 so the whole quote prefix is
+/// repeated on each row.
+fn flush_list_line(
+    lines: &mut Vec<Line<'static>>,
+    current: &mut Vec<Span<'static>>,
+    count: &mut usize,
+    links: &mut Vec<LinkTarget>,
+    prefix_width: usize,
+    bullet_width: usize,
+    width: usize,
+) {
+    *count = 0;
+    if current.is_empty() {
+        return;
+    }
+    let line = Line::from(std::mem::take(current));
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let body_width = width.saturating_sub(prefix_width);
+    if body_width < MIN_LIST_BODY_WIDTH || UnicodeWidthStr::width(text.as_str()) <= width {
+        lines.push(line);
+        return;
+    }
+
+    let body: Vec<char> = text.chars().skip(prefix_width).collect();
+    let body_text: String = body.iter().collect();
+    let mut rows = wrap_line_ranges(&body_text, body_width).rows;
+    for row in rows.iter_mut().skip(1) {
+        while row.start < row.end && body[row.start].is_whitespace() {
+            row.start += 1;
+        }
+    }
+
+    let line_idx = lines.len();
+    // Char range of each row within the original line.
+    let row_spans: Vec<(usize, usize)> = rows
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            let start = if idx == 0 {
+                0
+            } else {
+                prefix_width + row.start
+            };
+            (start, prefix_width + row.end)
+        })
+        .collect();
+    // Continuation rows repeat the guides (everything before the bullet).
+    let guides = slice_spans(&line.spans, 0, prefix_width.saturating_sub(bullet_width));
+    for (idx, &(start, end)) in row_spans.iter().enumerate() {
+        let mut spans = Vec::new();
+        if idx > 0 {
+            spans.extend(guides.iter().cloned());
+            spans.push(Span::raw(" ".repeat(prefix_width.min(bullet_width))));
+        }
+        spans.extend(slice_spans(&line.spans, start, end));
+        lines.push(Line::from(spans));
+    }
+
+    // Links on this line were pushed last; split each into one segment per
+    // row it covers so every row stays clickable.
+    let first = links
+        .iter()
+        .position(|link| link.line_idx == line_idx)
+        .unwrap_or(links.len());
+    for link in links.split_off(first) {
+        if link.line_idx != line_idx {
+            links.push(link);
+            continue;
+        }
+        for (row, &(start, end)) in row_spans.iter().enumerate() {
+            let seg_start = link.start_char.max(start);
+            let seg_end = link.end_char.min(end);
+            if seg_start >= seg_end {
+                continue;
+            }
+            let indent = if row == 0 { 0 } else { prefix_width };
+            links.push(LinkTarget {
+                line_idx: line_idx + row,
+                start_char: seg_start - start + indent,
+                end_char: seg_end - start + indent,
+                url: link.url.clone(),
+                exists: link.exists,
+            });
+        }
+    }
+}
+
+/// GitHub-style anchor for a heading: lowercase, punctuation dropped and
+/// spaces turned into hyphens (`Getting Started!` → `getting-started`).
+pub fn heading_slug(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|ch| match ch {
+            ' ' => Some('-'),
+            '-' | '_' => Some(ch),
+            _ if ch.is_alphanumeric() => Some(ch),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Flags local links whose target is missing, resolving relative targets
+/// against `base_dir`, and strikes them through in `theme.broken_link`.
+pub fn mark_broken_links(rendered: &mut RenderedMarkdown, base_dir: &Path, theme: &Theme) {
+    let broken_style = Style::new()
+        .fg(theme.broken_link)
+        .add_modifier(Modifier::CROSSED_OUT);
+    for link in &mut rendered.links {
+        let Some(target) = local_link_path(&link.url, base_dir) else {
+            continue;
+        };
+        link.exists = target.exists();
+        if link.exists {
+            continue;
+        }
+        if let Some(line) = rendered.lines.get_mut(link.line_idx) {
+            let total: usize = line
+                .spans
+                .iter()
+                .map(|span| span.content.chars().count())
+                .sum();
+            let mut spans = slice_spans(&line.spans, 0, link.start_char);
+            spans.extend(
+                slice_spans(&line.spans, link.start_char, link.end_char)
+                    .into_iter()
+                    .map(|span| {
+                        let style = span.style.patch(broken_style);
+                        span.style(style)
+                    }),
+            );
+            spans.extend(slice_spans(&line.spans, link.end_char, total));
+            line.spans = spans;
+        }
+    }
+}
+
+/// Filesystem path a link points at, or `None` for URLs with a scheme,
+/// in-page anchors and empty targets.
+fn local_link_path(url: &str, base_dir: &Path) -> Option<PathBuf> {
+    if let Some((scheme, _)) = url.split_once(':')
+        && scheme.len() > 1
+        && scheme
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '+' | '-' | '.'))
+    {
+        return None;
+    }
+    let target = url.split(['#', '?']).next().unwrap_or("");
+    if target.is_empty() {
+        return None;
+    }
+    Some(base_dir.join(percent_decode(target)))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = bytes
+            .get(idx + 1..idx + 3)
+            .and_then(|pair| std::str::from_utf8(pair).ok())
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok());
+        match (bytes[idx], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                idx += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8(out).unwrap_or_else(|_| text.to_string())
+}
+
+/// Applies the text substitutions enabled in `options` to prose (never code).
+fn prose_text(text: String, options: &RenderOptions) -> String {
+    if options.emoji_shortcodes && text.contains(':') {
+        replace_shortcodes(&text)
+    } else {
+        text
+    }
+}
+
+/// Spans covering chars `start..end` of the concatenated span text.
+fn slice_spans(spans: &[Span<'static>], start: usize, end: usize) -> Vec<Span<'static>> {
+    let mut out = Vec::new();
+    let mut offset = 0usize;
+    for span in spans {
+        let len = span.content.chars().count();
+        let from = start.max(offset);
+        let to = end.min(offset + len);
+        if from < to {
+            let text: String = span
+                .content
+                .chars()
+                .skip(from - offset)
+                .take(to - from)
+                .collect();
+            out.push(Span::styled(text, span.style));
+        }
+        offset += len;
+    }
+    out
+}
+
+/// Parses an HTML tag into `(is_closing, lowercase name)`; `None` for
+/// comments, declarations and anything that is not a tag.
+fn html_tag(html: &str) -> Option<(bool, String)> {
+    let inner = html.trim().strip_prefix('<')?;
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, inner),
+    };
+    let name: String = inner
+        .chars()
+        .take_while(|ch| ch.is_ascii_alphanumeric())
+        .collect();
+    if name.is_empty() {
+        return None;
+    }
+    Some((closing, name.to_ascii_lowercase()))
+}
+
+/// Byte offsets at which each source line starts.
+fn source_line_starts(markdown: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(markdown.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect()
+}
+
+struct FootnoteDefinition {
+    label: String,
+    lines: Vec<Line<'static>>,
+    links: Vec<LinkTarget>,
+    kinds: Vec<(Range<usize>, LineKind)>,
+}
+
+fn footnote_number(numbers: &mut Vec<String>, label: &str) -> usize {
+    if let Some(idx) = numbers.iter().position(|known| known == label) {
+        return idx + 1;
+    }
+    numbers.push(label.to_string());
+    numbers.len()
+}
+
+fn render_footnotes(
+    lines: &mut Vec<Line<'static>>,
+    links: &mut Vec<LinkTarget>,
+    kind_ranges: &mut Vec<(Range<usize>, LineKind)>,
+    mut defs: Vec<FootnoteDefinition>,
+    numbers: &mut Vec<String>,
+    theme: &Theme,
+) {
+    if defs.is_empty() {
+        return;
+    }
+    for def in &defs {
+        footnote_number(numbers, &def.label);
+    }
+    defs.sort_by_key(|def| footnote_number(numbers, &def.label));
+
+    if lines.last().is_some_and(|line| !line.spans.is_empty()) {
+        lines.push(Line::raw(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "-".repeat(32),
+        Style::new().fg(theme.rule),
+    )));
+    lines.push(Line::from(Span::styled(
+        "Footnotes",
+        Style::new().fg(theme.heading).add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::raw(""));
+
+    let marker_style = Style::new().fg(theme.link);
+    for def in defs {
+        let marker = format!("[{}] ", footnote_number(numbers, &def.label));
+        let marker_len = marker.chars().count();
+        let base = lines.len();
+        for mut link in def.links {
+            if link.line_idx == 0 {
+                link.start_char += marker_len;
+                link.end_char += marker_len;
+            }
+            link.line_idx += base;
+            links.push(link);
+        }
+        for (range, kind) in def.kinds {
+            kind_ranges.push((range.start + base..range.end + base, kind));
+        }
+        let mut body = def.lines.into_iter();
+        let mut first = vec![Span::styled(marker, marker_style)];
+        if let Some(line) = body.next() {
+            first.extend(line.spans);
+        }
+        lines.push(Line::from(first));
+        lines.extend(body);
+    }
+}
+
+fn find_autolinks(text: &str) -> Vec<(usize, usize, String)> {
+    let mut found = Vec::new();
+    let mut token_start: Option<usize> = None;
+    for (idx, ch) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        if !ch.is_whitespace() {
+            token_start.get_or_insert(idx);
+            continue;
+        }
+        let Some(start) = token_start.take() else {
+            continue;
+        };
+        let token = &text[start..idx];
+        let trimmed_start = token.trim_start_matches(['(', '<', '[', '"', '\'']);
+        let candidate = trimmed_start
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '>', ']', '"', '\'']);
+        let begin = start + (token.len() - trimmed_start.len());
+        let end = begin + candidate.len();
+        if let Some(url) = autolink_url(candidate) {
+            found.push((begin, end, url));
+        }
+    }
+    found
+}
+
+fn autolink_url(candidate: &str) -> Option<String> {
+    for scheme in ["https://", "http://"] {
+        if candidate.len() > scheme.len() && candidate.starts_with(scheme) {
+            return Some(candidate.to_string());
+        }
+    }
+    if let Some(address) = candidate.strip_prefix("mailto:") {
+        return is_email(address).then(|| candidate.to_string());
+    }
+    is_email(candidate).then(|| format!("mailto:{}", candidate))
+}
+
+fn is_email(candidate: &str) -> bool {
+    let Some((local, domain)) = candidate.split_once('@') else {
+        return false;
+    };
+    let valid_local = !local.is_empty()
+        && local
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "._%+-".contains(ch));
+    let valid_domain = domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && domain
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '.' || ch == '-');
+    valid_local && valid_domain
+}
+
+fn quote_prefix(depth: usize) -> String {
+    format!("{} ", ">".repeat(depth))
+}
+
+/// Line prefix inside blockquotes: `>` per level, with callout levels drawn
+/// as a bar in their color.
+fn quote_prefix_spans(
+    kinds: &[Option<AdmonitionKind>],
+    quote_style: Style,
+    theme: &Theme,
+) -> Vec<Span<'static>> {
+    if kinds.iter().all(Option::is_none) {
+        return vec![Span::styled(quote_prefix(kinds.len()), quote_style)];
+    }
+    let mut spans: Vec<Span<'static>> = kinds
+        .iter()
+        .map(|kind| match kind {
+            Some(kind) => Span::styled("▌", Style::new().fg(kind.color(theme))),
+            None => Span::styled(">", quote_style),
+        })
+        .collect();
+    spans.push(Span::raw(" "));
+    spans
+}
+
+/// Width in chars of the prefix [`quote_prefix_spans`] draws: one marker
+/// per level and a space.
+fn quote_prefix_width(kinds: &[Option<AdmonitionKind>]) -> usize {
+    kinds.len() + 1
+}
+
+/// GitHub callout types, written `> [!NOTE]` on a quote's first line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdmonitionKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl AdmonitionKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AdmonitionKind::Note => "Note",
+            AdmonitionKind::Tip => "Tip",
+            AdmonitionKind::Important => "Important",
+            AdmonitionKind::Warning => "Warning",
+            AdmonitionKind::Caution => "Caution",
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            AdmonitionKind::Note => "ℹ",
+            AdmonitionKind::Tip => "✦",
+            AdmonitionKind::Important => "❢",
+            AdmonitionKind::Warning => "⚠",
+            AdmonitionKind::Caution => "⛒",
+        }
+    }
+
+    pub fn color(self, theme: &Theme) -> Color {
+        match self {
+            AdmonitionKind::Note => theme.callout_note,
+            AdmonitionKind::Tip => theme.callout_tip,
+            AdmonitionKind::Important => theme.callout_important,
+            AdmonitionKind::Warning => theme.callout_warning,
+            AdmonitionKind::Caution => theme.callout_caution,
+        }
+    }
+}
+
+/// Reads a callout marker such as `[!NOTE]` (optionally after `>`). The
+/// marker must be alone on the line; case is ignored.
+pub fn parse_admonition(first_line: &str) -> Option<AdmonitionKind> {
+    let marker = first_line.trim().trim_start_matches('>').trim();
+    let name = marker.strip_prefix("[!")?.strip_suffix(']')?;
+    match name.to_ascii_uppercase().as_str() {
+        "NOTE" => Some(AdmonitionKind::Note),
+        "TIP" => Some(AdmonitionKind::Tip),
+        "IMPORTANT" => Some(AdmonitionKind::Important),
+        "WARNING" => Some(AdmonitionKind::Warning),
+        "CAUTION" => Some(AdmonitionKind::Caution),
+        _ => None,
+    }
+}
+
+/// Returns the raw markdown source as unstyled lines, with tabs expanded.
+pub fn render_plain_lines(markdown: &str, options: &RenderOptions) -> Vec<Line<'static>> {
+    normalize_line_endings(markdown)
         .lines()
-        .map(|line| Line::raw(line.to_string()))
+        .map(|line| Line::raw(expand_tabs(line, 0, options.tab_width)))
         .collect()
 }
 
+/// Converts `\r\n` and bare `\r` line endings to `\n`.
+pub fn normalize_line_endings(text: &str) -> Cow<'_, str> {
+    if text.contains('\r') {
+        Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Replaces tabs with spaces up to the next tab stop, counting columns from
+/// `start_col` so text appended mid-line stays aligned.
+fn expand_tabs(text: &str, start_col: usize, tab_width: usize) -> String {
+    if !text.contains('\t') {
+        return text.to_string();
+    }
+    let tab_width = tab_width.max(1);
+    let mut out = String::with_capacity(text.len());
+    let mut col = start_col;
+    for ch in text.chars() {
+        match ch {
+            '\t' => {
+                let spaces = tab_width - col % tab_width;
+                out.extend(std::iter::repeat_n(' ', spaces));
+                col += spaces;
+            }
+            '\n' => {
+                out.push(ch);
+                col = 0;
+            }
+            _ => {
+                out.push(ch);
+                col += 1;
+            }
+        }
+    }
+    out
+}
+
 pub fn estimate_rendered_lines(lines: &[Line<'static>], width: u16) -> u16 {
     if width == 0 {
         return 0;
@@ -393,8 +1381,12 @@ pub fn estimate_rendered_lines(lines: &[Line<'static>], width: u16) -> u16 {
     let width = width as usize;
     let mut total: usize = 0;
     for line in lines {
-        let line_width = line.width().max(1);
-        let wrapped = line_width.div_ceil(width);
+        let text: String = line
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        let wrapped = wrap_line_ranges(&text, width).rows.len();
         total = total.saturating_add(wrapped.max(1));
     }
     total.min(u16::MAX as usize) as u16
@@ -414,14 +1406,18 @@ fn render_table(
     if col_count == 0 {
         return;
     }
+    if use_stacked_table(col_count, max_width) {
+        render_stacked_table(lines, header, rows, col_count);
+        return;
+    }
 
     let mut widths = vec![0usize; col_count];
     for (idx, cell) in header.iter().enumerate() {
-        widths[idx] = widths[idx].max(cell.chars().count());
+        widths[idx] = widths[idx].max(UnicodeWidthStr::width(cell.as_str()));
     }
     for row in rows {
         for (idx, cell) in row.iter().enumerate() {
-            widths[idx] = widths[idx].max(cell.chars().count());
+            widths[idx] = widths[idx].max(UnicodeWidthStr::width(cell.as_str()));
         }
     }
 
@@ -445,7 +1441,7 @@ fn render_table(
                     .get(line_idx)
                     .map(String::as_str)
                     .unwrap_or("");
-                let pad = width.saturating_sub(cell_line.chars().count());
+                let pad = width.saturating_sub(UnicodeWidthStr::width(cell_line));
                 out.push(' ');
                 out.push_str(cell_line);
                 out.push_str(&" ".repeat(pad));
@@ -477,6 +1473,44 @@ fn render_table(
     }
 }
 
+/// Narrowest content width a grid column may be squeezed to before the table
+/// is rendered as stacked `Header: value` blocks instead.
+const MIN_TABLE_COLUMN_WIDTH: usize = 6;
+
+fn use_stacked_table(col_count: usize, max_width: u16) -> bool {
+    if max_width == 0 || col_count == 0 {
+        return false;
+    }
+    let needed = 1 + col_count * (3 + MIN_TABLE_COLUMN_WIDTH);
+    needed > max_width as usize
+}
+
+fn render_stacked_table(
+    lines: &mut Vec<Line<'static>>,
+    header: &[String],
+    rows: &[Vec<String>],
+    col_count: usize,
+) {
+    let label_style = Style::new().add_modifier(Modifier::BOLD);
+    for (row_idx, row) in rows.iter().enumerate() {
+        if row_idx > 0 {
+            lines.push(Line::raw(""));
+        }
+        for col in 0..col_count {
+            let label = header
+                .get(col)
+                .filter(|label| !label.is_empty())
+                .cloned()
+                .unwrap_or_else(|| format!("Column {}", col + 1));
+            let value = row.get(col).cloned().unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}: ", label), label_style),
+                Span::raw(value),
+            ]));
+        }
+    }
+}
+
 fn fit_table_widths(widths: Vec<usize>, col_count: usize, max_width: u16) -> Vec<usize> {
     if max_width == 0 || col_count == 0 {
         return widths;
@@ -523,22 +1557,18 @@ fn wrap_cell(cell: &str, width: usize) -> Vec<String> {
     if width == 0 {
         return vec![String::new()];
     }
-    let mut lines: Vec<String> = Vec::new();
-    let mut current = String::new();
-    let mut current_len = 0usize;
-
-    for ch in cell.chars() {
-        if current_len == width {
-            lines.push(std::mem::take(&mut current));
-            current_len = 0;
-        }
-        current.push(ch);
-        current_len += 1;
-    }
-    if !current.is_empty() || lines.is_empty() {
-        lines.push(current);
-    }
-    lines
+    let chars: Vec<char> = cell.chars().collect();
+    wrap_line_ranges(cell, width)
+        .rows
+        .iter()
+        .map(|row| {
+            chars[row.start..row.end]
+                .iter()
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .collect()
 }
 
 fn pad_row(row: &mut Vec<String>, columns: usize) {
@@ -548,26 +1578,87 @@ fn pad_row(row: &mut Vec<String>, columns: usize) {
     row.extend(std::iter::repeat_n(String::new(), columns - row.len()));
 }
 
+/// Splits a fence info string such as `rust,no_run` or
+/// `python {.line-numbers}` into the language token and the remaining
+/// attributes.
+fn split_code_info(info: &str) -> (&str, &str) {
+    let info = info.trim();
+    let end = info
+        .find(|ch: char| ch.is_whitespace() || ch == ',' || ch == '{')
+        .unwrap_or(info.len());
+    let attributes = info[end..].trim_start().trim_start_matches(',').trim();
+    (&info[..end], attributes)
+}
+
 fn render_code_block(
     lines: &mut Vec<Line<'static>>,
     code: &str,
     language: Option<&str>,
-    _theme: &Theme,
+    theme: &Theme,
+    options: &RenderOptions,
 ) {
     if code.is_empty() {
         return;
     }
+    let border = Style::new().fg(theme.code);
+    if !options.diagram_source
+        && let Some(lang) = language.filter(|lang| is_diagram_language(lang))
+    {
+        let count = code.lines().count();
+        let noun = if count == 1 { "line" } else { "lines" };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "[{} diagram — {} {}, press D to show source]",
+                lang, count, noun
+            ),
+            border.add_modifier(Modifier::ITALIC),
+        )));
+        return;
+    }
+    if let Some(lang) = language {
+        let mut header = vec![Span::styled(
+            format!("─── {} ───", lang),
+            border.add_modifier(Modifier::DIM),
+        )];
+        if options.highlight && !is_known_language(lang) {
+            header.push(Span::styled(
+                format!(" (no highlighter for '{}')", lang),
+                border.add_modifier(Modifier::DIM | Modifier::ITALIC),
+            ));
+        }
+        lines.push(Line::from(header));
+    }
     let fallback = Style::new().fg(Color::Rgb(230, 230, 230));
-    let syntax = language
-        .and_then(|lang| SYNTAX_SET.find_syntax_by_token(lang))
-        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-    let syn_theme = &THEME_SET.themes["base16-ocean.dark"];
-    let mut highlighter = HighlightLines::new(syntax, syn_theme);
+    // Only touch the syntect sets when highlighting, so they never load
+    // with `--no-highlight`.
+    let mut highlighter = options.highlight.then(|| {
+        let syntax = language
+            .and_then(|lang| SYNTAX_SET.find_syntax_by_token(lang))
+            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+        let syn_theme = THEME_SET
+            .themes
+            .get(theme.code_theme)
+            .unwrap_or_else(|| &THEME_SET.themes[DEFAULT_CODE_THEME]);
+        HighlightLines::new(syntax, syn_theme)
+    });
 
-    for line in LinesWithEndings::from(code) {
+    let mut code = normalize_line_endings(code);
+    if options.reformat_json
+        && language.is_some_and(|lang| lang.eq_ignore_ascii_case("json"))
+        && code.trim().lines().count() == 1
+        && let Some(pretty) = reformat_json(&code)
+    {
+        code = Cow::Owned(pretty);
+    }
+    for line in LinesWithEndings::from(&code) {
         let line_input = line.trim_end_matches('\n');
         let mut spans: Vec<Span<'static>> = Vec::new();
-        spans.push(Span::styled("    ", fallback));
+        spans.push(Span::styled("│ ", border));
+        let Some(highlighter) = highlighter.as_mut() else {
+            spans.push(Span::styled(line_input.to_string(), border));
+            lines.push(Line::from(spans));
+            continue;
+        };
         if let Ok(ranges) = highlighter.highlight_line(line_input, &SYNTAX_SET) {
             for (style, text) in ranges {
                 let fg = Color::Rgb(style.foreground.r, style.foreground.g, style.foreground.b);
@@ -580,5 +1671,28 @@ fn render_code_block(
     }
 }
 
+/// Pretty-prints `text` as JSON with two-space indentation, keeping key
+/// order. Returns `None` when it does not parse.
+pub fn reformat_json(text: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// Whether a fence language has a syntax to highlight with. Plain-text
+/// names count as known since they are not meant to be highlighted.
+pub fn is_known_language(lang: &str) -> bool {
+    ["text", "txt", "plain", "plaintext"]
+        .iter()
+        .any(|name| lang.eq_ignore_ascii_case(name))
+        || SYNTAX_SET.find_syntax_by_token(lang).is_some()
+}
+
+/// Fence languages for diagrams a terminal cannot draw.
+fn is_diagram_language(lang: &str) -> bool {
+    ["mermaid", "plantuml", "puml"]
+        .iter()
+        .any(|name| lang.eq_ignore_ascii_case(name))
+}
+
 #[cfg(test)]
 mod tests;
@@ -23,12 +23,870 @@ code
 | 1 | 2 |
 "#;
     let theme = Theme::pastel();
-    let lines = render_markdown_with_links(md, 80, &theme).0;
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
     let text: Vec<String> = lines.iter().map(line_text).collect();
 
     assert!(text.iter().any(|line| line == "Title"));
     assert!(text.iter().any(|line| line.contains("- item")));
-    assert!(text.iter().any(|line| line.contains("    code")));
+    assert!(text.iter().any(|line| line.contains("│ code")));
     assert!(text.iter().any(|line| line.contains("| A | B |")));
     assert!(text.iter().any(|line| line.contains("| 1 | 2 |")));
 }
+
+#[test]
+fn html_export_contains_headings_tables_and_code_language() {
+    let md = "# Title\n\n| A | B |\n| - | - |\n| 1 | 2 |\n\n```rust\nfn main() {}\n```\n";
+    let html = render_html_document("doc <1>", md);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>doc &lt;1&gt;</title>"));
+    assert!(html.contains("<h1>Title</h1>"));
+    assert!(html.contains("<table>"));
+    assert!(html.contains("class=\"language-rust\""));
+}
+
+#[test]
+fn estimated_rows_follow_word_wrapping() {
+    let text = "aaa bbb ccc ddd eee fff";
+    let lines = vec![Line::raw(text)];
+    let expected = wrap_line_ranges(text, 6).rows.len();
+
+    assert_eq!(expected, 6);
+    assert_eq!(estimate_rendered_lines(&lines, 6) as usize, expected);
+}
+
+#[test]
+fn table_cells_wrap_on_word_boundaries() {
+    assert_eq!(wrap_cell("hello world", 6), vec!["hello", "world"]);
+    assert_eq!(wrap_cell("abcdefgh", 3), vec!["abc", "def", "gh"]);
+    assert_eq!(wrap_cell("", 4), vec![""]);
+}
+
+#[test]
+fn nested_blockquotes_show_depth_prefixes() {
+    let md = "> outer\n>\n> > inner\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+
+    assert!(text.iter().any(|line| line == "> outer"));
+    assert!(text.iter().any(|line| line == ">> inner"));
+}
+
+#[test]
+fn wrapped_quote_rows_repeat_the_quote_marker() {
+    let md = "> a quoted paragraph that runs past the width\n>\n> > nested quote that also wraps\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 20, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+
+    assert_eq!(
+        text,
+        [
+            "> a quoted paragraph",
+            "> that runs past ",
+            "> the width",
+            "",
+            ">> nested quote that",
+            ">> also wraps",
+        ]
+    );
+    assert_eq!(lines[1].spans[0].style.fg, Some(theme.quote));
+}
+
+#[test]
+fn footnotes_render_inline_marker_and_trailing_definition() {
+    let md = "Claim[^note] made.\n\n[^note]: Supporting definition.\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+
+    assert!(text.iter().any(|line| line == "Claim[1] made."));
+    assert!(text.iter().any(|line| line == "Footnotes"));
+    assert_eq!(
+        text.iter().rev().find(|line| !line.is_empty()).unwrap(),
+        "[1] Supporting definition."
+    );
+}
+
+#[test]
+fn bare_urls_and_emails_become_link_targets() {
+    let md = "Visit https://example.com, or mail me@example.org.\n";
+    let theme = Theme::pastel();
+    let RenderedMarkdown { lines, links, .. } =
+        render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    let text = line_text(&lines[0]);
+
+    assert_eq!(links.len(), 2);
+    assert_eq!(links[0].url, "https://example.com");
+    assert_eq!(links[0].line_idx, 0);
+    assert_eq!((links[0].start_char, links[0].end_char), (6, 25));
+    assert_eq!(links[1].url, "mailto:me@example.org");
+    let mail: String = text
+        .chars()
+        .skip(links[1].start_char)
+        .take(links[1].end_char - links[1].start_char)
+        .collect();
+    assert_eq!(mail, "me@example.org");
+}
+
+#[test]
+fn fenced_code_block_shows_language_header() {
+    let md = "```rust\nfn main() {}\n```\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+
+    let header = text.iter().position(|line| line.contains("rust")).unwrap();
+    assert!(text[header].starts_with("───"));
+    assert_eq!(text[header + 1], "│ fn main() {}");
+}
+
+#[test]
+fn recognizes_fence_languages_with_a_syntax() {
+    assert!(is_known_language("rust"));
+    assert!(is_known_language("Python"));
+    assert!(is_known_language("sh"));
+    assert!(is_known_language("text"));
+    assert!(!is_known_language("zig"));
+    assert!(!is_known_language("no-such-lang"));
+}
+
+#[test]
+fn unknown_fence_languages_note_the_missing_highlighter() {
+    let md = "```zig\nconst x = 1;\n```\n\n```rust\nlet x = 1;\n```\n";
+    let theme = Theme::pastel();
+    let text: Vec<String> = render_markdown_with_links(md, 80, &theme, &RenderOptions::default())
+        .lines
+        .iter()
+        .map(line_text)
+        .collect();
+    assert!(text.contains(&"─── zig ─── (no highlighter for 'zig')".to_string()));
+    assert!(text.contains(&"─── rust ───".to_string()));
+
+    let plain = RenderOptions {
+        highlight: false,
+        ..RenderOptions::default()
+    };
+    let text: Vec<String> = render_markdown_with_links(md, 80, &theme, &plain)
+        .lines
+        .iter()
+        .map(line_text)
+        .collect();
+    assert!(text.contains(&"─── zig ───".to_string()));
+}
+
+#[test]
+fn code_theme_names_are_validated() {
+    assert_eq!(
+        resolve_code_theme("InspiredGitHub").unwrap(),
+        "InspiredGitHub"
+    );
+    let err = resolve_code_theme("no-such-theme").unwrap_err();
+    assert!(err.contains("no-such-theme"));
+    assert!(err.contains("base16-ocean.dark"));
+}
+
+#[test]
+fn code_and_table_lines_are_tagged_with_their_kind() {
+    let md = "Intro\n\n```rust\nlet x = 1;\n```\n\n| A |\n| - |\n| 1 |\n";
+    let theme = Theme::pastel();
+    let rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    assert_eq!(rendered.kinds.len(), rendered.lines.len());
+
+    let kind_of = |needle: &str| {
+        let idx = rendered
+            .lines
+            .iter()
+            .position(|line| line_text(line).contains(needle))
+            .unwrap();
+        rendered.kinds[idx]
+    };
+    assert_eq!(kind_of("Intro"), LineKind::Prose);
+    assert_eq!(kind_of("let x = 1;"), LineKind::Code);
+    assert_eq!(kind_of("| 1 |"), LineKind::Table);
+}
+
+#[test]
+fn tabs_expand_to_configured_width_in_text_and_code() {
+    let theme = Theme::pastel();
+    let options = RenderOptions {
+        tab_width: 2,
+        ..RenderOptions::default()
+    };
+
+    let lines = render_markdown_with_links("a\tb\n", 80, &theme, &options).lines;
+    assert_eq!(line_text(&lines[0]), "a b");
+
+    let md = "```\n\tindented\n```\n";
+    let lines = render_markdown_with_links(md, 80, &theme, &options).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+    assert!(text.iter().any(|line| line == "│   indented"));
+
+    let plain = render_plain_lines("\tx", &RenderOptions::default());
+    assert_eq!(line_text(&plain[0]), "    x");
+}
+
+#[test]
+fn tables_pad_double_width_cells_by_display_width() {
+    let md = "| 名前 | B |\n| - | - |\n| x | y |\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+
+    assert!(text.iter().any(|line| line == "| 名前 | B |"));
+    assert!(text.iter().any(|line| line == "| ---- | - |"));
+    assert!(text.iter().any(|line| line == "| x    | y |"));
+}
+
+#[test]
+fn narrow_widths_switch_tables_to_stacked_layout() {
+    assert!(!use_stacked_table(2, 0));
+    assert!(!use_stacked_table(2, 19));
+    assert!(use_stacked_table(2, 18));
+    assert!(use_stacked_table(5, 40));
+    assert!(!use_stacked_table(5, 80));
+}
+
+#[test]
+fn stacked_table_renders_header_value_blocks() {
+    let md = "| Name | Role |\n| - | - |\n| Ada | Engineer |\n| Bob | Writer |\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 12, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+
+    let start = text.iter().position(|line| line == "Name: Ada").unwrap();
+    assert_eq!(
+        &text[start..start + 5],
+        &[
+            "Name: Ada",
+            "Role: Engineer",
+            "",
+            "Name: Bob",
+            "Role: Writer"
+        ]
+    );
+}
+
+#[test]
+fn long_list_items_wrap_with_hanging_indent() {
+    let md = "- first level item that is long enough to wrap\n  - nested item that also wraps around [here](https://x.y)\n";
+    let theme = Theme::pastel();
+    let rendered = render_markdown_with_links(md, 24, &theme, &RenderOptions::default());
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    let rows: Vec<&str> = text
+        .iter()
+        .map(String::as_str)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    assert_eq!(
+        rows,
+        [
+            "- first level item that ",
+            "  is long enough to wrap",
+            "│  - nested item that ",
+            "│    also wraps around ",
+            "│    here",
+        ]
+    );
+    let link = &rendered.links[0];
+    assert_eq!(text[link.line_idx], "│    here");
+    assert_eq!((link.start_char, link.end_char), (5, 9));
+}
+
+#[test]
+fn nested_list_items_get_a_guide_per_enclosing_level() {
+    let md = "- one\n  - two\n    - three\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+    assert_eq!(text, ["- one", "│  - two", "│  │  - three"]);
+
+    let third = &lines[2];
+    assert_eq!(third.spans[0].content, "│  │  ");
+    assert_eq!(third.spans[0].style.fg, Some(theme.list_guide));
+}
+
+#[test]
+fn headings_are_indexed_with_level_and_line() {
+    let md = "# Guide\n\nIntro.\n\n## Install *now*\n\ntext\n";
+    let rendered = render_markdown_with_links(md, 80, &Theme::pastel(), &RenderOptions::default());
+    let summary: Vec<(u8, &str, String)> = rendered
+        .headings
+        .iter()
+        .map(|h| {
+            (
+                h.level,
+                h.text.as_str(),
+                line_text(&rendered.lines[h.line_idx]),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (1, "Guide", "Guide".to_string()),
+            (2, "Install now", "Install now".to_string())
+        ]
+    );
+}
+
+#[test]
+fn br_tags_break_lines_and_kbd_is_emphasized() {
+    let theme = Theme::pastel();
+    let md = "first<br>second<BR/>third\n\nPress <kbd>Ctrl</kbd> now\n";
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+    assert_eq!(&text[..3], ["first", "second", "third"]);
+
+    let press = lines
+        .iter()
+        .find(|line| line_text(line) == "Press Ctrl now")
+        .expect("kbd line");
+    let key = press
+        .spans
+        .iter()
+        .find(|span| span.content == "Ctrl")
+        .unwrap();
+    assert!(key.style.add_modifier.contains(Modifier::BOLD));
+    assert!(key.style.add_modifier.contains(Modifier::REVERSED));
+}
+
+#[test]
+fn unknown_html_is_shown_as_dim_raw_text() {
+    let theme = Theme::pastel();
+    let md = "<details>\n<summary>More</summary>\n</details>\n\nA <span>tag</span>.\n";
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+    assert_eq!(
+        &text[..3],
+        ["<details>", "<summary>More</summary>", "</details>"]
+    );
+    assert!(text.contains(&"A <span>tag</span>.".to_string()));
+
+    let raw = lines[0].spans[0].style;
+    assert_eq!(raw.fg, Some(theme.html));
+    assert!(raw.add_modifier.contains(Modifier::DIM));
+}
+
+#[test]
+fn inline_code_inside_a_link_is_underlined_code_and_clickable() {
+    let theme = Theme::pastel();
+    let rendered = render_markdown_with_links(
+        "Try [`run`](http://x) now",
+        80,
+        &theme,
+        &RenderOptions::default(),
+    );
+    assert_eq!(line_text(&rendered.lines[0]), "Try run now");
+    let code = rendered.lines[0]
+        .spans
+        .iter()
+        .find(|span| span.content == "run")
+        .unwrap();
+    assert_eq!(code.style.fg, Some(theme.code));
+    assert!(code.style.add_modifier.contains(Modifier::UNDERLINED));
+
+    assert_eq!(rendered.links.len(), 1);
+    let link = &rendered.links[0];
+    assert_eq!((link.line_idx, link.start_char, link.end_char), (0, 4, 7));
+    assert_eq!(link.url, "http://x");
+}
+
+#[test]
+fn fence_attributes_do_not_hide_the_language() {
+    assert_eq!(split_code_info("rust,no_run"), ("rust", "no_run"));
+    assert_eq!(
+        split_code_info(" python {.line-numbers}"),
+        ("python", "{.line-numbers}")
+    );
+    assert_eq!(split_code_info("rust"), ("rust", ""));
+
+    let theme = Theme::pastel();
+    let render = |md: &str| render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    let with_attributes = render("```rust,no_run\nfn main() { let x = 1; }\n```\n").lines;
+    let plain_rust = render("```rust\nfn main() { let x = 1; }\n```\n").lines;
+    assert_eq!(line_text(&with_attributes[0]), "─── rust ───");
+    assert_eq!(with_attributes[1], plain_rust[1]);
+    let colors: std::collections::HashSet<_> = with_attributes[1]
+        .spans
+        .iter()
+        .skip(1)
+        .filter_map(|span| span.style.fg)
+        .collect();
+    assert!(colors.len() > 1, "expected Rust highlighting");
+}
+
+#[test]
+fn heading_slugs_follow_github_anchors() {
+    assert_eq!(heading_slug("Installation"), "installation");
+    assert_eq!(heading_slug("Getting Started!"), "getting-started");
+    assert_eq!(heading_slug(" API v2.0 (beta) "), "api-v20-beta");
+    assert_eq!(heading_slug("snake_case & more"), "snake_case--more");
+}
+
+#[test]
+fn missing_local_link_targets_are_flagged_broken() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("present.md"), "# here").unwrap();
+    let theme = Theme::pastel();
+    let md = "[ok](present.md#intro) [gone](missing.md) [web](https://example.com) [top](#top)";
+    let mut rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    mark_broken_links(&mut rendered, dir.path(), &theme);
+
+    let exists: Vec<(&str, bool)> = rendered
+        .links
+        .iter()
+        .map(|link| (link.url.as_str(), link.exists))
+        .collect();
+    assert_eq!(
+        exists,
+        vec![
+            ("present.md#intro", true),
+            ("missing.md", false),
+            ("https://example.com", true),
+            ("#top", true),
+        ]
+    );
+    let gone = rendered.lines[0]
+        .spans
+        .iter()
+        .find(|span| span.content == "gone")
+        .expect("broken link span");
+    assert_eq!(gone.style.fg, Some(theme.broken_link));
+    assert!(gone.style.add_modifier.contains(Modifier::CROSSED_OUT));
+    let ok = rendered.lines[0]
+        .spans
+        .iter()
+        .find(|span| span.content == "ok")
+        .expect("link span");
+    assert!(!ok.style.add_modifier.contains(Modifier::CROSSED_OUT));
+}
+
+#[test]
+fn local_link_paths_decode_escapes_and_skip_urls() {
+    let base = Path::new("/docs");
+    assert_eq!(
+        local_link_path("my%20notes.md?x=1", base),
+        Some(PathBuf::from("/docs/my notes.md"))
+    );
+    assert_eq!(local_link_path("mailto:a@b.c", base), None);
+    assert_eq!(local_link_path("#section", base), None);
+}
+
+#[test]
+fn emoji_shortcodes_render_in_prose_but_not_code() {
+    let theme = Theme::pastel();
+    let md = "Ship :rocket: `:rocket:`\n\n| a |\n|---|\n| :tada: |\n";
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+    assert_eq!(text[0], "Ship 🚀 `:rocket:`");
+    assert!(text.iter().any(|line| line.contains("🎉")));
+
+    let options = RenderOptions {
+        emoji_shortcodes: false,
+        ..RenderOptions::default()
+    };
+    let lines = render_markdown_with_links("Ship :rocket:", 80, &theme, &options).lines;
+    assert_eq!(line_text(&lines[0]), "Ship :rocket:");
+}
+
+#[test]
+fn setext_headings_render_like_atx_headings() {
+    let theme = Theme::pastel();
+    let render = |md: &str| render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    for (setext, atx) in [
+        ("Title\n=====\n\nBody\n", "# Title\n\nBody\n"),
+        ("Intro\n\nSub\n---\n\nBody\n", "Intro\n\n## Sub\n\nBody\n"),
+    ] {
+        let (setext, atx) = (render(setext), render(atx));
+        assert_eq!(setext.lines, atx.lines);
+        assert_eq!(
+            setext.headings.iter().map(|h| h.level).collect::<Vec<_>>(),
+            atx.headings.iter().map(|h| h.level).collect::<Vec<_>>()
+        );
+    }
+    let text: Vec<String> = render("Title\n=====\n\nBody\n")
+        .lines
+        .iter()
+        .map(line_text)
+        .collect();
+    assert_eq!(text, ["Title", "", "Body"]);
+}
+
+#[test]
+fn lone_dashes_between_paragraphs_render_a_rule() {
+    let theme = Theme::pastel();
+    let rendered =
+        render_markdown_with_links("One\n\n---\n\nTwo\n", 80, &theme, &RenderOptions::default());
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    assert_eq!(text[0], "One");
+    assert_eq!(text[1], "");
+    assert!(text[2].chars().all(|ch| ch == '-') && !text[2].is_empty());
+    assert_eq!(text[3], "");
+    assert_eq!(text[4], "Two");
+    assert!(rendered.headings.is_empty());
+}
+
+#[test]
+fn diagram_fences_render_a_notice_unless_source_is_requested() {
+    let theme = Theme::pastel();
+    let md = "```mermaid\ngraph TD\n  A --> B\n```\n";
+    let rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    assert_eq!(
+        text,
+        ["[mermaid diagram — 2 lines, press D to show source]"]
+    );
+    assert_eq!(rendered.kinds[0], LineKind::Code);
+
+    let options = RenderOptions {
+        diagram_source: true,
+        ..RenderOptions::default()
+    };
+    let rendered = render_markdown_with_links(md, 80, &theme, &options);
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    assert!(text.iter().any(|line| line == "│ graph TD"));
+}
+
+#[test]
+fn blank_runs_collapse_and_links_follow_their_lines() {
+    let theme = Theme::pastel();
+    let md = "- a\n  - b\n\n[x](y) text\n\n## After\n";
+    let rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    assert_eq!(text, ["- a", "│  - b", "", "x text", "", "After"]);
+    assert_eq!(rendered.kinds.len(), text.len());
+    assert_eq!(rendered.source_lines.len(), text.len());
+
+    assert_eq!(rendered.links.len(), 1);
+    assert_eq!(rendered.links[0].line_idx, 3);
+    assert_eq!(text[rendered.links[0].line_idx], "x text");
+    assert_eq!(rendered.headings[0].line_idx, 5);
+}
+
+fn span_modifiers(md: &str, needle: &str) -> Modifier {
+    let theme = Theme::pastel();
+    let rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    rendered
+        .lines
+        .iter()
+        .flat_map(|line| line.spans.iter())
+        .find(|span| span.content.contains(needle))
+        .unwrap_or_else(|| panic!("no span containing {needle:?}"))
+        .style
+        .add_modifier
+}
+
+#[test]
+fn nested_emphasis_and_links_keep_every_modifier() {
+    assert_eq!(
+        span_modifiers("***both***", "both"),
+        Modifier::BOLD | Modifier::ITALIC
+    );
+    assert_eq!(
+        span_modifiers("~~[struck](x)~~", "struck"),
+        Modifier::CROSSED_OUT | Modifier::UNDERLINED
+    );
+    assert_eq!(
+        span_modifiers("**[*deep*](x)**", "deep"),
+        Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED
+    );
+    assert_eq!(
+        span_modifiers("[~~**all**~~](x)", "all"),
+        Modifier::BOLD | Modifier::CROSSED_OUT | Modifier::UNDERLINED
+    );
+    assert_eq!(
+        span_modifiers("*see https://example.com*", "https://"),
+        Modifier::ITALIC | Modifier::UNDERLINED
+    );
+}
+
+#[test]
+fn inline_code_keeps_surrounding_emphasis() {
+    assert_eq!(
+        span_modifiers("**`bold code`**", "bold code"),
+        Modifier::BOLD | Modifier::DIM
+    );
+    assert_eq!(
+        span_modifiers("~~[`struck code`](x)~~", "struck code"),
+        Modifier::CROSSED_OUT | Modifier::DIM | Modifier::UNDERLINED
+    );
+}
+
+#[test]
+fn crlf_and_bare_cr_leave_no_carriage_returns() {
+    let md =
+        "# Title\r\n\r\nSome body\r\ntext.\r\n\r\n```rust\r\nlet a = 1;\r\nlet b = 2;\r\n```\r\n";
+    let theme = Theme::pastel();
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+    assert!(text.iter().all(|line| !line.contains('\r')), "{text:?}");
+    assert!(text.iter().any(|line| line == "Some body text."));
+    assert!(text.iter().any(|line| line == "│ let a = 1;"));
+    assert!(text.iter().any(|line| line == "│ let b = 2;"));
+
+    let mut code = Vec::new();
+    render_code_block(
+        &mut code,
+        "one\rtwo\r\n",
+        None,
+        &theme,
+        &RenderOptions::default(),
+    );
+    let code: Vec<String> = code.iter().map(line_text).collect();
+    assert_eq!(code, vec!["│ one", "│ two"]);
+
+    let plain = render_plain_lines("a\r\nb\rc", &RenderOptions::default());
+    let plain: Vec<String> = plain.iter().map(line_text).collect();
+    assert_eq!(plain, vec!["a", "b", "c"]);
+}
+
+#[test]
+fn reformat_json_reindents_and_keeps_key_order() {
+    assert_eq!(
+        reformat_json(r#"{"name":"mdr","tags":["a","b"],"nested":{"ok":true}}"#).as_deref(),
+        Some(
+            "{\n  \"name\": \"mdr\",\n  \"tags\": [\n    \"a\",\n    \"b\"\n  ],\n  \"nested\": {\n    \"ok\": true\n  }\n}"
+        )
+    );
+    assert_eq!(reformat_json("{\"broken\": [1, 2}"), None);
+    assert_eq!(reformat_json("not json"), None);
+}
+
+#[test]
+fn minified_json_blocks_render_reindented() {
+    let theme = Theme::pastel();
+    let md = "```json\n{\"a\":1,\"b\":[2]}\n```\n\n```json\n{\"bad\":\n```\n";
+    let text: Vec<String> = render_markdown_with_links(md, 80, &theme, &RenderOptions::default())
+        .lines
+        .iter()
+        .map(line_text)
+        .collect();
+    assert!(text.contains(&"│   \"a\": 1,".to_string()), "{text:?}");
+    assert!(text.contains(&"│ {\"bad\":".to_string()), "{text:?}");
+
+    let options = RenderOptions {
+        reformat_json: false,
+        ..RenderOptions::default()
+    };
+    let text: Vec<String> = render_markdown_with_links(md, 80, &theme, &options)
+        .lines
+        .iter()
+        .map(line_text)
+        .collect();
+    assert!(
+        text.contains(&"│ {\"a\":1,\"b\":[2]}".to_string()),
+        "{text:?}"
+    );
+}
+
+#[test]
+fn code_blocks_without_highlighting_use_one_style() {
+    let theme = Theme::pastel();
+    let options = RenderOptions {
+        highlight: false,
+        ..RenderOptions::default()
+    };
+    let mut lines = Vec::new();
+    render_code_block(
+        &mut lines,
+        "fn main() {\n    let x = \"s\";\n}\n",
+        Some("rust"),
+        &theme,
+        &options,
+    );
+    let expected = Style::new().fg(theme.code);
+    let code_lines = &lines[1..];
+    assert_eq!(code_lines.len(), 3);
+    for line in code_lines {
+        assert!(line.spans.iter().all(|span| span.style == expected));
+        assert_eq!(line.spans.len(), 2);
+    }
+    assert_eq!(line_text(&code_lines[1]), "│     let x = \"s\";");
+}
+
+#[test]
+fn code_block_blank_lines_render_verbatim() {
+    let theme = Theme::pastel();
+    let md = "Before\n\n```\n\nfirst\n\n\nsecond\n\n```\n\nAfter\n";
+    let text: Vec<String> = render_markdown_with_links(md, 80, &theme, &RenderOptions::default())
+        .lines
+        .iter()
+        .map(line_text)
+        .collect();
+    assert_eq!(
+        text,
+        vec![
+            "Before",
+            "",
+            "│ ",
+            "│ first",
+            "│ ",
+            "│ ",
+            "│ second",
+            "│ ",
+            "",
+            "After"
+        ]
+    );
+}
+
+#[test]
+fn parses_admonition_markers() {
+    assert_eq!(parse_admonition("> [!NOTE]"), Some(AdmonitionKind::Note));
+    assert_eq!(
+        parse_admonition("[!WARNING]"),
+        Some(AdmonitionKind::Warning)
+    );
+    assert_eq!(parse_admonition(">  [!tip]  "), Some(AdmonitionKind::Tip));
+    assert_eq!(
+        parse_admonition("> [!IMPORTANT]"),
+        Some(AdmonitionKind::Important)
+    );
+    assert_eq!(
+        parse_admonition("> [!CAUTION]"),
+        Some(AdmonitionKind::Caution)
+    );
+    assert_eq!(parse_admonition("> [!NOTE] with a title"), None);
+    assert_eq!(parse_admonition("> [!UNKNOWN]"), None);
+    assert_eq!(parse_admonition("> just a quote"), None);
+}
+
+#[test]
+fn admonitions_render_as_titled_callouts() {
+    let theme = Theme::pastel();
+    let md = "> [!WARNING]\n> Mind the gap.\n\n> Plain quote.\n";
+    let lines = render_markdown_with_links(md, 80, &theme, &RenderOptions::default()).lines;
+    let text: Vec<String> = lines.iter().map(line_text).collect();
+    assert_eq!(
+        text,
+        vec!["▌ ⚠ Warning", "▌ Mind the gap.", "", "> Plain quote."]
+    );
+    let title = lines[0].spans.last().unwrap();
+    assert_eq!(title.style.fg, Some(theme.callout_warning));
+    assert!(title.style.add_modifier.contains(Modifier::BOLD));
+    assert_eq!(lines[1].spans[0].style.fg, Some(theme.callout_warning));
+    assert_eq!(lines[3].spans[0].style.fg, Some(theme.quote));
+}
+
+fn link_text(rendered: &RenderedMarkdown, link: &LinkTarget) -> String {
+    line_text(&rendered.lines[link.line_idx])
+        .chars()
+        .skip(link.start_char)
+        .take(link.end_char - link.start_char)
+        .collect()
+}
+
+#[test]
+fn links_split_across_a_hard_break_register_each_line() {
+    let theme = Theme::pastel();
+    let md = "> See [first part\\\nsecond part](https://example.com) here\n";
+    let rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    let segments: Vec<(usize, String)> = rendered
+        .links
+        .iter()
+        .map(|link| (link.line_idx, link_text(&rendered, link)))
+        .collect();
+    assert_eq!(
+        segments,
+        vec![
+            (0, "first part".to_string()),
+            (1, "second part".to_string())
+        ]
+    );
+    assert!(
+        rendered
+            .links
+            .iter()
+            .all(|link| link.url == "https://example.com")
+    );
+}
+
+#[test]
+fn list_links_wrapped_at_a_soft_break_stay_clickable_on_each_row() {
+    let theme = Theme::pastel();
+    let md = "- read [the long\nguide here](https://example.com) now\n";
+    let rendered = render_markdown_with_links(md, 20, &theme, &RenderOptions::default());
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    assert_eq!(text, vec!["- read the long ", "  guide here now"]);
+    let segments: Vec<(usize, String)> = rendered
+        .links
+        .iter()
+        .map(|link| (link.line_idx, link_text(&rendered, link)))
+        .collect();
+    assert_eq!(
+        segments,
+        vec![(0, "the long ".to_string()), (1, "guide here".to_string())]
+    );
+}
+
+fn rendered_text(md: &str, width: u16) -> Vec<String> {
+    render_markdown_with_links(md, width, &Theme::pastel(), &RenderOptions::default())
+        .lines
+        .iter()
+        .map(line_text)
+        .collect()
+}
+
+#[test]
+fn tight_lists_are_compact_and_loose_lists_are_spaced() {
+    let tight = rendered_text("- a\n- b\n- c\n\nafter\n", 80);
+    let loose = rendered_text("- a\n\n- b\n\n- c\n\nafter\n", 80);
+    assert_eq!(tight, ["- a", "- b", "- c", "", "after"]);
+    assert_eq!(loose, ["- a", "", "- b", "", "- c", "", "after"]);
+    assert_eq!(loose.len(), tight.len() + 2);
+}
+
+#[test]
+fn loose_items_ending_in_a_nested_list_stay_spaced() {
+    assert_eq!(
+        rendered_text("- a\n\n  - inner\n- b\n", 80),
+        ["- a", "", "│  - inner", "", "- b"]
+    );
+}
+
+#[test]
+fn ordered_lists_count_from_their_start_number() {
+    assert_eq!(
+        rendered_text("3. three\n4. four\n5. five that wraps here\n", 16),
+        ["3. three", "4. four", "5. five that ", "   wraps here"]
+    );
+    assert_eq!(
+        rendered_text("1. one\n   - sub\n2. two\n", 80),
+        ["1. one", "│  - sub", "2. two"]
+    );
+}
+
+#[test]
+fn reference_links_resolve_to_their_definitions() {
+    let theme = Theme::pastel();
+    let md = "Read [the guide][Guide], [docs][] and [faq].\n\n[guide]: https://example.com/guide\n[docs]: docs/index.md \"Docs\"\n[faq]: <faq.md>\n";
+    let rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    let targets: Vec<(String, &str)> = rendered
+        .links
+        .iter()
+        .map(|link| (link_text(&rendered, link), link.url.as_str()))
+        .collect();
+    assert_eq!(
+        targets,
+        vec![
+            ("the guide".to_string(), "https://example.com/guide"),
+            ("docs".to_string(), "docs/index.md"),
+            ("faq".to_string(), "faq.md"),
+        ]
+    );
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    assert_eq!(text, vec!["Read the guide, docs and faq."]);
+}
+
+#[test]
+fn undefined_references_render_as_literal_text() {
+    let theme = Theme::pastel();
+    let md = "See [the guide][missing] or [nothing].\n";
+    let rendered = render_markdown_with_links(md, 80, &theme, &RenderOptions::default());
+    assert!(rendered.links.is_empty());
+    let text: Vec<String> = rendered.lines.iter().map(line_text).collect();
+    assert_eq!(text, vec!["See [the guide][missing] or [nothing]."]);
+}
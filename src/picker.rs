@@ -1,6 +1,8 @@
 use std::fs;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
+
+use crate::file_kind::is_openable;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PickerEntryKind {
@@ -16,7 +18,9 @@ pub struct PickerEntry {
     pub label: String,
 }
 
-pub fn list_entries(dir: PathBuf, query: &str) -> io::Result<Vec<PickerEntry>> {
+/// Lists subdirectories and openable files in `dir` whose names contain
+/// `query`. With `any_text`, `.txt` and extensionless files are included.
+pub fn list_entries(dir: PathBuf, query: &str, any_text: bool) -> io::Result<Vec<PickerEntry>> {
     let dir = fs::canonicalize(&dir).unwrap_or(dir);
     if !dir.is_dir() {
         return Err(io::Error::new(
@@ -58,7 +62,7 @@ pub fn list_entries(dir: PathBuf, query: &str) -> io::Result<Vec<PickerEntry>> {
             continue;
         }
 
-        if path.is_file() && is_markdown(&path) {
+        if path.is_file() && is_openable(&path, any_text) {
             listed.push(PickerEntry {
                 path,
                 kind: PickerEntryKind::MarkdownFile,
@@ -89,16 +93,6 @@ fn kind_order(kind: &PickerEntryKind) -> u8 {
     }
 }
 
-fn is_markdown(path: &Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| {
-            let ext = ext.to_ascii_lowercase();
-            matches!(ext.as_str(), "md" | "markdown" | "mdown" | "mdx")
-        })
-        .unwrap_or(false)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,7 +105,7 @@ mod tests {
         std::fs::write(root.path().join("a.md"), "# a").expect("write md");
         std::fs::write(root.path().join("b.txt"), "b").expect("write txt");
 
-        let entries = list_entries(root.path().to_path_buf(), "").expect("list entries");
+        let entries = list_entries(root.path().to_path_buf(), "", false).expect("list entries");
 
         assert!(entries.iter().any(|e| e.kind == PickerEntryKind::Parent));
         assert!(entries.iter().any(|e| {
@@ -133,7 +127,7 @@ mod tests {
         std::fs::write(root.path().join("README.markdown"), "# readme").expect("write markdown");
         std::fs::write(root.path().join("guide.md"), "# guide").expect("write markdown");
 
-        let entries = list_entries(root.path().to_path_buf(), "read").expect("list entries");
+        let entries = list_entries(root.path().to_path_buf(), "read", false).expect("list entries");
 
         assert!(entries.iter().any(|e| {
             e.kind == PickerEntryKind::MarkdownFile
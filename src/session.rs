@@ -0,0 +1,242 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::background::Background;
+use crate::cli::parse_theme;
+use crate::document_queue::{DocumentQueue, QueuedDocument, read_document};
+
+/// Queue paths and focused index persisted between `--resume` runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SavedSession {
+    pub paths: Vec<PathBuf>,
+    pub current: usize,
+}
+
+impl SavedSession {
+    pub fn from_queue(queue: &DocumentQueue) -> Self {
+        Self {
+            paths: queue
+                .documents()
+                .iter()
+                .map(|doc| doc.path.clone())
+                .collect(),
+            current: queue.current_index(),
+        }
+    }
+}
+
+/// Location of the session file inside the platform cache directory.
+pub fn session_file() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("mdr").join("session"))
+}
+
+/// View toggles remembered between runs when `remember_view` is set in the
+/// config file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SavedView {
+    pub beeline: bool,
+    pub plain: bool,
+    /// Palette picked with `--theme`, the config file, or `:theme`; `None`
+    /// leaves it to background detection.
+    pub theme: Option<Background>,
+}
+
+pub fn view_file() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("mdr").join("view"))
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        return env::var_os("LOCALAPPDATA").map(PathBuf::from);
+    }
+    let home = PathBuf::from(env::var_os("HOME")?);
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library").join("Caches"))
+    } else {
+        Some(home.join(".cache"))
+    }
+}
+
+/// Serializes a session as a `current=<index>` header followed by one path per line.
+pub fn serialize_session(session: &SavedSession) -> String {
+    let mut out = format!("current={}\n", session.current);
+    for path in &session.paths {
+        out.push_str(&path.to_string_lossy());
+        out.push('\n');
+    }
+    out
+}
+
+pub fn deserialize_session(text: &str) -> Result<SavedSession, String> {
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default();
+    let current = header
+        .strip_prefix("current=")
+        .and_then(|value| value.parse::<usize>().ok())
+        .ok_or_else(|| format!("Invalid session header: {}", header))?;
+    let paths = lines
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    Ok(SavedSession { paths, current })
+}
+
+pub fn save_session(path: &Path, session: &SavedSession) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {}", parent.display(), err))?;
+    }
+    fs::write(path, serialize_session(session))
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+}
+
+/// Serializes view toggles as `key=value` lines.
+pub fn serialize_view(view: &SavedView) -> String {
+    let mut out = format!("beeline={}\nplain={}\n", view.beeline, view.plain);
+    match view.theme {
+        Some(Background::Light) => out.push_str("theme=light\n"),
+        Some(Background::Dark) => out.push_str("theme=dark\n"),
+        Some(Background::Unknown) | None => {}
+    }
+    out
+}
+
+pub fn deserialize_view(text: &str) -> Result<SavedView, String> {
+    let mut view = SavedView::default();
+    for line in text.lines().filter(|line| !line.is_empty()) {
+        let parsed = match line.split_once('=') {
+            Some(("beeline", value)) => parse_flag(value).map(|on| view.beeline = on),
+            Some(("plain", value)) => parse_flag(value).map(|on| view.plain = on),
+            Some(("theme", value)) => parse_theme(value).map(|theme| view.theme = Some(theme)),
+            _ => Err(format!("Invalid view setting: {}", line)),
+        };
+        parsed?;
+    }
+    Ok(view)
+}
+
+fn parse_flag(value: &str) -> Result<bool, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid view flag: {}", value))
+}
+
+/// Reads the saved view, if one was written by an earlier run.
+pub fn load_view(path: &Path) -> Result<Option<SavedView>, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => deserialize_view(&text).map(Some),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(format!("Failed to read {}: {}", path.display(), err)),
+    }
+}
+
+pub fn save_view(path: &Path, view: &SavedView) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {}", parent.display(), err))?;
+    }
+    fs::write(path, serialize_view(view))
+        .map_err(|err| format!("Failed to write {}: {}", path.display(), err))
+}
+
+/// Rebuilds a queue from a saved session, skipping files that no longer
+/// exist or cannot be read. Focus stays on the saved document when it
+/// survives, otherwise on the nearest earlier one.
+pub fn restore_queue(session: &SavedSession, lossy: bool) -> Result<DocumentQueue, String> {
+    let mut docs = Vec::new();
+    let mut current = 0;
+    for (idx, path) in session.paths.iter().enumerate() {
+        let Ok(content) = read_document(path, lossy) else {
+            continue;
+        };
+        if idx <= session.current {
+            current = docs.len();
+        }
+        docs.push(QueuedDocument::new(path.clone(), content));
+    }
+
+    let mut queue = DocumentQueue::new(docs)
+        .map_err(|_| "No documents from the saved session are still readable".to_string())?;
+    queue.focus_index(current);
+    Ok(queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_round_trips_paths_and_index() {
+        let session = SavedSession {
+            paths: vec![
+                PathBuf::from("/docs/intro.md"),
+                PathBuf::from("/docs/with space/setup.md"),
+            ],
+            current: 1,
+        };
+
+        let text = serialize_session(&session);
+        assert_eq!(deserialize_session(&text).unwrap(), session);
+    }
+
+    #[test]
+    fn deserialize_rejects_missing_header() {
+        assert!(deserialize_session("/docs/intro.md\n").is_err());
+    }
+
+    #[test]
+    fn view_round_trips_toggles_and_theme() {
+        for view in [
+            SavedView {
+                beeline: false,
+                plain: true,
+                theme: Some(Background::Light),
+            },
+            SavedView {
+                beeline: true,
+                plain: false,
+                theme: None,
+            },
+        ] {
+            assert_eq!(deserialize_view(&serialize_view(&view)), Ok(view));
+        }
+        assert!(deserialize_view("beeline=maybe\n").is_err());
+        assert!(deserialize_view("zoom=2\n").is_err());
+    }
+
+    #[test]
+    fn view_file_is_optional_until_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mdr").join("view");
+        assert_eq!(load_view(&path), Ok(None));
+
+        let view = SavedView {
+            beeline: false,
+            plain: false,
+            theme: Some(Background::Dark),
+        };
+        save_view(&path, &view).unwrap();
+        assert_eq!(load_view(&path), Ok(Some(view)));
+    }
+
+    #[test]
+    fn restore_skips_missing_files_and_keeps_focus() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.md");
+        let c = dir.path().join("c.md");
+        fs::write(&a, "# a").unwrap();
+        fs::write(&c, "# c").unwrap();
+        let session = SavedSession {
+            paths: vec![a, dir.path().join("b.md"), c.clone()],
+            current: 2,
+        };
+
+        let queue = restore_queue(&session, false).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.current().path, c);
+    }
+}
@@ -1,7 +1,13 @@
 use ratatui::style::Color;
 
+pub const DEFAULT_CODE_THEME: &str = "base16-ocean.dark";
+pub const LIGHT_CODE_THEME: &str = "InspiredGitHub";
+
+/// Colors used by the renderer and the reader UI.
 #[derive(Clone, Copy)]
 pub struct Theme {
+    /// Palette name shown on the `:about` screen.
+    pub name: &'static str,
     pub border: Color,
     pub title: Color,
     pub footer: Color,
@@ -10,8 +16,11 @@ pub struct Theme {
     pub code: Color,
     pub quote: Color,
     pub rule: Color,
+    /// Vertical guides marking nested list levels.
+    pub list_guide: Color,
     pub scrollbar_thumb: Color,
     pub scrollbar_track: Color,
+    pub text: Color,
     pub beeline_start: Color,
     pub beeline_end: Color,
     pub search_bg: Color,
@@ -19,11 +28,25 @@ pub struct Theme {
     pub search_bg_active: Color,
     pub search_fg_active: Color,
     pub link: Color,
+    /// Local links whose target file is missing.
+    pub broken_link: Color,
+    /// Raw HTML that is shown as-is.
+    pub html: Color,
+    /// Subtle background for the cursor line.
+    pub cursor_line_bg: Color,
+    /// Title and bar colors for `> [!NOTE]`-style callouts.
+    pub callout_note: Color,
+    pub callout_tip: Color,
+    pub callout_important: Color,
+    pub callout_warning: Color,
+    pub callout_caution: Color,
+    pub code_theme: &'static str,
 }
 
 impl Theme {
     pub fn pastel() -> Self {
         Self {
+            name: "dark",
             border: Color::Rgb(184, 193, 236),
             title: Color::Rgb(132, 140, 200),
             footer: Color::Rgb(160, 168, 210),
@@ -32,8 +55,10 @@ impl Theme {
             code: Color::Rgb(240, 200, 170),
             quote: Color::Rgb(190, 170, 220),
             rule: Color::Rgb(190, 190, 200),
+            list_guide: Color::Rgb(90, 96, 120),
             scrollbar_thumb: Color::Rgb(150, 190, 220),
             scrollbar_track: Color::Rgb(210, 220, 230),
+            text: Color::Rgb(210, 210, 215),
             beeline_start: Color::Rgb(170, 200, 230),
             beeline_end: Color::Rgb(230, 170, 200),
             search_bg: Color::Rgb(255, 230, 170),
@@ -41,6 +66,56 @@ impl Theme {
             search_bg_active: Color::Rgb(255, 200, 120),
             search_fg_active: Color::Rgb(40, 40, 40),
             link: Color::Rgb(120, 170, 230),
+            broken_link: Color::Rgb(230, 110, 110),
+            html: Color::Rgb(170, 190, 160),
+            cursor_line_bg: Color::Rgb(48, 52, 66),
+            callout_note: Color::Rgb(120, 170, 230),
+            callout_tip: Color::Rgb(130, 200, 140),
+            callout_important: Color::Rgb(180, 150, 230),
+            callout_warning: Color::Rgb(230, 190, 100),
+            callout_caution: Color::Rgb(230, 110, 110),
+            code_theme: DEFAULT_CODE_THEME,
+        }
+    }
+
+    /// Palette for dark terminal backgrounds (the pastel default).
+    pub fn dark() -> Self {
+        Self::pastel()
+    }
+
+    /// Deeper variant of the pastel palette that stays readable on light
+    /// terminal backgrounds.
+    pub fn light() -> Self {
+        Self {
+            name: "light",
+            border: Color::Rgb(110, 120, 180),
+            title: Color::Rgb(70, 78, 150),
+            footer: Color::Rgb(90, 98, 150),
+            heading: Color::Rgb(40, 100, 160),
+            list_bullet: Color::Rgb(30, 130, 100),
+            code: Color::Rgb(160, 90, 40),
+            quote: Color::Rgb(110, 80, 150),
+            rule: Color::Rgb(140, 140, 150),
+            list_guide: Color::Rgb(190, 194, 214),
+            scrollbar_thumb: Color::Rgb(80, 130, 180),
+            scrollbar_track: Color::Rgb(200, 205, 215),
+            text: Color::Rgb(40, 40, 50),
+            beeline_start: Color::Rgb(30, 80, 150),
+            beeline_end: Color::Rgb(150, 40, 100),
+            search_bg: Color::Rgb(255, 220, 120),
+            search_fg: Color::Rgb(30, 30, 30),
+            search_bg_active: Color::Rgb(240, 160, 60),
+            search_fg_active: Color::Rgb(20, 20, 20),
+            link: Color::Rgb(20, 90, 190),
+            broken_link: Color::Rgb(190, 40, 40),
+            html: Color::Rgb(100, 120, 80),
+            cursor_line_bg: Color::Rgb(232, 236, 246),
+            callout_note: Color::Rgb(20, 90, 190),
+            callout_tip: Color::Rgb(30, 130, 60),
+            callout_important: Color::Rgb(110, 60, 180),
+            callout_warning: Color::Rgb(160, 100, 0),
+            callout_caution: Color::Rgb(190, 40, 40),
+            code_theme: LIGHT_CODE_THEME,
         }
     }
 }
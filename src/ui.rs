@@ -1,15 +1,19 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::ops::Range;
+use std::panic;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
-use crossterm::event::{self, Event, KeyCode, MouseButton, MouseEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEventKind};
 use crossterm::terminal::{
     EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
 };
 use crossterm::{
-    event::DisableFocusChange, event::DisableMouseCapture, event::EnableFocusChange,
+    cursor::Show, event::DisableFocusChange, event::DisableMouseCapture, event::EnableFocusChange,
     event::EnableMouseCapture, execute,
 };
 use ratatui::Terminal;
@@ -20,38 +24,116 @@ use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{
     Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
 };
-use unicode_width::UnicodeWidthChar;
 
-use crate::beeline::apply_beeline;
-use crate::document_queue::{DocumentQueue, QueuedDocument};
+use crate::background::Background;
+use crate::beeline::{apply_beeline, apply_bionic};
+use crate::color::{ColorDepth, downgrade_color};
+use crate::command::{self, parse_command};
+use crate::document_queue::{DocumentQueue, QueuedDocument, read_document};
+use crate::file_kind::is_markdown;
+use crate::keymap::{Action, KeyMap};
+use crate::link_scheme::LinkSchemes;
 use crate::markdown::{
-    LinkTarget, estimate_rendered_lines, render_markdown_with_links, render_plain_lines,
+    LinkTarget, RenderOptions, estimate_rendered_lines, heading_slug, mark_broken_links,
+    render_html_document, render_markdown_with_links, render_plain_lines,
 };
 use crate::picker::{PickerEntry, PickerEntryKind, list_entries};
+use crate::session::SavedView;
 use crate::theme::Theme;
+use crate::wrap::{LineWrap, RowRange, char_width, wrap_line_ranges};
+
+/// Documents larger than this are shown as plain text unless full rendering
+/// is forced, so opening them doesn't stall on parsing and highlighting.
+const LARGE_DOCUMENT_BYTES: usize = 2 * 1024 * 1024;
+
+const H_SCROLL_STEP: u16 = 4;
+const BEELINE_INTENSITY_STEP: u8 = 10;
+
+pub const DEFAULT_WORDS_PER_MINUTE: usize = 200;
+
+pub struct TuiOptions {
+    pub enable_beeline: bool,
+    pub force_full_render: bool,
+    pub words_per_minute: usize,
+    pub center_search_matches: bool,
+    pub render_options: RenderOptions,
+    pub browser: Option<String>,
+    /// Open commands for custom URL schemes, tried before `browser`.
+    pub link_schemes: LinkSchemes,
+    /// Keys for the remappable reader actions.
+    pub keymap: KeyMap,
+    pub lossy_utf8: bool,
+    pub color_depth: ColorDepth,
+    pub watch: bool,
+    /// Keep the view at the bottom when a reload adds content.
+    pub follow: bool,
+    pub smooth_scroll: bool,
+    /// Capture mouse events; off for a keyboard-only session.
+    pub mouse: bool,
+    /// List `.txt` and extensionless files in the picker.
+    pub any_text: bool,
+    pub sticky_search: bool,
+    /// Number every rendered line in a gutter left of the text.
+    pub line_numbers: bool,
+    /// Wrap `]`/`[` around the ends of the queue.
+    pub wrap_queue: bool,
+    /// Blank columns on each side of the text, inside the border.
+    pub padding: u16,
+    /// Widest the text column may wrap at; wider areas center it.
+    pub max_text_width: Option<u16>,
+    /// Heading slug to scroll to when the first document is shown.
+    pub start_heading: Option<String>,
+    /// 1-based source line to scroll to when the first document is shown.
+    pub start_line: Option<usize>,
+    /// Start in plain mode, as left by the last run.
+    pub plain_mode: bool,
+    /// Palette chosen by flag, config, or saved view; `None` if detected.
+    pub theme_choice: Option<Background>,
+}
 
 pub fn run_tui(
     mut queue: DocumentQueue,
     picker_root: PathBuf,
-    enable_beeline: bool,
-) -> io::Result<()> {
+    options: TuiOptions,
+    mut theme: Theme,
+) -> io::Result<(DocumentQueue, SavedView)> {
     let mut stdout = io::stdout();
-    enable_raw_mode()?;
-    execute!(stdout, EnterAlternateScreen)?;
-    execute!(stdout, EnableMouseCapture, EnableFocusChange)?;
+    let guard = TerminalGuard::enable()?;
+    execute!(stdout, EnterAlternateScreen, EnableFocusChange)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
-    execute!(terminal.backend_mut(), DisableMouseCapture)?;
-    execute!(
-        terminal.backend_mut(),
-        EnableMouseCapture,
-        EnableFocusChange
-    )?;
-    let theme = Theme::pastel();
-
-    let mut state = AppState::new(enable_beeline);
+    let mut state = AppState::new(options.enable_beeline);
+    state.mouse_enabled = options.mouse;
+    state.any_text = options.any_text;
+    state.sticky_search = options.sticky_search;
+    state.line_numbers = options.line_numbers;
+    state.wrap_queue = options.wrap_queue;
+    state.padding = options.padding;
+    state.max_text_width = options.max_text_width;
+    state.pending_heading = options.start_heading;
+    state.pending_start_line = options.start_line;
+    state.plain_mode = options.plain_mode;
+    let mut theme_choice = options.theme_choice;
+    state.recapture_mouse(&mut terminal)?;
+    state.force_full_render = options.force_full_render;
+    state.words_per_minute = options.words_per_minute;
+    state.center_search_matches = options.center_search_matches;
+    state.render_options = options.render_options;
+    state.browser = options.browser;
+    state.link_schemes = options.link_schemes;
+    state.keymap = options.keymap;
+    state.lossy_utf8 = options.lossy_utf8;
+    state.color_depth = options.color_depth;
+    state.watch = options.watch;
+    state.follow = options.follow;
+    state.smooth_scroll = options.smooth_scroll;
 
     loop {
+        if let Some(background) = state.pending_theme.take() {
+            theme = switch_palette(&theme, background);
+            theme_choice = Some(background);
+            state.render_cache.invalidate();
+        }
         {
             let current = queue.current();
             let path = current.path.display().to_string();
@@ -68,6 +150,7 @@ pub fn run_tui(
                 queue_index,
                 queue_len,
                 queue_paths: &queue_paths,
+                plain_text: !is_markdown(&current.path),
             };
             terminal.draw(|frame| state.render(frame, &context, &theme))?;
         }
@@ -75,100 +158,330 @@ pub fn run_tui(
         if state.priming_mode {
             if event::poll(Duration::from_millis(80))? {
                 let event = event::read()?;
-                match state.handle_event(event, &mut terminal)? {
-                    EventResult::Quit => break,
-                    EventResult::NextDocument => {
-                        queue.next();
-                        state.on_document_changed();
-                    }
-                    EventResult::PreviousDocument => {
-                        queue.prev();
-                        state.on_document_changed();
-                    }
-                    EventResult::OpenPicker => {
-                        state.open_picker(picker_root.clone());
-                    }
-                    EventResult::OpenGoDialog => {
-                        state.open_go_dialog(queue.len(), queue.current_index());
-                    }
-                    EventResult::OpenPath(path) => {
-                        let mut switched = queue.focus_existing(&path);
-                        if !switched && let Ok(content) = fs::read_to_string(&path) {
-                            queue.push_and_focus(QueuedDocument::new(path, content));
-                            switched = true;
-                        }
-                        if switched {
-                            state.on_document_changed();
-                        }
-                    }
-                    EventResult::GoToIndex(index) => {
-                        if queue.focus_index(index) {
-                            state.on_document_changed();
-                        }
-                    }
-                    EventResult::Continue => {}
+                let result = state.handle_event(event, &mut terminal)?;
+                if apply_event_result(result, &mut queue, &mut state, &picker_root) {
+                    break;
                 }
-                execute!(
-                    terminal.backend_mut(),
-                    DisableMouseCapture,
-                    EnableMouseCapture
-                )?;
-                state.priming_mode = false;
-            } else {
-                execute!(
-                    terminal.backend_mut(),
-                    DisableMouseCapture,
-                    EnableMouseCapture
-                )?;
-                state.priming_mode = false;
             }
-        } else {
-            let event = event::read()?;
-            match state.handle_event(event, &mut terminal)? {
-                EventResult::Quit => break,
-                EventResult::NextDocument => {
-                    queue.next();
-                    state.on_document_changed();
-                }
-                EventResult::PreviousDocument => {
-                    queue.prev();
-                    state.on_document_changed();
+            state.recapture_mouse(&mut terminal)?;
+            state.priming_mode = false;
+        } else if state.scroll_target.is_some() {
+            if event::poll(SMOOTH_SCROLL_FRAME)? {
+                let event = event::read()?;
+                let result = state.handle_event(event, &mut terminal)?;
+                if apply_event_result(result, &mut queue, &mut state, &picker_root) {
+                    break;
                 }
-                EventResult::OpenPicker => {
-                    state.open_picker(picker_root.clone());
+            } else {
+                state.advance_smooth_scroll();
+            }
+        } else if state.watch {
+            if event::poll(WATCH_POLL_INTERVAL)? {
+                let event = event::read()?;
+                let result = state.handle_event(event, &mut terminal)?;
+                if apply_event_result(result, &mut queue, &mut state, &picker_root) {
+                    break;
                 }
-                EventResult::OpenGoDialog => {
-                    state.open_go_dialog(queue.len(), queue.current_index());
+            }
+            reload_if_modified(&mut queue, &mut state);
+        } else if let Some(remaining) = state.flash_time_left(Instant::now()) {
+            // Wake up when the flash is due to expire so the next draw
+            // clears it even if no key is pressed.
+            if event::poll(remaining)? {
+                let event = event::read()?;
+                let result = state.handle_event(event, &mut terminal)?;
+                if apply_event_result(result, &mut queue, &mut state, &picker_root) {
+                    break;
                 }
-                EventResult::OpenPath(path) => {
-                    let mut switched = queue.focus_existing(&path);
-                    if !switched && let Ok(content) = fs::read_to_string(&path) {
+            }
+        } else {
+            let event = event::read()?;
+            let result = state.handle_event(event, &mut terminal)?;
+            if apply_event_result(result, &mut queue, &mut state, &picker_root) {
+                break;
+            }
+        }
+    }
+
+    guard.restore()?;
+    let view = SavedView {
+        beeline: state.beeline_enabled,
+        plain: state.plain_mode,
+        theme: theme_choice,
+    };
+    Ok((queue, view))
+}
+
+type PanicHook = Box<dyn Fn(&panic::PanicHookInfo<'_>) + Sync + Send + 'static>;
+
+/// Raw mode plus a panic hook that restores the terminal before reporting.
+/// Dropping the guard restores the terminal and the previous hook, so every
+/// way out of [`run_tui`], including an I/O error, leaves the shell usable.
+struct TerminalGuard {
+    previous_hook: Option<Arc<PanicHook>>,
+}
+
+impl TerminalGuard {
+    fn enable() -> io::Result<Self> {
+        enable_raw_mode()?;
+        let previous_hook = Arc::new(panic::take_hook());
+        {
+            let previous_hook = Arc::clone(&previous_hook);
+            panic::set_hook(Box::new(move |info| {
+                let _ = restore_terminal();
+                previous_hook(info);
+            }));
+        }
+        Ok(Self {
+            previous_hook: Some(previous_hook),
+        })
+    }
+
+    /// Restores the terminal on normal exit, reporting any error.
+    fn restore(mut self) -> io::Result<()> {
+        self.reinstall_previous_hook();
+        restore_terminal()
+    }
+
+    /// Puts the hook from before [`enable`](Self::enable) back; `false` if
+    /// that already happened.
+    fn reinstall_previous_hook(&mut self) -> bool {
+        let Some(previous_hook) = self.previous_hook.take() else {
+            return false;
+        };
+        // Hooks cannot be changed while unwinding; the process is exiting.
+        if !std::thread::panicking() {
+            drop(panic::take_hook());
+            panic::set_hook(Box::new(move |info| previous_hook(info)));
+        }
+        true
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.reinstall_previous_hook() {
+            let _ = restore_terminal();
+        }
+    }
+}
+
+/// Puts the terminal back into cooked mode on the main screen. Safe to call
+/// more than once, which lets both the panic hook and normal exit use it.
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        DisableMouseCapture,
+        DisableFocusChange,
+        LeaveAlternateScreen,
+        Show
+    )
+}
+
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const FLASH_DURATION: Duration = Duration::from_secs(2);
+/// Renders slower than this report their time in the footer.
+const SLOW_RENDER_THRESHOLD: Duration = Duration::from_millis(200);
+const PICKER_PREVIEW_LINES: usize = 20;
+const SMOOTH_SCROLL_FRAME: Duration = Duration::from_millis(16);
+/// Each frame covers this fraction (1/n) of the remaining distance.
+const SMOOTH_SCROLL_DIVISOR: u16 = 3;
+
+/// Next scroll position when easing from `current` toward `target`: a third
+/// of the remaining distance, at least one row, never overshooting.
+fn smooth_scroll_step(current: u16, target: u16) -> u16 {
+    let step = (current.abs_diff(target) / SMOOTH_SCROLL_DIVISOR).max(1);
+    if current < target {
+        current.saturating_add(step).min(target)
+    } else {
+        current.saturating_sub(step).max(target)
+    }
+}
+
+/// Reloads the focused document when its modification time changes. The
+/// first check for a document only records its timestamp.
+fn reload_if_modified(queue: &mut DocumentQueue, state: &mut AppState) {
+    let path = queue.current().path.clone();
+    let Ok(modified) = fs::metadata(&path).and_then(|meta| meta.modified()) else {
+        return;
+    };
+    let previous = state.watched.replace((path.clone(), modified));
+    match previous {
+        Some((watched, seen)) if watched == path && seen != modified => {}
+        _ => return,
+    }
+    // A touch without a real edit bumps the mtime; only redraw on new content.
+    if let Ok(content) = read_document(&path, state.lossy_utf8)
+        && queue.replace_current_content(content)
+    {
+        state.on_document_reloaded(Instant::now());
+    }
+}
+
+fn apply_event_result(
+    result: EventResult,
+    queue: &mut DocumentQueue,
+    state: &mut AppState,
+    picker_root: &Path,
+) -> bool {
+    match result {
+        EventResult::Quit => return true,
+        EventResult::NextDocument => {
+            if state.wrap_queue {
+                queue.next();
+                state.on_document_changed();
+            } else if queue.next_clamped() {
+                state.on_document_changed();
+            } else {
+                state.flash = Some(("End of queue".to_string(), Instant::now()));
+            }
+        }
+        EventResult::PreviousDocument => {
+            if state.wrap_queue {
+                queue.prev();
+                state.on_document_changed();
+            } else if queue.prev_clamped() {
+                state.on_document_changed();
+            } else {
+                state.flash = Some(("Start of queue".to_string(), Instant::now()));
+            }
+        }
+        EventResult::AlternateDocument => {
+            if queue.focus_alternate() {
+                state.on_document_changed();
+            } else {
+                state.flash = Some(("No alternate document".to_string(), Instant::now()));
+            }
+        }
+        EventResult::OpenPicker => {
+            state.open_picker(picker_root.to_path_buf());
+        }
+        EventResult::CopyDocumentPath => {
+            let (sequence, message) = document_path_copy(&queue.current().canonical_path);
+            let mut stdout = io::stdout();
+            state.status_message = Some(
+                match write!(stdout, "{}", sequence).and_then(|_| stdout.flush()) {
+                    Ok(()) => message,
+                    Err(err) => format!("Could not copy path: {}", err),
+                },
+            );
+        }
+        EventResult::OpenPickerHere => {
+            state.open_picker(document_picker_dir(&queue.current().path, picker_root));
+        }
+        EventResult::OpenGoDialog => {
+            state.open_go_dialog(&pin_states(queue), queue.current_index());
+        }
+        EventResult::TogglePin => {
+            let message = if queue.toggle_pin() {
+                "Pinned"
+            } else {
+                "Unpinned"
+            };
+            state.flash = Some((message.to_string(), Instant::now()));
+        }
+        EventResult::OpenGlobalSearch => {
+            let docs: Vec<Vec<String>> = queue
+                .documents()
+                .iter()
+                .map(|doc| state.searchable_lines(doc))
+                .collect();
+            state.open_global_search(docs);
+        }
+        EventResult::OpenPath(path) => {
+            if queue.focus_existing(&path) {
+                state.on_document_changed();
+            } else {
+                match read_document(&path, state.lossy_utf8) {
+                    Ok(content) => {
                         queue.push_and_focus(QueuedDocument::new(path, content));
-                        switched = true;
-                    }
-                    if switched {
                         state.on_document_changed();
                     }
+                    Err(err) => state.status_message = Some(open_error_message(&path, &err)),
                 }
-                EventResult::GoToIndex(index) => {
-                    if queue.focus_index(index) {
-                        state.on_document_changed();
+            }
+        }
+        EventResult::OpenPaths(paths) => {
+            let mut first = None;
+            for path in paths {
+                if !queue.focus_existing(&path) {
+                    match read_document(&path, state.lossy_utf8) {
+                        Ok(content) => queue.push_and_focus(QueuedDocument::new(path, content)),
+                        Err(err) => {
+                            state.status_message = Some(open_error_message(&path, &err));
+                            continue;
+                        }
                     }
                 }
-                EventResult::Continue => {}
+                first.get_or_insert(queue.current_index());
+            }
+            if let Some(index) = first {
+                queue.focus_index(index);
+                state.on_document_changed();
+            }
+        }
+        EventResult::MoveDocument { from, to } => {
+            if queue.move_item(from, to) {
+                state.render_cache.invalidate();
+                state.go_dialog_order = go_dialog_order(&pin_states(queue));
+            }
+        }
+        EventResult::GoToIndex(index) => {
+            if queue.focus_index(index) {
+                state.on_document_changed();
             }
         }
+        EventResult::RunCommand(command) => run_command(command, queue, state),
+        EventResult::Continue => {}
     }
+    false
+}
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        DisableMouseCapture,
-        DisableFocusChange,
-        LeaveAlternateScreen
-    )?;
-    terminal.show_cursor()?;
-    Ok(())
+fn run_command(command: command::Command, queue: &mut DocumentQueue, state: &mut AppState) {
+    match command {
+        command::Command::Reload => {
+            let path = queue.current().path.clone();
+            match read_document(&path, state.lossy_utf8) {
+                Ok(content) => {
+                    // An explicit reload is acknowledged even when nothing changed.
+                    let _ = queue.replace_current_content(content);
+                    state.on_document_reloaded(Instant::now());
+                }
+                Err(err) => state.status_message = Some(open_error_message(&path, &err)),
+            }
+        }
+        command::Command::Theme(background) => state.pending_theme = Some(background),
+        command::Command::Toc => state.open_toc(),
+        command::Command::About => state.open_about(),
+        command::Command::ExportHtml(out) => {
+            let doc = queue.current();
+            let title = doc
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| doc.path.display().to_string());
+            let html = render_html_document(&title, &doc.content);
+            state.status_message = Some(match fs::write(&out, html) {
+                Ok(()) => format!("Exported {}", out.display()),
+                Err(err) => format!("Failed to write {}: {}", out.display(), err),
+            });
+        }
+        // Handled by the prompt, which knows the current scroll limits.
+        command::Command::Goto(_) => {}
+    }
+}
+
+/// Palette for `background`, keeping a code theme the user picked explicitly.
+fn switch_palette(current: &Theme, background: Background) -> Theme {
+    let (mut next, other) = match background {
+        Background::Light => (Theme::light(), Theme::dark()),
+        Background::Dark | Background::Unknown => (Theme::dark(), Theme::light()),
+    };
+    if current.code_theme != other.code_theme {
+        next.code_theme = current.code_theme;
+    }
+    next
 }
 
 struct AppState {
@@ -176,12 +489,18 @@ struct AppState {
     viewport_height: u16,
     rendered_lines: u16,
     beeline_enabled: bool,
+    bionic_enabled: bool,
+    /// BeeLine tint strength as a percentage (0-100).
+    beeline_intensity: u8,
     plain_mode: bool,
     show_help: bool,
     search_mode: bool,
     search_query: String,
     search_matches: Vec<SearchMatch>,
     search_index: usize,
+    /// List of every match with a snippet, toggled with Tab while searching.
+    search_results_open: bool,
+    search_results_selected: usize,
     current_links: Vec<LinkTarget>,
     current_line_offsets: Vec<u16>,
     current_wraps: Vec<LineWrap>,
@@ -195,10 +514,92 @@ struct AppState {
     picker_query: String,
     picker_dir: PathBuf,
     picker_entries: Vec<PickerEntry>,
+    /// Rendered previews of picker entries, keyed by path; cleared on close.
+    picker_previews: HashMap<PathBuf, Vec<Line<'static>>>,
     picker_selected: usize,
+    /// Files marked with Space, opened together on Enter.
+    picker_marked: HashSet<PathBuf>,
     go_dialog_open: bool,
     go_dialog_total: usize,
+    /// Queue index shown on each go-dialog row: pinned documents first.
+    go_dialog_order: Vec<usize>,
+    /// How many leading go-dialog rows are pinned.
+    go_dialog_pinned: usize,
+    /// Selected go-dialog row (not queue index).
     go_dialog_selected: usize,
+    global_search_open: bool,
+    global_search_query: String,
+    global_search_docs: Vec<Vec<String>>,
+    global_search_results: Vec<GlobalSearchResult>,
+    global_search_selected: usize,
+    pending_search_jump: Option<(String, usize)>,
+    scroll_to_search_match: bool,
+    render_cache: RenderCache,
+    force_full_render: bool,
+    large_document_fallback: bool,
+    current_stats: DocStats,
+    show_stats: bool,
+    /// Overlay heading and search-match ticks on the scrollbar column.
+    show_minimap: bool,
+    /// Split the view with the raw markdown source on the left.
+    show_source: bool,
+    no_wrap: bool,
+    h_scroll: u16,
+    max_line_width: u16,
+    viewport_width: u16,
+    words_per_minute: usize,
+    center_search_matches: bool,
+    render_options: RenderOptions,
+    browser: Option<String>,
+    link_schemes: LinkSchemes,
+    keymap: KeyMap,
+    lossy_utf8: bool,
+    color_depth: ColorDepth,
+    selection: Option<Selection>,
+    /// Text typed after `:`, while the command prompt is open.
+    line_prompt: Option<String>,
+    /// Parallel to `current_lines_text`: source line of each rendered line.
+    current_source_lines: Vec<usize>,
+    current_headings: Vec<HeadingAnchor>,
+    /// True while the left button is held after starting a selection.
+    selecting: bool,
+    /// Transient footer message, cleared on the next key press.
+    status_message: Option<String>,
+    /// Footer message shown until `FLASH_DURATION` has passed.
+    flash: Option<(String, Instant)>,
+    watch: bool,
+    follow: bool,
+    /// Path and modification time last seen by `--watch`.
+    watched: Option<(PathBuf, SystemTime)>,
+    reload_count: usize,
+    smooth_scroll: bool,
+    /// Keep the search query when switching documents.
+    sticky_search: bool,
+    line_numbers: bool,
+    wrap_queue: bool,
+    padding: u16,
+    max_text_width: Option<u16>,
+    /// Heading slug to jump to once the document has been laid out.
+    pending_heading: Option<String>,
+    pending_start_line: Option<usize>,
+    /// Content at the top of the view before a resize, restored once the
+    /// document is laid out at the new width.
+    resize_anchor: Option<ScrollAnchor>,
+    /// Number typed ahead of a command such as `50%`.
+    count_prefix: Option<u16>,
+    mouse_enabled: bool,
+    any_text: bool,
+    cursor_line_enabled: bool,
+    /// Logical line highlighted while the cursor line is enabled.
+    cursor_line: usize,
+    /// Where an in-progress smooth page scroll is heading.
+    scroll_target: Option<u16>,
+    toc_open: bool,
+    /// The `:about` diagnostics overlay.
+    about_open: bool,
+    toc_selected: usize,
+    /// Palette requested by `:theme`, applied by the run loop.
+    pending_theme: Option<Background>,
 }
 
 struct RenderContext<'a> {
@@ -207,6 +608,8 @@ struct RenderContext<'a> {
     queue_index: usize,
     queue_len: usize,
     queue_paths: &'a [String],
+    /// Not a markdown file (only opened with `--any-text`); shown in plain mode.
+    plain_text: bool,
 }
 
 impl AppState {
@@ -216,12 +619,16 @@ impl AppState {
             viewport_height: 0,
             rendered_lines: 0,
             beeline_enabled: enable_beeline,
+            bionic_enabled: false,
+            beeline_intensity: 100,
             plain_mode: false,
             show_help: false,
             search_mode: false,
             search_query: String::new(),
             search_matches: Vec::new(),
             search_index: 0,
+            search_results_open: false,
+            search_results_selected: 0,
             current_links: Vec::new(),
             current_line_offsets: Vec::new(),
             current_wraps: Vec::new(),
@@ -235,10 +642,70 @@ impl AppState {
             picker_query: String::new(),
             picker_dir: PathBuf::new(),
             picker_entries: Vec::new(),
+            picker_previews: HashMap::new(),
             picker_selected: 0,
+            picker_marked: HashSet::new(),
             go_dialog_open: false,
             go_dialog_total: 0,
+            go_dialog_order: Vec::new(),
+            go_dialog_pinned: 0,
             go_dialog_selected: 0,
+            global_search_open: false,
+            global_search_query: String::new(),
+            global_search_docs: Vec::new(),
+            global_search_results: Vec::new(),
+            global_search_selected: 0,
+            pending_search_jump: None,
+            scroll_to_search_match: false,
+            render_cache: RenderCache::default(),
+            force_full_render: false,
+            large_document_fallback: false,
+            current_stats: DocStats::default(),
+            show_stats: false,
+            show_minimap: false,
+            show_source: false,
+            no_wrap: false,
+            h_scroll: 0,
+            max_line_width: 0,
+            viewport_width: 0,
+            center_search_matches: true,
+            render_options: RenderOptions::default(),
+            browser: None,
+            link_schemes: LinkSchemes::default(),
+            keymap: KeyMap::default(),
+            lossy_utf8: false,
+            color_depth: ColorDepth::TrueColor,
+            selection: None,
+            line_prompt: None,
+            current_source_lines: Vec::new(),
+            current_headings: Vec::new(),
+            selecting: false,
+            status_message: None,
+            flash: None,
+            watch: false,
+            follow: false,
+            watched: None,
+            reload_count: 0,
+            smooth_scroll: false,
+            count_prefix: None,
+            sticky_search: false,
+            line_numbers: false,
+            wrap_queue: true,
+            padding: 0,
+            max_text_width: None,
+            pending_heading: None,
+            pending_start_line: None,
+            resize_anchor: None,
+            mouse_enabled: true,
+            any_text: false,
+            cursor_line_enabled: false,
+            cursor_line: 0,
+            scroll_target: None,
+            toc_open: false,
+            about_open: false,
+            toc_selected: 0,
+            pending_theme: None,
+            words_per_minute: DEFAULT_WORDS_PER_MINUTE,
         }
     }
 
@@ -250,8 +717,16 @@ impl AppState {
 
     fn on_document_changed(&mut self) {
         self.scroll = 0;
+        self.cursor_line = 0;
         self.search_mode = false;
-        self.clear_search_state();
+        self.line_prompt = None;
+        if self.sticky_search && !self.search_query.is_empty() {
+            // Matches are recomputed for the new document on the next render.
+            self.reset_search_matches();
+            self.scroll_to_search_match = true;
+        } else {
+            self.clear_search_state();
+        }
         self.hover_link = None;
         self.show_help = false;
         self.scroll_before_help = None;
@@ -259,8 +734,46 @@ impl AppState {
         self.current_line_offsets.clear();
         self.current_wraps.clear();
         self.current_lines_text.clear();
+        self.current_stats = DocStats::default();
+        self.render_cache.invalidate();
+        self.selection = None;
+        self.selecting = false;
         self.close_picker();
         self.close_go_dialog();
+        self.close_global_search();
+        self.toc_open = false;
+        if let Some((query, ordinal)) = self.pending_search_jump.take() {
+            self.search_query = query;
+            self.search_index = ordinal;
+            self.scroll_to_search_match = true;
+        }
+    }
+
+    /// Keeps scroll and search state, but drops everything derived from the
+    /// old content.
+    fn on_document_reloaded(&mut self, now: Instant) {
+        let was_at_bottom = is_at_bottom(self.scroll, self.rendered_lines, self.viewport_height);
+        self.scroll = follow_scroll(self.follow, was_at_bottom, self.scroll);
+        self.render_cache.invalidate();
+        self.selection = None;
+        self.selecting = false;
+        self.reload_count += 1;
+        self.flash = Some((format!("⟳ reloaded ({})", self.reload_count), now));
+    }
+
+    fn expire_flash(&mut self, now: Instant) {
+        if let Some((_, shown_at)) = &self.flash
+            && flash_expired(now.saturating_duration_since(*shown_at))
+        {
+            self.flash = None;
+        }
+    }
+
+    /// How long the footer flash has left to show, or `None` without one.
+    fn flash_time_left(&self, now: Instant) -> Option<Duration> {
+        self.flash.as_ref().map(|(_, shown_at)| {
+            FLASH_DURATION.saturating_sub(now.saturating_duration_since(*shown_at))
+        })
     }
 
     fn open_picker(&mut self, start_dir: PathBuf) {
@@ -278,19 +791,26 @@ impl AppState {
         self.picker_open = false;
         self.picker_query.clear();
         self.picker_entries.clear();
+        self.picker_previews.clear();
+        self.picker_marked.clear();
         self.picker_dir = PathBuf::new();
         self.picker_selected = 0;
     }
 
-    fn open_go_dialog(&mut self, total: usize, current_index: usize) {
+    /// Opens the go dialog over a queue whose documents have the given pin
+    /// states, selecting the row of `current_index`.
+    fn open_go_dialog(&mut self, pinned: &[bool], current_index: usize) {
         self.close_picker();
+        let total = pinned.len();
         self.go_dialog_open = total > 0;
         self.go_dialog_total = total;
-        self.go_dialog_selected = if total == 0 {
-            0
-        } else {
-            current_index.min(total - 1)
-        };
+        self.go_dialog_order = go_dialog_order(pinned);
+        self.go_dialog_pinned = pinned.iter().filter(|&&pin| pin).count();
+        self.go_dialog_selected = self
+            .go_dialog_order
+            .iter()
+            .position(|&idx| idx == current_index)
+            .unwrap_or(0);
         self.search_mode = false;
         self.show_help = false;
         self.hover_link = None;
@@ -299,29 +819,328 @@ impl AppState {
     fn close_go_dialog(&mut self) {
         self.go_dialog_open = false;
         self.go_dialog_total = 0;
+        self.go_dialog_order.clear();
+        self.go_dialog_pinned = 0;
         self.go_dialog_selected = 0;
     }
 
+    /// Text of `doc` as the reader shows it at the current width, so global
+    /// search results count the same matches `/` finds after the jump.
+    fn searchable_lines(&self, doc: &QueuedDocument) -> Vec<String> {
+        let plain = self.plain_mode
+            || use_plain_fallback(doc.content.len(), self.force_full_render)
+            || !is_markdown(&doc.path);
+        let lines = if plain {
+            render_plain_lines(&doc.content, &self.render_options)
+        } else {
+            let width = if self.no_wrap {
+                0
+            } else {
+                self.content_area.width
+            };
+            render_markdown_with_links(&doc.content, width, &Theme::dark(), &self.render_options)
+                .lines
+        };
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn open_global_search(&mut self, docs: Vec<Vec<String>>) {
+        self.close_picker();
+        self.close_go_dialog();
+        self.global_search_open = true;
+        self.global_search_query.clear();
+        self.global_search_docs = docs;
+        self.global_search_results.clear();
+        self.global_search_selected = 0;
+        self.search_mode = false;
+        self.show_help = false;
+        self.hover_link = None;
+    }
+
+    fn close_global_search(&mut self) {
+        self.global_search_open = false;
+        self.global_search_query.clear();
+        self.global_search_docs.clear();
+        self.global_search_results.clear();
+        self.global_search_selected = 0;
+    }
+
+    /// Opens the heading list with the section containing the top row selected.
+    fn open_toc(&mut self) {
+        if self.current_headings.is_empty() {
+            self.status_message = Some("No headings in this document".to_string());
+            return;
+        }
+        self.close_picker();
+        self.close_go_dialog();
+        self.close_global_search();
+        self.toc_open = true;
+        self.toc_selected = self
+            .current_headings
+            .iter()
+            .rposition(|heading| heading.scroll_pos <= self.scroll)
+            .unwrap_or(0);
+        self.search_mode = false;
+        self.show_help = false;
+        self.hover_link = None;
+    }
+
+    fn refresh_global_search_results(&mut self) {
+        self.global_search_results =
+            build_global_matches(&self.global_search_docs, &self.global_search_query);
+        if self.global_search_selected >= self.global_search_results.len() {
+            self.global_search_selected = self.global_search_results.len().saturating_sub(1);
+        }
+    }
+
     fn refresh_picker_entries(&mut self) {
         self.picker_entries =
-            list_entries(self.picker_dir.clone(), &self.picker_query).unwrap_or_default();
+            list_entries(self.picker_dir.clone(), &self.picker_query, self.any_text)
+                .unwrap_or_default();
         if self.picker_selected >= self.picker_entries.len() {
             self.picker_selected = self.picker_entries.len().saturating_sub(1);
         }
     }
 
+    fn match_scroll_target(&self, pos: u16, max_scroll: u16) -> u16 {
+        jump_scroll_target(
+            pos,
+            self.viewport_height,
+            max_scroll,
+            self.center_search_matches,
+        )
+    }
+
+    /// Moves the cursor line by `delta` logical lines and scrolls just enough
+    /// to keep all of its rows in view.
+    fn move_cursor_line(&mut self, delta: isize) {
+        let last = self.current_lines_text.len().saturating_sub(1);
+        self.cursor_line = self.cursor_line.saturating_add_signed(delta).min(last);
+        let Some(&start) = self.current_line_offsets.get(self.cursor_line) else {
+            return;
+        };
+        let end = start.saturating_add(line_rows(&self.current_wraps, self.cursor_line));
+        if start < self.scroll {
+            self.scroll = start;
+        } else if end > self.scroll.saturating_add(self.viewport_height) {
+            self.scroll = end.saturating_sub(self.viewport_height);
+        }
+    }
+
+    fn scroll_page(&mut self, down: bool, page: u16, max_scroll: u16, pending: Option<u16>) {
+        if !self.smooth_scroll {
+            self.scroll = if down {
+                self.scroll.saturating_add(page)
+            } else {
+                self.scroll.saturating_sub(page)
+            };
+            return;
+        }
+        let base = pending.unwrap_or(self.scroll);
+        self.scroll_target = Some(if down {
+            base.saturating_add(page).min(max_scroll)
+        } else {
+            base.saturating_sub(page)
+        });
+    }
+
+    /// Moves one animation frame toward `scroll_target`, clearing it on arrival.
+    fn advance_smooth_scroll(&mut self) {
+        if let Some(target) = self.scroll_target {
+            self.scroll = smooth_scroll_step(self.scroll, target);
+            if self.scroll == target {
+                self.scroll_target = None;
+            }
+        }
+    }
+
+    fn handle_line_prompt_key_input(&mut self, code: KeyCode, max_scroll: u16) -> EventResult {
+        let Some(input) = self.line_prompt.as_mut() else {
+            return EventResult::Continue;
+        };
+        match code {
+            KeyCode::Esc => self.line_prompt = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(ch) => input.push(ch),
+            KeyCode::Enter => {
+                let input = self.line_prompt.take().unwrap_or_default();
+                match parse_command(&input) {
+                    Ok(command::Command::Goto(line_number)) => {
+                        self.scroll = scroll_for_source_line(
+                            &self.current_source_lines,
+                            &self.current_line_offsets,
+                            line_number,
+                        )
+                        .min(max_scroll);
+                    }
+                    Ok(command) => return EventResult::RunCommand(command),
+                    Err(err) => self.status_message = Some(err),
+                }
+            }
+            _ => {}
+        }
+        EventResult::Continue
+    }
+
+    fn handle_toc_key_input(&mut self, code: KeyCode, max_scroll: u16) -> EventResult {
+        let last = self.current_headings.len().saturating_sub(1);
+        match code {
+            KeyCode::Esc => self.toc_open = false,
+            KeyCode::Up | KeyCode::BackTab => {
+                self.toc_selected = self.toc_selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                self.toc_selected = (self.toc_selected + 1).min(last);
+            }
+            KeyCode::Home => self.toc_selected = 0,
+            KeyCode::End => self.toc_selected = last,
+            KeyCode::Enter => {
+                if let Some(pos) = self
+                    .current_headings
+                    .get(self.toc_selected)
+                    .map(|heading| heading.scroll_pos)
+                {
+                    self.scroll = self.match_scroll_target(pos, max_scroll);
+                }
+                self.toc_open = false;
+            }
+            _ => {}
+        }
+        EventResult::Continue
+    }
+
+    /// Like [`handle_key_input`](Self::handle_key_input), but also sees
+    /// modifiers: Ctrl-C quits unless a search query is being typed, and
+    /// Ctrl-^ switches to the alternate document.
+    fn handle_key_event(&mut self, key: KeyEvent, max_scroll: u16, page: u16) -> KeyAction {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        if key.code == KeyCode::Char('c') && control {
+            return if self.search_mode {
+                KeyAction::None
+            } else {
+                KeyAction::Quit
+            };
+        }
+        // Terminals report Ctrl-^ as either `^` or `6` with Control held.
+        if matches!(key.code, KeyCode::Char('^' | '6')) && control && !self.search_mode {
+            return KeyAction::AlternateDocument;
+        }
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            && let Some(action) = self.mapped_key_action(key.code, key.modifiers, max_scroll)
+        {
+            self.scroll_target = None;
+            self.count_prefix = None;
+            return action;
+        }
+        self.handle_key_input(key.code, max_scroll, page)
+    }
+
+    /// Runs the action the keymap binds to this key. Quit works everywhere;
+    /// the rest are ignored while typing a search or reading the help.
+    fn mapped_key_action(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        max_scroll: u16,
+    ) -> Option<KeyAction> {
+        let action = self.keymap.action_for(code, modifiers)?;
+        if action != Action::Quit && (self.search_mode || self.show_help) {
+            return None;
+        }
+        let positions =
+            || -> Vec<u16> { self.current_headings.iter().map(|h| h.scroll_pos).collect() };
+        // A centered heading sits mid-viewport, so search onward from there
+        // rather than from the top row.
+        let focus = if self.center_search_matches {
+            self.scroll.saturating_add(self.viewport_height / 2)
+        } else {
+            self.scroll
+        };
+        Some(match action {
+            Action::Quit => KeyAction::Quit,
+            Action::NextDocument => KeyAction::NextDocument,
+            Action::PreviousDocument => KeyAction::PreviousDocument,
+            Action::AlternateDocument => KeyAction::AlternateDocument,
+            Action::NextHeading => {
+                if let Some(pos) = next_heading_pos(&positions(), focus) {
+                    self.scroll = self.match_scroll_target(pos, max_scroll);
+                }
+                KeyAction::None
+            }
+            Action::PreviousHeading => {
+                if let Some(pos) = previous_heading_pos(&positions(), focus) {
+                    self.scroll = self.match_scroll_target(pos, max_scroll);
+                }
+                KeyAction::None
+            }
+            Action::GoDialog => KeyAction::OpenGoDialog,
+            Action::TogglePin => KeyAction::TogglePin,
+            Action::OpenPicker => KeyAction::OpenPicker,
+            Action::OpenPickerHere => KeyAction::OpenPickerHere,
+            Action::CopyPath => KeyAction::CopyDocumentPath,
+            Action::GlobalSearch => KeyAction::OpenGlobalSearch,
+        })
+    }
+
     fn handle_key_input(&mut self, code: KeyCode, max_scroll: u16, page: u16) -> KeyAction {
+        // Any key interrupts a running page animation; paging keys restart it
+        // from the pending target so repeated presses accumulate.
+        let pending_target = self.scroll_target.take();
+        // Digits typed before a command; any other key consumes them.
+        let count = self.count_prefix.take();
+        if let Some(action) = self.mapped_key_action(code, KeyModifiers::NONE, max_scroll) {
+            return action;
+        }
         match code {
-            KeyCode::Char('q') => KeyAction::Quit,
+            KeyCode::Char(':') if !self.search_mode && !self.show_help => {
+                self.line_prompt = Some(String::new());
+                KeyAction::None
+            }
             KeyCode::Char('/') if !self.show_help => {
                 self.search_mode = true;
                 self.clear_search_state();
                 KeyAction::None
             }
+            KeyCode::Tab if self.search_mode => {
+                self.search_results_open = !self.search_results_open;
+                self.search_results_selected = self.search_index;
+                KeyAction::None
+            }
+            KeyCode::Up if self.search_results_open => {
+                self.search_results_selected = self.search_results_selected.saturating_sub(1);
+                KeyAction::None
+            }
+            KeyCode::Down if self.search_results_open => {
+                self.search_results_selected = (self.search_results_selected + 1)
+                    .min(self.search_matches.len().saturating_sub(1));
+                KeyAction::None
+            }
+            KeyCode::Enter if self.search_results_open => {
+                self.search_mode = false;
+                self.search_results_open = false;
+                if let Some(pos) = self
+                    .search_matches
+                    .get(self.search_results_selected)
+                    .map(|m| m.scroll_pos)
+                {
+                    self.search_index = self.search_results_selected;
+                    self.scroll = self.match_scroll_target(pos, max_scroll);
+                }
+                KeyAction::None
+            }
+            KeyCode::Esc if self.search_results_open => {
+                self.search_results_open = false;
+                KeyAction::None
+            }
             KeyCode::Enter if self.search_mode => {
                 self.search_mode = false;
+                self.search_results_open = false;
                 if let Some(pos) = self.search_matches.first().map(|m| m.scroll_pos) {
-                    self.scroll = pos.min(max_scroll);
+                    self.scroll = self.match_scroll_target(pos, max_scroll);
                 }
                 KeyAction::None
             }
@@ -345,12 +1164,23 @@ impl AppState {
                 self.reset_search_matches();
                 KeyAction::None
             }
+            KeyCode::Char(ch @ '0'..='9') if !self.search_mode && !self.show_help => {
+                let digit = ch as u16 - u16::from(b'0');
+                self.count_prefix =
+                    Some(count.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                KeyAction::None
+            }
+            KeyCode::Char('%') if !self.search_mode && !self.show_help => {
+                if let Some(percent) = count {
+                    self.scroll = scroll_for_percent(percent, max_scroll);
+                }
+                KeyAction::None
+            }
             KeyCode::Char('n') if !self.search_mode && !self.show_help => {
                 if !self.search_matches.is_empty() {
                     self.search_index = (self.search_index + 1) % self.search_matches.len();
-                    self.scroll = self.search_matches[self.search_index]
-                        .scroll_pos
-                        .min(max_scroll);
+                    let pos = self.search_matches[self.search_index].scroll_pos;
+                    self.scroll = self.match_scroll_target(pos, max_scroll);
                 }
                 KeyAction::None
             }
@@ -361,9 +1191,8 @@ impl AppState {
                     } else {
                         self.search_index -= 1;
                     }
-                    self.scroll = self.search_matches[self.search_index]
-                        .scroll_pos
-                        .min(max_scroll);
+                    let pos = self.search_matches[self.search_index].scroll_pos;
+                    self.scroll = self.match_scroll_target(pos, max_scroll);
                 }
                 KeyAction::None
             }
@@ -378,40 +1207,107 @@ impl AppState {
             }
             KeyCode::Char('b') => {
                 self.beeline_enabled = !self.beeline_enabled;
+                if self.beeline_enabled {
+                    self.bionic_enabled = false;
+                }
+                KeyAction::None
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.beeline_intensity = self
+                    .beeline_intensity
+                    .saturating_add(BEELINE_INTENSITY_STEP)
+                    .min(100);
+                KeyAction::None
+            }
+            KeyCode::Char('-') => {
+                self.beeline_intensity = self
+                    .beeline_intensity
+                    .saturating_sub(BEELINE_INTENSITY_STEP);
+                KeyAction::None
+            }
+            KeyCode::Char('B') => {
+                self.bionic_enabled = !self.bionic_enabled;
+                if self.bionic_enabled {
+                    self.beeline_enabled = false;
+                }
                 KeyAction::None
             }
             KeyCode::Char('m') => {
                 self.plain_mode = !self.plain_mode;
                 KeyAction::None
             }
-            KeyCode::Char(']') if !self.search_mode && !self.show_help => KeyAction::NextDocument,
-            KeyCode::Char('[') if !self.search_mode && !self.show_help => {
-                KeyAction::PreviousDocument
+            KeyCode::Char('w') => {
+                self.show_stats = !self.show_stats;
+                KeyAction::None
             }
-            KeyCode::Char('g') if !self.search_mode && !self.show_help => KeyAction::OpenGoDialog,
-            KeyCode::Char('o') if !self.search_mode && !self.show_help => KeyAction::OpenPicker,
-            KeyCode::Down => {
-                self.scroll = self.scroll.saturating_add(1);
+            KeyCode::Char('s') if !self.search_mode && !self.show_help => {
+                self.show_source = !self.show_source;
                 KeyAction::None
             }
-            KeyCode::Up => {
-                self.scroll = self.scroll.saturating_sub(1);
+            KeyCode::Char('M') => {
+                self.show_minimap = !self.show_minimap;
+                KeyAction::None
+            }
+            KeyCode::Char('L') => {
+                self.line_numbers = !self.line_numbers;
+                KeyAction::None
+            }
+            KeyCode::Char('c') if !self.search_mode && !self.show_help => {
+                self.cursor_line_enabled = !self.cursor_line_enabled;
+                if self.cursor_line_enabled {
+                    self.cursor_line = line_from_rendered(&self.current_line_offsets, self.scroll)
+                        .map_or(0, |(line_idx, _)| line_idx);
+                }
+                KeyAction::None
+            }
+            KeyCode::Char('j')
+                if self.cursor_line_enabled && !self.search_mode && !self.show_help =>
+            {
+                self.move_cursor_line(1);
+                KeyAction::None
+            }
+            KeyCode::Char('k')
+                if self.cursor_line_enabled && !self.search_mode && !self.show_help =>
+            {
+                self.move_cursor_line(-1);
+                KeyAction::None
+            }
+            KeyCode::Char('D') => {
+                self.render_options.diagram_source = !self.render_options.diagram_source;
+                self.render_cache.invalidate();
+                KeyAction::None
+            }
+            KeyCode::Char('W') => {
+                self.no_wrap = !self.no_wrap;
+                self.h_scroll = 0;
+                KeyAction::None
+            }
+            KeyCode::Left if self.no_wrap => {
+                self.h_scroll = self.h_scroll.saturating_sub(H_SCROLL_STEP);
+                KeyAction::None
+            }
+            KeyCode::Right if self.no_wrap => {
+                self.h_scroll = clamp_h_scroll(
+                    self.h_scroll.saturating_add(H_SCROLL_STEP),
+                    self.max_line_width,
+                    self.viewport_width,
+                );
                 KeyAction::None
             }
-            KeyCode::PageDown => {
-                self.scroll = self.scroll.saturating_add(page);
+            KeyCode::Down => {
+                self.scroll = self.scroll.saturating_add(1);
                 KeyAction::None
             }
-            KeyCode::PageUp => {
-                self.scroll = self.scroll.saturating_sub(page);
+            KeyCode::Up => {
+                self.scroll = self.scroll.saturating_sub(1);
                 KeyAction::None
             }
-            KeyCode::Char(' ') | KeyCode::Tab => {
-                self.scroll = self.scroll.saturating_add(page);
+            KeyCode::PageDown | KeyCode::Char(' ') | KeyCode::Tab => {
+                self.scroll_page(true, page, max_scroll, pending_target);
                 KeyAction::None
             }
-            KeyCode::BackTab => {
-                self.scroll = self.scroll.saturating_sub(page);
+            KeyCode::PageUp | KeyCode::BackTab => {
+                self.scroll_page(false, page, max_scroll, pending_target);
                 KeyAction::None
             }
             KeyCode::Home => {
@@ -434,57 +1330,98 @@ impl AppState {
             .margin(1)
             .split(frame.size());
 
-        let title_text = queue_label(context.queue_index, context.queue_len, context.path);
-        let title = Span::styled(
-            title_text,
-            Style::new().fg(theme.title).add_modifier(Modifier::BOLD),
-        );
         let block = Block::default()
             .borders(Borders::ALL)
-            .border_style(Style::new().fg(theme.border))
-            .title(title);
-        frame.render_widget(&block, chunks[0]);
-
+            .border_style(Style::new().fg(theme.border));
         let inner = block.inner(chunks[0]);
         let content_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Min(1), Constraint::Length(1)])
             .split(inner);
-        self.content_area = content_chunks[0];
+        let text_area = padded_area(content_chunks[0], self.padding);
+        self.content_area = text_area;
 
         if self.show_help {
             let help_lines = help_lines();
-            self.render_lines_with_scroll(frame, &help_lines, content_chunks[0], 0);
+            self.render_lines_with_scroll(frame, &help_lines, text_area, 0);
         } else {
+            let (source_area, doc_area) = if self.show_source {
+                let panes = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(text_area);
+                (Some(panes[0]), panes[1])
+            } else {
+                (None, text_area)
+            };
+            let doc_area = capped_area(doc_area, self.max_text_width);
             if let Some(prev) = self.scroll_before_help.take() {
                 self.scroll = prev;
             }
-            let mut lines = if self.plain_mode {
-                self.current_links.clear();
-                render_plain_lines(context.markdown)
-            } else {
-                let (lines, links) =
-                    render_markdown_with_links(context.markdown, content_chunks[0].width, theme);
-                self.current_links = links;
-                lines
+            self.large_document_fallback =
+                use_plain_fallback(context.markdown.len(), self.force_full_render);
+            let plain = self.plain_mode || self.large_document_fallback || context.plain_text;
+            let key = RenderKey {
+                doc_index: context.queue_index,
+                width: doc_area.width,
+                plain_mode: plain,
+                beeline_enabled: self.beeline_enabled,
+                bionic_enabled: self.bionic_enabled,
+                beeline_intensity: self.beeline_intensity,
+                no_wrap: self.no_wrap,
+                line_numbers: self.line_numbers,
+            };
+            let (mut lines, gutter) = match self.render_cache.lookup(&key) {
+                Some((lines, gutter)) => (lines.to_vec(), gutter),
+                None => {
+                    let started = Instant::now();
+                    let (lines, gutter) =
+                        self.render_document_with_gutter(context, doc_area.width, plain, theme);
+                    if self.flash.is_none()
+                        && let Some(message) = render_time_message(started.elapsed())
+                    {
+                        self.flash = Some((message, Instant::now()));
+                    }
+                    self.render_cache.store(key, lines.clone(), gutter);
+                    (lines, gutter)
+                }
+            };
+            let gutter_area = Rect {
+                width: gutter,
+                ..doc_area
             };
-            if self.beeline_enabled && !self.plain_mode {
-                lines = apply_beeline(&lines, theme);
+            let doc_area = Rect {
+                x: doc_area.x + gutter,
+                width: doc_area.width - gutter,
+                ..doc_area
+            };
+            self.content_area = doc_area;
+
+            if let Some(anchor) = self.resize_anchor.take() {
+                self.scroll = scroll_for_anchor(
+                    &anchor,
+                    &self.current_source_lines,
+                    &self.current_wraps,
+                    &self.current_line_offsets,
+                );
             }
 
-            let lines_text: Vec<String> = lines
-                .iter()
-                .map(|line| {
-                    line.spans
-                        .iter()
-                        .map(|span| span.content.as_ref())
-                        .collect()
-                })
-                .collect();
-            self.current_lines_text = lines_text;
-            let (wraps, offsets) = build_wraps(&self.current_lines_text, content_chunks[0].width);
-            self.current_wraps = wraps;
-            self.current_line_offsets = offsets;
+            if let Some(fragment) = self.pending_heading.take()
+                && let Some(heading) = self
+                    .current_headings
+                    .iter()
+                    .find(|heading| heading_slug(&heading.text) == fragment.to_lowercase())
+            {
+                self.scroll = heading.scroll_pos;
+            }
+            if let Some(line_number) = self.pending_start_line.take() {
+                self.scroll = start_line_scroll(
+                    &self.current_source_lines,
+                    &self.current_line_offsets,
+                    context.markdown.lines().count(),
+                    line_number,
+                );
+            }
 
             if self.search_query.is_empty() {
                 self.clear_search_state();
@@ -494,18 +1431,82 @@ impl AppState {
                     &self.search_query,
                     &self.current_wraps,
                     &self.current_line_offsets,
-                    content_chunks[0].width,
+                    doc_area.width,
                 );
                 if self.search_index >= self.search_matches.len() {
                     self.search_index = 0;
                 }
+                if self.scroll_to_search_match {
+                    self.scroll_to_search_match = false;
+                    if let Some(pos) = self
+                        .search_matches
+                        .get(self.search_index)
+                        .map(|m| m.scroll_pos)
+                    {
+                        self.scroll = self.match_scroll_target(pos, u16::MAX);
+                    }
+                }
+                self.search_results_selected = self
+                    .search_results_selected
+                    .min(self.search_matches.len().saturating_sub(1));
                 let active = self.search_matches.get(self.search_index);
                 lines = apply_search_highlight(&lines, &self.search_query, active, theme);
             }
+            if let Some(selection) = self.selection {
+                let ranges = selection_line_ranges(
+                    selection.anchor,
+                    selection.head,
+                    &self.current_lines_text,
+                );
+                lines = apply_selection_highlight(&lines, &ranges, theme);
+            }
 
-            self.render_lines(frame, &lines, content_chunks[0]);
+            self.render_lines(frame, &lines, doc_area);
+            if gutter > 0 {
+                let numbers = line_number_gutter(
+                    &self.current_line_offsets,
+                    self.scroll,
+                    gutter_area.height,
+                    gutter,
+                );
+                frame.render_widget(
+                    Paragraph::new(numbers).style(Style::new().fg(theme.footer).dim()),
+                    gutter_area,
+                );
+            }
+            if self.cursor_line_enabled
+                && let Some(rect) = cursor_line_rect(
+                    doc_area,
+                    &self.current_wraps,
+                    &self.current_line_offsets,
+                    self.cursor_line,
+                    self.scroll,
+                )
+            {
+                frame
+                    .buffer_mut()
+                    .set_style(rect, Style::new().bg(theme.cursor_line_bg));
+            }
+            if let Some(area) = source_area {
+                self.render_source_pane(frame, context.markdown, area, theme);
+            }
         }
 
+        // Drawn after the content so the breadcrumb reflects any scroll
+        // adjustment made while rendering (e.g. search jumps).
+        let mut title_text = queue_label(context.queue_index, context.queue_len, context.path);
+        if !self.show_help {
+            for heading in breadcrumb_for_scroll(&self.current_headings, self.scroll) {
+                title_text.push_str(" › ");
+                title_text.push_str(&heading);
+            }
+        }
+        let title = Span::styled(
+            title_text,
+            Style::new().fg(theme.title).add_modifier(Modifier::BOLD),
+        );
+        frame.render_widget(block.title(title), chunks[0]);
+
         if self.rendered_lines > self.viewport_height {
             let scroll_len = self
                 .rendered_lines
@@ -520,30 +1521,56 @@ impl AppState {
                 .track_style(Style::new().fg(theme.scrollbar_track));
             frame.render_stateful_widget(scrollbar, content_chunks[1], &mut scrollbar_state);
         }
+        if self.show_minimap && !self.show_help {
+            self.render_minimap(frame, content_chunks[1], theme);
+        }
 
         let footer_chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Min(1), Constraint::Length(24)])
+            .constraints([Constraint::Min(1), Constraint::Length(32)])
             .split(chunks[1]);
 
+        self.expire_flash(Instant::now());
         let help = if self.search_mode {
             Line::raw(format!("/{}", self.search_query))
+        } else if let Some(input) = &self.line_prompt {
+            Line::raw(format!(":{}", input))
+        } else if let Some(message) = &self.status_message {
+            Line::raw(message.clone())
+        } else if let Some((message, _)) = &self.flash {
+            Line::raw(message.clone())
         } else if let Some(url) = &self.hover_link {
             Line::raw(format!("link: {}", url))
+        } else if self.show_stats && !self.show_help {
+            Line::raw(format!(
+                "words: {} • chars: {} • lines: {}",
+                self.current_stats.words, self.current_stats.chars, self.current_stats.lines
+            ))
+        } else if self.large_document_fallback && !self.plain_mode {
+            Line::raw("Large file shown as plain text • rerun with --full-render to style it")
         } else {
             Line::raw("Press h for commands • / search • q quit")
         }
         .style(Style::new().fg(theme.footer).dim());
         frame.render_widget(Paragraph::new(help), footer_chunks[0]);
 
+        let reading_time = (!self.show_help && self.current_stats.words > 0).then(|| {
+            Span::styled(
+                format!(
+                    "~{} min",
+                    reading_time_minutes(self.current_stats.words, self.words_per_minute)
+                ),
+                Style::new().fg(theme.footer).dim(),
+            )
+        });
         let total_lines = self.rendered_lines.max(1);
         if self.rendered_lines > self.viewport_height {
             let max_scroll = total_lines.saturating_sub(self.viewport_height);
-            let percent = if max_scroll == 0 {
-                100
-            } else {
-                (self.scroll.saturating_mul(100) / max_scroll).min(100)
-            };
+            let percent = self
+                .scroll
+                .saturating_mul(100)
+                .checked_div(max_scroll)
+                .map_or(100, |percent| percent.min(100));
             let mut status_spans = vec![Span::styled(
                 format!("{}/{}", self.scroll.saturating_add(1), total_lines),
                 Style::new().fg(theme.footer).dim(),
@@ -567,8 +1594,17 @@ impl AppState {
                 format!("{}%", percent),
                 Style::new().fg(theme.footer).dim(),
             ));
+            if let Some(reading_time) = reading_time {
+                status_spans.push(Span::raw(" "));
+                status_spans.push(reading_time);
+            }
             let status = Line::from(status_spans);
             frame.render_widget(Paragraph::new(status).right_aligned(), footer_chunks[1]);
+        } else if let Some(reading_time) = reading_time {
+            frame.render_widget(
+                Paragraph::new(Line::from(reading_time)).right_aligned(),
+                footer_chunks[1],
+            );
         }
 
         if self.picker_open {
@@ -577,10 +1613,157 @@ impl AppState {
         if self.go_dialog_open {
             self.render_go_dialog_overlay(frame, chunks[0], context.queue_paths, theme);
         }
+        if self.global_search_open {
+            self.render_global_search_overlay(frame, chunks[0], context.queue_paths, theme);
+        }
+        if self.toc_open {
+            self.render_toc_overlay(frame, chunks[0], theme);
+        }
+        if self.about_open {
+            let size = frame.size();
+            let info = Diagnostics {
+                terminal: (size.width, size.height),
+                color_depth: self.color_depth,
+                theme_name: theme.name,
+                code_theme: theme.code_theme,
+                path: context.path,
+                size_bytes: context.markdown.len(),
+                queue_len: context.queue_len,
+            };
+            self.render_about_overlay(frame, chunks[0], &info, theme);
+        }
+        if self.search_results_open {
+            self.render_search_results_overlay(frame, chunks[0], theme);
+        }
+        if self.color_depth != ColorDepth::TrueColor {
+            downgrade_buffer(frame.buffer_mut(), self.color_depth);
+        }
+    }
+
+    /// Renders the document narrowed by a line-number gutter wide enough for
+    /// its line count, returning the lines and the gutter width (0 when line
+    /// numbers are off). Narrowing can add lines, so the width is widened
+    /// until the count fits.
+    fn render_document_with_gutter(
+        &mut self,
+        context: &RenderContext,
+        width: u16,
+        plain: bool,
+        theme: &Theme,
+    ) -> (Vec<Line<'static>>, u16) {
+        if !self.line_numbers {
+            return (self.render_document(context, width, plain, theme), 0);
+        }
+        let mut gutter = line_number_gutter_width(context.markdown.lines().count());
+        loop {
+            if gutter >= width {
+                return (self.render_document(context, width, plain, theme), 0);
+            }
+            let lines = self.render_document(context, width - gutter, plain, theme);
+            let needed = line_number_gutter_width(lines.len());
+            if needed <= gutter {
+                return (lines, gutter);
+            }
+            gutter = needed;
+        }
+    }
+
+    fn render_document(
+        &mut self,
+        context: &RenderContext,
+        width: u16,
+        plain: bool,
+        theme: &Theme,
+    ) -> Vec<Line<'static>> {
+        let markdown = context.markdown;
+        let mut headings = Vec::new();
+        let empty = markdown.trim().is_empty();
+        let lines = if empty {
+            self.current_links.clear();
+            self.current_source_lines = vec![0];
+            vec![empty_document_line(theme)]
+        } else if plain {
+            self.current_links.clear();
+            let lines = render_plain_lines(markdown, &self.render_options);
+            self.current_source_lines = (0..lines.len()).collect();
+            lines
+        } else {
+            let table_width = if self.no_wrap { 0 } else { width };
+            let mut rendered =
+                render_markdown_with_links(markdown, table_width, theme, &self.render_options);
+            if let Some(base_dir) = Path::new(context.path).parent() {
+                mark_broken_links(&mut rendered, base_dir, theme);
+            }
+            self.current_links = rendered.links;
+            self.current_source_lines = rendered.source_lines;
+            headings = rendered.headings;
+            if self.beeline_enabled {
+                apply_beeline(
+                    &rendered.lines,
+                    &rendered.kinds,
+                    theme,
+                    f32::from(self.beeline_intensity) / 100.0,
+                )
+            } else if self.bionic_enabled {
+                apply_bionic(&rendered.lines, &rendered.kinds)
+            } else {
+                rendered.lines
+            }
+        };
+
+        let lines_text: Vec<String> = lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+        self.current_lines_text = lines_text;
+        self.current_stats = if empty {
+            DocStats::default()
+        } else {
+            document_stats(&self.current_lines_text)
+        };
+        let wrap_width = if self.no_wrap { u16::MAX } else { width };
+        let (wraps, offsets) = build_wraps(&self.current_lines_text, wrap_width);
+        self.current_headings = headings
+            .into_iter()
+            .filter_map(|heading| {
+                Some(HeadingAnchor {
+                    level: heading.level,
+                    text: heading.text,
+                    scroll_pos: *offsets.get(heading.line_idx)?,
+                })
+            })
+            .collect();
+        self.current_wraps = wraps;
+        self.current_line_offsets = offsets;
+
+        lines
     }
 
     fn render_lines(&mut self, frame: &mut ratatui::Frame, lines: &[Line<'static>], area: Rect) {
-        self.render_lines_with_scroll(frame, lines, area, self.scroll);
+        if self.no_wrap {
+            self.viewport_height = area.height;
+            self.viewport_width = area.width;
+            self.rendered_lines = lines.len().min(u16::MAX as usize) as u16;
+            self.max_line_width = lines
+                .iter()
+                .map(Line::width)
+                .max()
+                .unwrap_or(0)
+                .min(u16::MAX as usize) as u16;
+            self.h_scroll = clamp_h_scroll(self.h_scroll, self.max_line_width, area.width);
+            let max_scroll = self.rendered_lines.saturating_sub(self.viewport_height);
+            let paragraph = Paragraph::new(Text::from(lines.to_vec()))
+                .scroll((self.scroll.min(max_scroll), self.h_scroll));
+            frame.render_widget(paragraph, area);
+        } else {
+            self.h_scroll = 0;
+            self.render_lines_with_scroll(frame, lines, area, self.scroll);
+        }
         let max_scroll = self.rendered_lines.saturating_sub(self.viewport_height);
         if self.scroll > max_scroll {
             self.scroll = max_scroll;
@@ -604,7 +1787,60 @@ impl AppState {
         frame.render_widget(paragraph, area);
     }
 
-    fn render_picker_overlay(&self, frame: &mut ratatui::Frame, area: Rect, theme: &Theme) {
+    /// Raw markdown beside the rendered view, scrolled so the source line
+    /// behind the top rendered row is at the top of the pane.
+    fn render_source_pane(
+        &self,
+        frame: &mut ratatui::Frame,
+        markdown: &str,
+        area: Rect,
+        theme: &Theme,
+    ) {
+        let block = Block::default()
+            .borders(Borders::RIGHT)
+            .border_style(Style::new().fg(theme.border));
+        let inner = block.inner(area);
+        let lines = render_plain_lines(markdown, &self.render_options);
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        let (_, offsets) = build_wraps(&text, inner.width);
+        let source_line = source_line_at_scroll(
+            &self.current_source_lines,
+            &self.current_line_offsets,
+            self.scroll,
+        );
+        let scroll = offsets
+            .get(source_line)
+            .or(offsets.last())
+            .copied()
+            .unwrap_or(0);
+        let paragraph = Paragraph::new(lines)
+            .style(Style::new().fg(theme.footer))
+            .wrap(Wrap { trim: false })
+            .scroll((scroll, 0))
+            .block(block);
+        frame.render_widget(paragraph, area);
+    }
+
+    /// Marks headings and search matches in `gutter` at rows proportional to
+    /// their position in the document; matches are drawn over headings.
+    fn render_minimap(&self, frame: &mut ratatui::Frame, gutter: Rect, theme: &Theme) {
+        let headings: Vec<u16> = self.current_headings.iter().map(|h| h.scroll_pos).collect();
+        let matches: Vec<u16> = self.search_matches.iter().map(|m| m.scroll_pos).collect();
+        let buffer = frame.buffer_mut();
+        for (positions, symbol, color) in [
+            (headings, "─", theme.heading),
+            (matches, "•", theme.search_bg_active),
+        ] {
+            for row in minimap_ticks(&positions, self.rendered_lines, gutter.height) {
+                buffer
+                    .get_mut(gutter.x, gutter.y + row)
+                    .set_symbol(symbol)
+                    .set_fg(color);
+            }
+        }
+    }
+
+    fn render_picker_overlay(&mut self, frame: &mut ratatui::Frame, area: Rect, theme: &Theme) {
         let popup = centered_rect(80, 70, area);
         frame.render_widget(Clear, popup);
         let block = Block::default()
@@ -638,6 +1874,11 @@ impl AppState {
         );
         frame.render_widget(Paragraph::new(query), chunks[1]);
 
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(chunks[2]);
+
         let mut lines = Vec::new();
         if self.picker_entries.is_empty() {
             lines.push(Line::styled(
@@ -645,7 +1886,7 @@ impl AppState {
                 Style::new().fg(theme.footer).dim(),
             ));
         } else {
-            let visible = chunks[2].height.max(1) as usize;
+            let visible = body[0].height.max(1) as usize;
             let start = self
                 .picker_selected
                 .saturating_sub(visible.saturating_sub(1));
@@ -659,13 +1900,35 @@ impl AppState {
                         .bg(theme.search_bg_active)
                         .add_modifier(Modifier::BOLD);
                 }
-                lines.push(Line::styled(entry.label.clone(), style));
+                let label = if self.picker_marked.contains(&entry.path) {
+                    format!("* {}", entry.label)
+                } else {
+                    entry.label.clone()
+                };
+                lines.push(Line::styled(label, style));
             }
         }
-        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[2]);
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), body[0]);
+
+        if let Some(entry) = self.picker_entries.get(self.picker_selected) {
+            let render_options = &self.render_options;
+            let preview = self
+                .picker_previews
+                .entry(entry.path.clone())
+                .or_insert_with(|| picker_preview_lines(entry, theme, render_options));
+            let block = Block::default()
+                .borders(Borders::LEFT)
+                .border_style(Style::new().fg(theme.border));
+            frame.render_widget(
+                Paragraph::new(preview.clone())
+                    .block(block)
+                    .wrap(Wrap { trim: false }),
+                body[1],
+            );
+        }
 
         let help = Line::styled(
-            "Enter open/enter dir  Backspace up  Esc close",
+            "Enter open/enter dir  Space mark  Backspace up  Esc close",
             Style::new().fg(theme.footer).dim(),
         );
         frame.render_widget(Paragraph::new(help), chunks[3]);
@@ -706,31 +1969,280 @@ impl AppState {
                 .go_dialog_selected
                 .saturating_sub(visible.saturating_sub(1));
             let end = (start + visible).min(queue_paths.len());
-            for (idx, path) in queue_paths[start..end].iter().enumerate() {
-                let absolute_idx = start + idx;
+            for row in start..end {
+                let doc_idx = self.go_dialog_order.get(row).copied().unwrap_or(row);
+                let Some(path) = queue_paths.get(doc_idx) else {
+                    continue;
+                };
                 let mut style = Style::new().fg(theme.footer);
-                if absolute_idx == self.go_dialog_selected {
+                if row == self.go_dialog_selected {
                     style = style
                         .fg(theme.search_fg_active)
                         .bg(theme.search_bg_active)
                         .add_modifier(Modifier::BOLD);
                 }
+                let pin = if row < self.go_dialog_pinned {
+                    "📌 "
+                } else {
+                    ""
+                };
                 lines.push(Line::styled(
-                    format!("[{}/{}] {}", absolute_idx + 1, queue_paths.len(), path),
+                    format!("{}[{}/{}] {}", pin, doc_idx + 1, queue_paths.len(), path),
                     style,
                 ));
             }
         }
         frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[0]);
+        frame.render_widget(
+            Paragraph::new(Line::styled(
+                "Enter go  Esc close  Up/Down select  Shift+Up/Down move",
+                Style::new().fg(theme.footer).dim(),
+            )),
+            chunks[1],
+        );
+    }
+
+    fn render_global_search_overlay(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: Rect,
+        queue_paths: &[String],
+        theme: &Theme,
+    ) {
+        let popup = centered_rect(80, 70, area);
+        frame.render_widget(Clear, popup);
+        let block = Block::default()
+            .title(Span::styled(
+                "Search All Documents",
+                Style::new().fg(theme.title).add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::new().fg(theme.border));
+        frame.render_widget(block.clone(), popup);
+        let inner = block.inner(popup);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let query = Line::styled(
+            format!("query: {}", self.global_search_query),
+            Style::new().fg(theme.footer),
+        );
+        frame.render_widget(Paragraph::new(query), chunks[0]);
+
+        let mut lines = Vec::new();
+        if self.global_search_results.is_empty() {
+            let message = if self.global_search_query.is_empty() {
+                "Type to search every queued document"
+            } else {
+                "No matches"
+            };
+            lines.push(Line::styled(message, Style::new().fg(theme.footer).dim()));
+        } else {
+            let visible = chunks[1].height.max(1) as usize;
+            let start = self
+                .global_search_selected
+                .saturating_sub(visible.saturating_sub(1));
+            let end = (start + visible).min(self.global_search_results.len());
+            for idx in start..end {
+                let result = &self.global_search_results[idx];
+                let mut style = Style::new().fg(theme.footer);
+                if idx == self.global_search_selected {
+                    style = style
+                        .fg(theme.search_fg_active)
+                        .bg(theme.search_bg_active)
+                        .add_modifier(Modifier::BOLD);
+                }
+                let path = queue_paths
+                    .get(result.doc_index)
+                    .map(String::as_str)
+                    .unwrap_or("");
+                lines.push(Line::styled(
+                    format!("{}:{}  {}", path, result.line_idx + 1, result.preview),
+                    style,
+                ));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines), chunks[1]);
         frame.render_widget(
             Paragraph::new(Line::styled(
                 "Enter go  Esc close  Up/Down select",
                 Style::new().fg(theme.footer).dim(),
             )),
+            chunks[2],
+        );
+    }
+
+    fn render_search_results_overlay(&self, frame: &mut ratatui::Frame, area: Rect, theme: &Theme) {
+        let popup = centered_rect(80, 60, area);
+        frame.render_widget(Clear, popup);
+        let block = Block::default()
+            .title(Span::styled(
+                format!("Matches ({})", self.search_matches.len()),
+                Style::new().fg(theme.title).add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::new().fg(theme.border));
+        frame.render_widget(block.clone(), popup);
+        let inner = block.inner(popup);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let mut lines = Vec::new();
+        if self.search_matches.is_empty() {
+            lines.push(Line::styled(
+                "No matches",
+                Style::new().fg(theme.footer).dim(),
+            ));
+        }
+        let visible = chunks[0].height.max(1) as usize;
+        let start = self
+            .search_results_selected
+            .saturating_sub(visible.saturating_sub(1));
+        let end = (start + visible).min(self.search_matches.len());
+        let snippet_width = usize::from(chunks[0].width.saturating_sub(7));
+        for idx in start..end {
+            let m = &self.search_matches[idx];
+            let text = self
+                .current_lines_text
+                .get(m.line_idx)
+                .map(String::as_str)
+                .unwrap_or("");
+            let snippet = snippet_around(text, m.start..m.end, snippet_width);
+            let mut base = Style::new().fg(theme.footer);
+            if idx == self.search_results_selected {
+                base = base.bg(theme.search_bg_active);
+            }
+            let emphasis = base.fg(theme.search_fg_active).add_modifier(Modifier::BOLD);
+            let mut spans = vec![Span::styled(format!("{:>5}  ", m.line_idx + 1), base.dim())];
+            let mut last = 0;
+            for (from, to) in match_ranges(&snippet, &self.search_query) {
+                spans.push(Span::styled(snippet[last..from].to_string(), base));
+                spans.push(Span::styled(snippet[from..to].to_string(), emphasis));
+                last = to;
+            }
+            spans.push(Span::styled(snippet[last..].to_string(), base));
+            lines.push(Line::from(spans));
+        }
+        frame.render_widget(Paragraph::new(lines), chunks[0]);
+        frame.render_widget(
+            Paragraph::new(Line::styled(
+                "Enter jump  Esc close  Up/Down select",
+                Style::new().fg(theme.footer).dim(),
+            )),
+            chunks[1],
+        );
+    }
+
+    fn open_about(&mut self) {
+        self.close_picker();
+        self.close_go_dialog();
+        self.close_global_search();
+        self.toc_open = false;
+        self.about_open = true;
+    }
+
+    fn render_about_overlay(
+        &self,
+        frame: &mut ratatui::Frame,
+        area: Rect,
+        info: &Diagnostics<'_>,
+        theme: &Theme,
+    ) {
+        let popup = centered_rect(70, 50, area);
+        frame.render_widget(Clear, popup);
+        let block = Block::default()
+            .title(Span::styled(
+                "About",
+                Style::new().fg(theme.title).add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::new().fg(theme.border));
+        frame.render_widget(block.clone(), popup);
+        let inner = block.inner(popup);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+        let lines: Vec<Line> = diagnostics_lines(info)
+            .into_iter()
+            .map(|line| Line::styled(line, Style::new().fg(theme.footer)))
+            .collect();
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[0]);
+        frame.render_widget(
+            Paragraph::new(Line::styled(
+                "Any key closes",
+                Style::new().fg(theme.footer).dim(),
+            )),
+            chunks[1],
+        );
+    }
+
+    fn render_toc_overlay(&self, frame: &mut ratatui::Frame, area: Rect, theme: &Theme) {
+        let popup = centered_rect(60, 70, area);
+        frame.render_widget(Clear, popup);
+        let block = Block::default()
+            .title(Span::styled(
+                "Contents",
+                Style::new().fg(theme.title).add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::new().fg(theme.border));
+        frame.render_widget(block.clone(), popup);
+        let inner = block.inner(popup);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let visible = chunks[0].height.max(1) as usize;
+        let start = self.toc_selected.saturating_sub(visible.saturating_sub(1));
+        let end = (start + visible).min(self.current_headings.len());
+        let mut lines = Vec::new();
+        for (idx, heading) in self.current_headings[start..end].iter().enumerate() {
+            let mut style = Style::new().fg(theme.footer);
+            if start + idx == self.toc_selected {
+                style = style
+                    .fg(theme.search_fg_active)
+                    .bg(theme.search_bg_active)
+                    .add_modifier(Modifier::BOLD);
+            }
+            let indent = "  ".repeat(usize::from(heading.level.saturating_sub(1)));
+            lines.push(Line::styled(format!("{}{}", indent, heading.text), style));
+        }
+        frame.render_widget(Paragraph::new(lines), chunks[0]);
+        frame.render_widget(
+            Paragraph::new(Line::styled(
+                "Enter jump  Esc close  Up/Down select",
+                Style::new().fg(theme.footer).dim(),
+            )),
             chunks[1],
         );
     }
 
+    /// Re-enables mouse capture, which some terminals drop on resize or focus
+    /// changes. Does nothing with `--no-mouse`.
+    fn recapture_mouse(
+        &self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        if !self.mouse_enabled {
+            return Ok(());
+        }
+        execute!(
+            terminal.backend_mut(),
+            DisableMouseCapture,
+            EnableMouseCapture
+        )
+    }
+
     fn handle_event(
         &mut self,
         event: Event,
@@ -738,16 +2250,31 @@ impl AppState {
     ) -> io::Result<EventResult> {
         match event {
             Event::Key(key) => {
+                self.status_message = None;
                 if self.picker_open {
                     return Ok(self.handle_picker_key_input(key.code));
                 }
                 if self.go_dialog_open {
-                    return Ok(self.handle_go_dialog_key_input(key.code));
+                    return Ok(self.handle_go_dialog_key_input(key.code, key.modifiers));
+                }
+                if self.global_search_open {
+                    return Ok(self.handle_global_search_key_input(key.code));
                 }
 
                 let page = self.viewport_height.saturating_sub(1).max(1);
                 let max_scroll = self.rendered_lines.saturating_sub(self.viewport_height);
-                match self.handle_key_input(key.code, max_scroll, page) {
+                if self.toc_open {
+                    return Ok(self.handle_toc_key_input(key.code, max_scroll));
+                }
+                if self.about_open {
+                    // Any key dismisses the screen.
+                    self.about_open = false;
+                    return Ok(EventResult::Continue);
+                }
+                if self.line_prompt.is_some() {
+                    return Ok(self.handle_line_prompt_key_input(key.code, max_scroll));
+                }
+                match self.handle_key_event(key, max_scroll, page) {
                     KeyAction::Quit => return Ok(EventResult::Quit),
                     KeyAction::OpenLink => {
                         if let Some(url) = link_at_scroll(
@@ -756,13 +2283,18 @@ impl AppState {
                             &self.current_line_offsets,
                             self.scroll,
                         ) {
-                            let _ = open_url(&url);
+                            let _ = open_url(&url, self.browser.as_deref(), &self.link_schemes);
                         }
                     }
                     KeyAction::NextDocument => return Ok(EventResult::NextDocument),
+                    KeyAction::AlternateDocument => return Ok(EventResult::AlternateDocument),
                     KeyAction::PreviousDocument => return Ok(EventResult::PreviousDocument),
                     KeyAction::OpenPicker => return Ok(EventResult::OpenPicker),
+                    KeyAction::OpenPickerHere => return Ok(EventResult::OpenPickerHere),
                     KeyAction::OpenGoDialog => return Ok(EventResult::OpenGoDialog),
+                    KeyAction::TogglePin => return Ok(EventResult::TogglePin),
+                    KeyAction::OpenGlobalSearch => return Ok(EventResult::OpenGlobalSearch),
+                    KeyAction::CopyDocumentPath => return Ok(EventResult::CopyDocumentPath),
                     KeyAction::None => {}
                 }
             }
@@ -771,57 +2303,83 @@ impl AppState {
                 let max_scroll = self.rendered_lines.saturating_sub(self.viewport_height);
                 match mouse.kind {
                     MouseEventKind::ScrollDown => {
+                        self.scroll_target = None;
                         self.scroll = self.scroll.saturating_add(3).min(max_scroll);
                         if !self.show_help {
                             self.hover_link = update_hover(self, mouse.column, mouse.row);
                         }
                     }
                     MouseEventKind::ScrollUp => {
+                        self.scroll_target = None;
                         self.scroll = self.scroll.saturating_sub(3);
                         if !self.show_help {
                             self.hover_link = update_hover(self, mouse.column, mouse.row);
                         }
                     }
-                    MouseEventKind::Moved | MouseEventKind::Drag(_) => {
-                        if !self.show_help {
-                            self.hover_link = update_hover(self, mouse.column, mouse.row);
+                    MouseEventKind::Drag(MouseButton::Left) if self.selecting => {
+                        let head = text_position_at_mouse(self, mouse.column, mouse.row);
+                        if let (Some(selection), Some(head)) = (self.selection.as_mut(), head) {
+                            selection.head = head;
                         }
                     }
-                    MouseEventKind::Down(MouseButton::Left) => {
+                    MouseEventKind::Moved | MouseEventKind::Drag(_) if !self.show_help => {
+                        if self.cursor_line_enabled
+                            && let Some(pos) = text_position_at_mouse(self, mouse.column, mouse.row)
+                        {
+                            self.cursor_line = pos.line_idx;
+                        }
+                        self.hover_link = update_hover(self, mouse.column, mouse.row);
+                    }
+                    MouseEventKind::Down(MouseButton::Left)
                         if !self.show_help
                             && !self.search_mode
                             && mouse.column >= self.content_area.x
                             && mouse.column < self.content_area.x + self.content_area.width
                             && mouse.row >= self.content_area.y
-                            && mouse.row < self.content_area.y + self.content_area.height
-                        {
-                            let local_y = mouse.row.saturating_sub(self.content_area.y);
-                            let rendered_line = self.scroll.saturating_add(local_y);
-                            let local_x = mouse.column.saturating_sub(self.content_area.x);
-                            self.hover_link = link_at_position(
-                                &self.current_links,
-                                &self.current_wraps,
-                                &self.current_line_offsets,
-                                &self.current_lines_text,
-                                rendered_line,
-                                local_x,
-                            );
-                            if let Some(url) = self.hover_link.clone() {
-                                let _ = open_url(&url);
+                            && mouse.row < self.content_area.y + self.content_area.height =>
+                    {
+                        self.selection =
+                            text_position_at_mouse(self, mouse.column, mouse.row).map(|pos| {
+                                Selection {
+                                    anchor: pos,
+                                    head: pos,
+                                }
+                            });
+                        self.selecting = self.selection.is_some();
+                    }
+                    MouseEventKind::Up(MouseButton::Left) if self.selecting => {
+                        self.selecting = false;
+                        match self.selection {
+                            Some(selection) if selection.anchor != selection.head => {
+                                let ranges = selection_line_ranges(
+                                    selection.anchor,
+                                    selection.head,
+                                    &self.current_lines_text,
+                                );
+                                let text = selected_text(&self.current_lines_text, &ranges);
+                                write!(terminal.backend_mut(), "{}", osc52_copy_sequence(&text))?;
+                                terminal.backend_mut().flush()?;
+                                self.status_message =
+                                    Some(format!("Copied {} characters", text.chars().count()));
+                            }
+                            _ => {
+                                self.selection = None;
+                                self.hover_link = update_hover(self, mouse.column, mouse.row);
+                                if let Some(url) = self.hover_link.clone() {
+                                    let _ =
+                                        open_url(&url, self.browser.as_deref(), &self.link_schemes);
+                                }
                             }
                         }
                     }
-                    MouseEventKind::Up(_) => {
-                        if !self.show_help {
-                            self.hover_link = update_hover(self, mouse.column, mouse.row);
-                        }
+                    MouseEventKind::Up(_) if !self.show_help => {
+                        self.hover_link = update_hover(self, mouse.column, mouse.row);
                     }
                     _ => {}
                 }
             }
             Event::FocusGained => {
-                let _ = execute!(terminal.backend_mut(), DisableMouseCapture);
-                let _ = execute!(terminal.backend_mut(), EnableMouseCapture);
+                let _ = self.recapture_mouse(terminal);
                 if let Some((col, row)) = self.last_mouse_pos {
                     self.hover_link = update_hover(self, col, row);
                 }
@@ -830,8 +2388,16 @@ impl AppState {
                 self.hover_link = None;
             }
             Event::Resize(_, _) => {
-                let _ = execute!(terminal.backend_mut(), DisableMouseCapture);
-                let _ = execute!(terminal.backend_mut(), EnableMouseCapture);
+                // Keep an earlier anchor if resize events arrive before a redraw.
+                if self.resize_anchor.is_none() {
+                    self.resize_anchor = anchor_at_scroll(
+                        &self.current_source_lines,
+                        &self.current_wraps,
+                        &self.current_line_offsets,
+                        self.scroll,
+                    );
+                }
+                let _ = self.recapture_mouse(terminal);
                 if let Some((col, row)) = self.last_mouse_pos {
                     self.hover_link = update_hover(self, col, row);
                 }
@@ -843,19 +2409,24 @@ impl AppState {
 
     fn handle_picker_key_input(&mut self, code: KeyCode) -> EventResult {
         match code {
+            // The first Esc clears a typed filter; the next closes the picker.
+            KeyCode::Esc if !self.picker_query.is_empty() => {
+                self.picker_query.clear();
+                self.refresh_picker_entries();
+                EventResult::Continue
+            }
             KeyCode::Esc => {
                 self.close_picker();
                 EventResult::Continue
             }
             KeyCode::Up | KeyCode::BackTab => {
-                self.picker_selected = self.picker_selected.saturating_sub(1);
+                self.picker_selected =
+                    wrapping_prev(self.picker_selected, self.picker_entries.len());
                 EventResult::Continue
             }
             KeyCode::Down | KeyCode::Tab => {
-                if !self.picker_entries.is_empty() {
-                    self.picker_selected =
-                        (self.picker_selected + 1).min(self.picker_entries.len() - 1);
-                }
+                self.picker_selected =
+                    wrapping_next(self.picker_selected, self.picker_entries.len());
                 EventResult::Continue
             }
             KeyCode::Home => {
@@ -878,11 +2449,26 @@ impl AppState {
                 self.refresh_picker_entries();
                 EventResult::Continue
             }
+            KeyCode::Char(' ') => {
+                if let Some(entry) = self.picker_entries.get(self.picker_selected)
+                    && entry.kind == PickerEntryKind::MarkdownFile
+                    && !self.picker_marked.remove(&entry.path)
+                {
+                    self.picker_marked.insert(entry.path.clone());
+                }
+                EventResult::Continue
+            }
             KeyCode::Char(c) => {
                 self.picker_query.push(c);
                 self.refresh_picker_entries();
                 EventResult::Continue
             }
+            KeyCode::Enter if !self.picker_marked.is_empty() => {
+                let mut paths: Vec<PathBuf> = self.picker_marked.drain().collect();
+                paths.sort();
+                self.close_picker();
+                EventResult::OpenPaths(paths)
+            }
             KeyCode::Enter => {
                 if let Some(entry) = self.picker_entries.get(self.picker_selected).cloned() {
                     match entry.kind {
@@ -906,21 +2492,38 @@ impl AppState {
         }
     }
 
-    fn handle_go_dialog_key_input(&mut self, code: KeyCode) -> EventResult {
+    fn handle_go_dialog_key_input(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> EventResult {
         match code {
             KeyCode::Esc => {
                 self.close_go_dialog();
                 EventResult::Continue
             }
+            KeyCode::Up if modifiers.contains(KeyModifiers::SHIFT) => {
+                let row = self.go_dialog_selected;
+                if row == 0 {
+                    return EventResult::Continue;
+                }
+                self.move_go_dialog_row(row, row - 1)
+            }
+            KeyCode::Down if modifiers.contains(KeyModifiers::SHIFT) => {
+                let row = self.go_dialog_selected;
+                if row + 1 >= self.go_dialog_total {
+                    return EventResult::Continue;
+                }
+                self.move_go_dialog_row(row, row + 1)
+            }
             KeyCode::Up | KeyCode::BackTab => {
-                self.go_dialog_selected = self.go_dialog_selected.saturating_sub(1);
+                self.go_dialog_selected =
+                    wrapping_prev(self.go_dialog_selected, self.go_dialog_total);
                 EventResult::Continue
             }
             KeyCode::Down | KeyCode::Tab => {
-                if self.go_dialog_total > 0 {
-                    self.go_dialog_selected =
-                        (self.go_dialog_selected + 1).min(self.go_dialog_total - 1);
-                }
+                self.go_dialog_selected =
+                    wrapping_next(self.go_dialog_selected, self.go_dialog_total);
                 EventResult::Continue
             }
             KeyCode::Home => {
@@ -932,7 +2535,7 @@ impl AppState {
                 EventResult::Continue
             }
             KeyCode::Enter => {
-                let selected = self.go_dialog_selected;
+                let selected = self.go_dialog_row_index(self.go_dialog_selected);
                 self.close_go_dialog();
                 EventResult::GoToIndex(selected)
             }
@@ -940,10 +2543,73 @@ impl AppState {
         }
     }
 
+    fn go_dialog_row_index(&self, row: usize) -> usize {
+        self.go_dialog_order.get(row).copied().unwrap_or(row)
+    }
+
+    /// Moves the document on go-dialog row `from` to the neighboring row
+    /// `to`. Documents stay within their pinned or unpinned group.
+    fn move_go_dialog_row(&mut self, from: usize, to: usize) -> EventResult {
+        if (from < self.go_dialog_pinned) != (to < self.go_dialog_pinned) {
+            return EventResult::Continue;
+        }
+        self.go_dialog_selected = to;
+        EventResult::MoveDocument {
+            from: self.go_dialog_row_index(from),
+            to: self.go_dialog_row_index(to),
+        }
+    }
+
+    fn handle_global_search_key_input(&mut self, code: KeyCode) -> EventResult {
+        match code {
+            KeyCode::Esc => {
+                self.close_global_search();
+                EventResult::Continue
+            }
+            KeyCode::Up | KeyCode::BackTab => {
+                self.global_search_selected = self.global_search_selected.saturating_sub(1);
+                EventResult::Continue
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                if !self.global_search_results.is_empty() {
+                    self.global_search_selected =
+                        (self.global_search_selected + 1).min(self.global_search_results.len() - 1);
+                }
+                EventResult::Continue
+            }
+            KeyCode::Backspace => {
+                self.global_search_query.pop();
+                self.refresh_global_search_results();
+                EventResult::Continue
+            }
+            KeyCode::Char(c) => {
+                self.global_search_query.push(c);
+                self.refresh_global_search_results();
+                EventResult::Continue
+            }
+            KeyCode::Enter => {
+                if let Some(result) = self
+                    .global_search_results
+                    .get(self.global_search_selected)
+                    .cloned()
+                {
+                    self.pending_search_jump =
+                        Some((self.global_search_query.clone(), result.ordinal));
+                    self.close_global_search();
+                    EventResult::GoToIndex(result.doc_index)
+                } else {
+                    EventResult::Continue
+                }
+            }
+            _ => EventResult::Continue,
+        }
+    }
+
     fn clear_search_state(&mut self) {
         self.search_query.clear();
         self.search_matches.clear();
         self.search_index = 0;
+        self.search_results_open = false;
     }
 
     fn reset_search_matches(&mut self) {
@@ -961,36 +2627,98 @@ struct SearchMatch {
     scroll_pos: u16,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct DocStats {
+    words: usize,
+    chars: usize,
+    lines: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RenderKey {
+    doc_index: usize,
+    width: u16,
+    plain_mode: bool,
+    beeline_enabled: bool,
+    bionic_enabled: bool,
+    beeline_intensity: u8,
+    no_wrap: bool,
+    line_numbers: bool,
+}
+
+/// Styled document lines from the last full render, reused while the inputs
+/// that shape them stay the same.
+#[derive(Default)]
+struct RenderCache {
+    key: Option<RenderKey>,
+    lines: Vec<Line<'static>>,
+    gutter: u16,
+}
+
+impl RenderCache {
+    fn lookup(&self, key: &RenderKey) -> Option<(&[Line<'static>], u16)> {
+        (self.key.as_ref() == Some(key)).then_some((self.lines.as_slice(), self.gutter))
+    }
+
+    fn store(&mut self, key: RenderKey, lines: Vec<Line<'static>>, gutter: u16) {
+        self.key = Some(key);
+        self.lines = lines;
+        self.gutter = gutter;
+    }
+
+    fn invalidate(&mut self) {
+        self.key = None;
+        self.lines.clear();
+    }
+}
+
+#[derive(Clone, Debug)]
+struct GlobalSearchResult {
+    doc_index: usize,
+    line_idx: usize,
+    preview: String,
+    ordinal: usize,
+}
+
 enum KeyAction {
     None,
     Quit,
     OpenLink,
     NextDocument,
     PreviousDocument,
+    AlternateDocument,
     OpenPicker,
+    OpenPickerHere,
     OpenGoDialog,
+    TogglePin,
+    OpenGlobalSearch,
+    CopyDocumentPath,
 }
 
 enum EventResult {
     Continue,
     Quit,
+    CopyDocumentPath,
     OpenPicker,
+    OpenPickerHere,
     OpenGoDialog,
+    OpenGlobalSearch,
     OpenPath(PathBuf),
+    /// Several files marked in the picker; the first is focused.
+    OpenPaths(Vec<PathBuf>),
     GoToIndex(usize),
-    NextDocument,
-    PreviousDocument,
-}
-
-#[derive(Clone)]
-struct LineWrap {
-    rows: Vec<RowRange>,
-}
-
-#[derive(Clone, Copy)]
-struct RowRange {
-    start: usize,
-    end: usize,
+    /// Pin or unpin the current document.
+    TogglePin,
+    MoveDocument {
+        from: usize,
+        to: usize,
+    },
+    NextDocument,
+    PreviousDocument,
+    /// Back to the previously focused document.
+    AlternateDocument,
+    /// A `:` command that needs the queue or the run loop.
+    RunCommand(command::Command),
 }
 
 fn help_lines() -> Vec<Line<'static>> {
@@ -1003,34 +2731,272 @@ fn help_lines() -> Vec<Line<'static>> {
         Line::raw("Navigation:"),
         Line::raw("  Up/Down              Scroll line by line"),
         Line::raw("  Space                Page down"),
+        Line::raw("  } / {                Next/previous heading"),
         Line::raw("  ]                    Next document"),
         Line::raw("  [                    Previous document"),
+        Line::raw("  ' / Ctrl-^           Alternate (previously viewed) document"),
         Line::raw("  Mouse wheel          Scroll"),
         Line::raw(""),
         Line::raw("Search:"),
         Line::raw("  /                    Start search"),
+        Line::raw("  :                    Command prompt (:42, :goto N, :theme light|dark,"),
+        Line::raw("                       :reload, :toc, :export html FILE, :about)"),
+        Line::raw("  N%                   Jump to N percent of the document"),
         Line::raw("  Enter                Jump to first match"),
         Line::raw("  Esc                  Cancel search"),
         Line::raw("  n / N                Next/previous match"),
+        Line::raw("  Tab                  List matches while typing a search"),
+        Line::raw("  ?                    Search all queued documents"),
         Line::raw(""),
         Line::raw("Modes:"),
         Line::raw("  b                    Toggle BeeLine"),
+        Line::raw("  + / -                BeeLine intensity up/down"),
+        Line::raw("  B                    Toggle bionic reading"),
         Line::raw("  m                    Toggle plain mode"),
+        Line::raw("  w                    Toggle word/char/line counts"),
+        Line::raw("  W                    Toggle no-wrap mode (Left/Right scroll)"),
+        Line::raw("  D                    Toggle mermaid/plantuml diagram source"),
+        Line::raw("  s                    Toggle side-by-side markdown source"),
+        Line::raw("  M                    Toggle mini-map of headings and matches"),
+        Line::raw("  L                    Toggle line numbers"),
+        Line::raw("  c                    Toggle cursor line (j/k or mouse to move)"),
         Line::raw(""),
         Line::raw("General:"),
         Line::raw("  g                    Go to document"),
+        Line::raw("  p                    Pin/unpin document (pinned list first in g)"),
         Line::raw("  o                    Open markdown filesystem browser"),
+        Line::raw("  O                    Open browser in current document's directory"),
+        Line::raw("  Y                    Copy the document's path to the clipboard"),
         Line::raw("  h                    Toggle help"),
-        Line::raw("  q                    Quit"),
+        Line::raw("  q / Ctrl-C           Quit"),
+    ]
+}
+
+/// Scroll position that brings `pos` into view, either centered in the
+/// viewport or aligned to its top row.
+fn jump_scroll_target(pos: u16, viewport_height: u16, max_scroll: u16, center: bool) -> u16 {
+    let target = if center {
+        pos.saturating_sub(viewport_height / 2)
+    } else {
+        pos
+    };
+    target.min(max_scroll)
+}
+
+/// Columns for a line-number gutter: the digits of the largest number plus
+/// one separating space.
+fn line_number_gutter_width(line_count: usize) -> u16 {
+    let digits = line_count.max(1).ilog10() + 1;
+    digits as u16 + 1
+}
+
+/// Gutter rows for the viewport starting at `scroll`: each line's number,
+/// right-aligned, on its first row and blanks on its wrapped rows.
+fn line_number_gutter(offsets: &[u16], scroll: u16, height: u16, width: u16) -> Vec<Line<'static>> {
+    let digits = usize::from(width.saturating_sub(1));
+    (0..height)
+        .map_while(|row| {
+            let rendered = scroll.checked_add(row)?;
+            let (line_idx, wrap_row) = line_from_rendered(offsets, rendered)?;
+            Some(if wrap_row == 0 {
+                Line::raw(format!("{:>digits$} ", line_idx + 1))
+            } else {
+                Line::raw("")
+            })
+        })
+        .collect()
+}
+
+fn clamp_h_scroll(h_scroll: u16, max_line_width: u16, viewport_width: u16) -> u16 {
+    h_scroll.min(max_line_width.saturating_sub(viewport_width))
+}
+
+/// Placeholder shown instead of a blank body for empty or whitespace-only
+/// documents.
+fn empty_document_line(theme: &Theme) -> Line<'static> {
+    Line::styled("(empty document)", Style::new().fg(theme.footer).dim()).centered()
+}
+
+fn document_stats(text: &[String]) -> DocStats {
+    DocStats {
+        words: text
+            .iter()
+            .map(|line| line.split_whitespace().count())
+            .sum(),
+        chars: text.iter().map(|line| line.chars().count()).sum(),
+        lines: text.len(),
+    }
+}
+
+fn reading_time_minutes(word_count: usize, wpm: usize) -> u32 {
+    if word_count == 0 {
+        return 0;
+    }
+    let minutes = word_count.div_ceil(wpm.max(1));
+    minutes.min(u32::MAX as usize) as u32
+}
+
+fn use_plain_fallback(content_len: usize, force_full_render: bool) -> bool {
+    !force_full_render && content_len > LARGE_DOCUMENT_BYTES
+}
+
+/// Maps every cell's colors down to `depth` after the frame is drawn, so
+/// theme, BeeLine, and syntax colors are all covered in one place.
+fn downgrade_buffer(buffer: &mut ratatui::buffer::Buffer, depth: ColorDepth) {
+    for cell in buffer.content.iter_mut() {
+        cell.fg = downgrade_color(cell.fg, depth);
+        cell.bg = downgrade_color(cell.bg, depth);
+    }
+}
+
+fn flash_expired(elapsed: Duration) -> bool {
+    elapsed >= FLASH_DURATION
+}
+
+/// Footer note for a render that took noticeably long, e.g. `rendered in
+/// 320ms` or `rendered in 1.5s`.
+fn render_time_message(elapsed: Duration) -> Option<String> {
+    if elapsed < SLOW_RENDER_THRESHOLD {
+        return None;
+    }
+    let millis = elapsed.as_millis();
+    Some(if millis < 1000 {
+        format!("rendered in {}ms", millis)
+    } else {
+        format!("rendered in {:.1}s", elapsed.as_secs_f64())
+    })
+}
+
+/// Directory the picker starts in for `O`: the current document's parent,
+/// or `fallback` when the document path has no parent component.
+fn document_picker_dir(doc_path: &Path, fallback: &Path) -> PathBuf {
+    match doc_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => fallback.to_path_buf(),
+    }
+}
+
+fn open_error_message(path: &Path, err: &io::Error) -> String {
+    format!("Could not open {}: {}", path.display(), err)
+}
+
+struct HeadingAnchor {
+    level: u8,
+    text: String,
+    /// Rendered row the heading starts on.
+    scroll_pos: u16,
+}
+
+/// Titles of the enclosing headings at `scroll`, outermost first: the most
+/// recent heading at or above the top row for each level still in scope.
+fn breadcrumb_for_scroll(headings: &[HeadingAnchor], scroll: u16) -> Vec<String> {
+    let mut trail: Vec<&HeadingAnchor> = Vec::new();
+    for heading in headings
+        .iter()
+        .take_while(|heading| heading.scroll_pos <= scroll)
+    {
+        while trail.last().is_some_and(|last| last.level >= heading.level) {
+            trail.pop();
+        }
+        trail.push(heading);
+    }
+    trail
+        .into_iter()
+        .map(|heading| heading.text.clone())
+        .collect()
+}
+
+/// First heading below `scroll`, or `None` past the last heading.
+fn next_heading_pos(positions: &[u16], scroll: u16) -> Option<u16> {
+    positions.iter().copied().find(|&pos| pos > scroll)
+}
+
+/// Last heading above `scroll`, or `None` before the first heading.
+fn previous_heading_pos(positions: &[u16], scroll: u16) -> Option<u16> {
+    positions.iter().copied().rev().find(|&pos| pos < scroll)
+}
+
+/// What the `:about` screen reports, gathered at render time.
+struct Diagnostics<'a> {
+    terminal: (u16, u16),
+    color_depth: ColorDepth,
+    theme_name: &'a str,
+    code_theme: &'a str,
+    path: &'a str,
+    size_bytes: usize,
+    queue_len: usize,
+}
+
+fn diagnostics_lines(info: &Diagnostics<'_>) -> Vec<String> {
+    vec![
+        format!("mdr {}", env!("CARGO_PKG_VERSION")),
+        format!("Terminal:  {}x{}", info.terminal.0, info.terminal.1),
+        format!("Colors:    {}", info.color_depth.label()),
+        format!("Theme:     {} (code: {})", info.theme_name, info.code_theme),
+        format!("Document:  {}", info.path),
+        format!("Size:      {} bytes", info.size_bytes),
+        format!("Queue:     {} documents", info.queue_len),
     ]
 }
 
+fn pin_states(queue: &DocumentQueue) -> Vec<bool> {
+    queue.documents().iter().map(|doc| doc.pinned).collect()
+}
+
+/// Go-dialog row order as queue indices: pinned documents first, each group
+/// in queue order.
+fn go_dialog_order(pinned: &[bool]) -> Vec<usize> {
+    let (mut order, rest): (Vec<usize>, Vec<usize>) =
+        (0..pinned.len()).partition(|&idx| pinned[idx]);
+    order.extend(rest);
+    order
+}
+
 fn queue_label(current_index: usize, total: usize, path: &str) -> String {
     let total = total.max(1);
     let current = (current_index + 1).min(total);
     format!("[{}/{}] {}", current, total, path)
 }
 
+/// `area` narrowed by `padding` columns on each side, keeping at least one
+/// column so narrow terminals still show text.
+fn padded_area(area: Rect, padding: u16) -> Rect {
+    let padding = padding.min(area.width.saturating_sub(1) / 2);
+    Rect {
+        x: area.x + padding,
+        width: area.width - 2 * padding,
+        ..area
+    }
+}
+
+/// Whether the last page is showing, including when everything fits.
+fn is_at_bottom(scroll: u16, rendered_lines: u16, viewport_height: u16) -> bool {
+    scroll >= rendered_lines.saturating_sub(viewport_height)
+}
+
+/// Scroll after a reload: with `--follow`, a view that was at the bottom
+/// moves to the new bottom (`u16::MAX`, clamped when drawn); otherwise it
+/// stays put.
+fn follow_scroll(follow: bool, was_at_bottom: bool, scroll: u16) -> u16 {
+    if follow && was_at_bottom {
+        u16::MAX
+    } else {
+        scroll
+    }
+}
+
+/// `area` narrowed to at most `max_width` columns, centered horizontally.
+fn capped_area(area: Rect, max_width: Option<u16>) -> Rect {
+    match max_width {
+        Some(max_width) if area.width > max_width => Rect {
+            x: area.x + (area.width - max_width) / 2,
+            width: max_width,
+            ..area
+        },
+        _ => area,
+    }
+}
+
 fn centered_rect(width_percent: u16, height_percent: u16, area: Rect) -> Rect {
     let vertical = Layout::default()
         .direction(Direction::Vertical)
@@ -1050,6 +3016,43 @@ fn centered_rect(width_percent: u16, height_percent: u16, area: Rect) -> Rect {
         .split(vertical[1])[1]
 }
 
+/// Preview shown beside the picker list: the first rendered lines of a
+/// markdown file, or the number of entries inside a directory.
+fn picker_preview_lines(
+    entry: &PickerEntry,
+    theme: &Theme,
+    options: &RenderOptions,
+) -> Vec<Line<'static>> {
+    let dim = Style::new().fg(theme.footer).dim();
+    match entry.kind {
+        PickerEntryKind::MarkdownFile => match read_document(&entry.path, true) {
+            Ok(markdown) => {
+                let mut lines = if is_markdown(&entry.path) {
+                    render_markdown_with_links(&markdown, 0, theme, options).lines
+                } else {
+                    render_plain_lines(&markdown, options)
+                };
+                lines.truncate(PICKER_PREVIEW_LINES);
+                lines
+            }
+            Err(err) => vec![Line::styled(format!("Cannot preview: {}", err), dim)],
+        },
+        PickerEntryKind::Directory | PickerEntryKind::Parent => {
+            let text = match list_entries(entry.path.clone(), "", true) {
+                Ok(entries) => {
+                    let count = entries
+                        .iter()
+                        .filter(|e| e.kind != PickerEntryKind::Parent)
+                        .count();
+                    format!("{} {}", count, if count == 1 { "entry" } else { "entries" })
+                }
+                Err(err) => format!("Cannot list: {}", err),
+            };
+            vec![Line::styled(text, dim)]
+        }
+    }
+}
+
 fn find_matches(lines: &[String], query: &str) -> Vec<SearchMatch> {
     if query.is_empty() {
         return Vec::new();
@@ -1071,6 +3074,21 @@ fn find_matches(lines: &[String], query: &str) -> Vec<SearchMatch> {
         .collect()
 }
 
+fn build_global_matches(docs: &[Vec<String>], query: &str) -> Vec<GlobalSearchResult> {
+    let mut results = Vec::new();
+    for (doc_index, lines) in docs.iter().enumerate() {
+        for (ordinal, m) in find_matches(lines, query).into_iter().enumerate() {
+            results.push(GlobalSearchResult {
+                doc_index,
+                line_idx: m.line_idx,
+                preview: lines[m.line_idx].trim().to_string(),
+                ordinal,
+            });
+        }
+    }
+    results
+}
+
 fn apply_search_highlight(
     lines: &[Line<'static>],
     query: &str,
@@ -1167,28 +3185,93 @@ fn apply_search_highlight_line(
     }
 }
 
+/// Byte ranges of every term of `query` in `line`, or nothing unless all
+/// terms occur on the line.
 fn match_ranges(line: &str, query: &str) -> Vec<(usize, usize)> {
-    let hay = line.as_bytes();
-    let needle = query.as_bytes();
-    if needle.is_empty() || needle.len() > hay.len() {
+    let mut ranges = Vec::new();
+    for term in search_terms(query) {
+        let found = term_ranges(line, term);
+        if found.is_empty() {
+            return Vec::new();
+        }
+        ranges.extend(found);
+    }
+    ranges.sort_unstable();
+    ranges.dedup();
+    ranges
+}
+
+/// Splits a query into whitespace-separated terms; a `"quoted phrase"` is
+/// kept as one literal term (an unclosed quote runs to the end).
+fn search_terms(query: &str) -> Vec<&str> {
+    let mut terms = Vec::new();
+    let mut rest = query;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            if end > 0 {
+                terms.push(&quoted[..end]);
+            }
+            rest = quoted.get(end + 1..).unwrap_or("");
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            terms.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    terms
+}
+
+fn term_ranges(line: &str, query: &str) -> Vec<(usize, usize)> {
+    let needle: Vec<char> = query.chars().flat_map(fold_char).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    let char_bounds: Vec<(usize, usize)> = line
+        .char_indices()
+        .map(|(start, ch)| (start, start + ch.len_utf8()))
+        .collect();
+    let hay: Vec<(char, usize)> = line
+        .chars()
+        .enumerate()
+        .flat_map(|(idx, ch)| fold_char(ch).map(move |folded| (folded, idx)))
+        .collect();
+    if needle.len() > hay.len() {
         return Vec::new();
     }
+
     let mut ranges = Vec::new();
     for i in 0..=hay.len() - needle.len() {
-        let mut matched = true;
-        for j in 0..needle.len() {
-            if !hay[i + j].eq_ignore_ascii_case(&needle[j]) {
-                matched = false;
-                break;
-            }
+        let last = i + needle.len() - 1;
+        let starts_on_char = i == 0 || hay[i - 1].1 != hay[i].1;
+        let ends_on_char = last + 1 == hay.len() || hay[last + 1].1 != hay[last].1;
+        if !starts_on_char || !ends_on_char {
+            continue;
         }
-        if matched {
-            ranges.push((i, i + needle.len()));
+        if hay[i..=last]
+            .iter()
+            .zip(&needle)
+            .all(|((folded, _), expected)| folded == expected)
+        {
+            ranges.push((char_bounds[hay[i].1].0, char_bounds[hay[last].1].1));
         }
     }
     ranges
 }
 
+fn fold_char(ch: char) -> impl Iterator<Item = char> {
+    // Full case folding expands the sharp s, so `Straße` matches `STRASSE`.
+    match ch {
+        'ß' | 'ẞ' => 's'.to_lowercase().chain(Some('s')),
+        _ => ch.to_lowercase().chain(None),
+    }
+}
+
 fn build_search_matches(
     lines: &[String],
     query: &str,
@@ -1206,6 +3289,137 @@ fn build_search_matches(
     matches
 }
 
+/// Up to `width` characters of `line` around the match at byte `range`,
+/// trimmed of surrounding whitespace, with `…` marking cut-off ends.
+fn snippet_around(line: &str, range: Range<usize>, width: usize) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let match_start = line[..range.start].chars().count();
+    let match_len = line[range].chars().count();
+    let lead = chars.iter().take_while(|ch| ch.is_whitespace()).count();
+    let trail = chars.len()
+        - chars[lead..]
+            .iter()
+            .rev()
+            .take_while(|ch| ch.is_whitespace())
+            .count();
+    if trail - lead <= width {
+        return chars[lead..trail].iter().collect();
+    }
+    let context = width.saturating_sub(match_len) / 2;
+    let mut start = match_start.saturating_sub(context).max(lead);
+    let mut end = (start + width).min(trail);
+    start = end.saturating_sub(width).max(lead);
+    let mut snippet = String::new();
+    if start > lead {
+        snippet.push('…');
+        start += 1;
+    }
+    let cut_end = end < trail;
+    if cut_end {
+        end -= 1;
+    }
+    snippet.extend(&chars[start..end.max(start)]);
+    if cut_end {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Scroll offset `percent` of the way through the document, clamped to 100%.
+fn scroll_for_percent(percent: u16, max_scroll: u16) -> u16 {
+    (u32::from(percent.min(100)) * u32::from(max_scroll) / 100) as u16
+}
+
+/// Scroll offset that puts 1-based source line `line_number` at the top of
+/// the viewport: the first rendered row of the block containing that line.
+fn scroll_for_source_line(source_lines: &[usize], offsets: &[u16], line_number: usize) -> u16 {
+    let target = line_number.saturating_sub(1);
+    let containing = source_lines.partition_point(|&line| line <= target);
+    let block_line = match containing.checked_sub(1) {
+        Some(idx) => source_lines[idx],
+        None => return 0,
+    };
+    let first = source_lines.partition_point(|&line| line < block_line);
+    offsets.get(first).copied().unwrap_or(0)
+}
+
+/// Initial scroll for `--start-line`: like [`scroll_for_source_line`], but a
+/// line past the end of a `line_count`-line source scrolls to the bottom
+/// (the caller clamps `u16::MAX` to the last page).
+fn start_line_scroll(
+    source_lines: &[usize],
+    offsets: &[u16],
+    line_count: usize,
+    line_number: usize,
+) -> u16 {
+    if line_number > line_count {
+        return u16::MAX;
+    }
+    scroll_for_source_line(source_lines, offsets, line_number)
+}
+
+/// Position of the top row in terms that survive re-wrapping: the source
+/// block, the rendered line within it, and the first character on the row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ScrollAnchor {
+    source_line: usize,
+    line_in_block: usize,
+    char_idx: usize,
+}
+
+fn anchor_at_scroll(
+    source_lines: &[usize],
+    wraps: &[LineWrap],
+    offsets: &[u16],
+    scroll: u16,
+) -> Option<ScrollAnchor> {
+    let (line_idx, row) = line_from_rendered(offsets, scroll)?;
+    let source_line = *source_lines.get(line_idx)?;
+    let first = source_lines.partition_point(|&line| line < source_line);
+    let char_idx = wraps
+        .get(line_idx)
+        .and_then(|wrap| wrap.rows.get(usize::from(row)))
+        .map_or(0, |range| range.start);
+    Some(ScrollAnchor {
+        source_line,
+        line_in_block: line_idx.saturating_sub(first),
+        char_idx,
+    })
+}
+
+/// Scroll offset that puts `anchor` back at the top after re-wrapping. If
+/// the block now has fewer lines, its last line is used.
+fn scroll_for_anchor(
+    anchor: &ScrollAnchor,
+    source_lines: &[usize],
+    wraps: &[LineWrap],
+    offsets: &[u16],
+) -> u16 {
+    let first = source_lines.partition_point(|&line| line < anchor.source_line);
+    let end = source_lines.partition_point(|&line| line <= anchor.source_line);
+    if first >= offsets.len() {
+        return offsets.last().copied().unwrap_or(0);
+    }
+    let line_idx = (first + anchor.line_in_block).min(end.max(first + 1) - 1);
+    let row = wraps.get(line_idx).map_or(0, |wrap| {
+        wrap.rows
+            .iter()
+            .rposition(|range| range.start <= anchor.char_idx)
+            .unwrap_or(0)
+    });
+    offsets
+        .get(line_idx)
+        .map_or(0, |&offset| offset.saturating_add(row as u16))
+}
+
+/// 0-based source line behind the rendered row at `scroll`; the inverse of
+/// [`scroll_for_source_line`] at block granularity.
+fn source_line_at_scroll(source_lines: &[usize], offsets: &[u16], scroll: u16) -> usize {
+    line_from_rendered(offsets, scroll)
+        .and_then(|(line_idx, _)| source_lines.get(line_idx).copied())
+        .unwrap_or(0)
+}
+
 fn line_from_rendered(offsets: &[u16], rendered_line: u16) -> Option<(usize, u16)> {
     if offsets.is_empty() {
         return None;
@@ -1284,7 +3498,9 @@ fn update_hover(state: &AppState, column: u16, row: u16) -> Option<String> {
     }
     let local_y = row.saturating_sub(state.content_area.y);
     let rendered_line = state.scroll.saturating_add(local_y);
-    let local_x = column.saturating_sub(state.content_area.x);
+    let local_x = column
+        .saturating_sub(state.content_area.x)
+        .saturating_add(state.h_scroll);
     link_at_position(
         &state.current_links,
         &state.current_wraps,
@@ -1295,6 +3511,154 @@ fn update_hover(state: &AppState, column: u16, row: u16) -> Option<String> {
     )
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct TextPosition {
+    line_idx: usize,
+    char_idx: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Selection {
+    anchor: TextPosition,
+    head: TextPosition,
+}
+
+/// Document position under a mouse cell. Points outside the content area
+/// are clamped to its edges so drags past the border keep extending the
+/// selection; columns past the end of a row snap to its last character.
+fn text_position_at_mouse(state: &AppState, column: u16, row: u16) -> Option<TextPosition> {
+    let area = state.content_area;
+    if area.width == 0 || area.height == 0 {
+        return None;
+    }
+    let column = column.clamp(area.x, area.x + area.width - 1);
+    let row = row.clamp(area.y, area.y + area.height - 1);
+    let rendered_line = state.scroll.saturating_add(row - area.y);
+    let local_x = (column - area.x).saturating_add(state.h_scroll);
+
+    let (line_idx, wrap_row) = line_from_rendered(&state.current_line_offsets, rendered_line)?;
+    let row_range = state
+        .current_wraps
+        .get(line_idx)?
+        .rows
+        .get(wrap_row as usize)?;
+    let line_text = state.current_lines_text.get(line_idx)?;
+    let char_idx = char_index_at_col(line_text, row_range, local_x as usize)
+        .unwrap_or_else(|| row_range.end.saturating_sub(1).max(row_range.start));
+    Some(TextPosition { line_idx, char_idx })
+}
+
+/// Per-line `(line_idx, start_char, end_char)` ranges covered by a selection
+/// between `a` and `b`, given in either order. Both endpoints are included.
+fn selection_line_ranges(
+    a: TextPosition,
+    b: TextPosition,
+    lines_text: &[String],
+) -> Vec<(usize, usize, usize)> {
+    let (start, end) = if a <= b { (a, b) } else { (b, a) };
+    (start.line_idx..=end.line_idx)
+        .filter_map(|line_idx| {
+            let len = lines_text.get(line_idx)?.chars().count();
+            let from = if line_idx == start.line_idx {
+                start.char_idx.min(len)
+            } else {
+                0
+            };
+            let to = if line_idx == end.line_idx {
+                (end.char_idx + 1).min(len)
+            } else {
+                len
+            };
+            Some((line_idx, from, to.max(from)))
+        })
+        .collect()
+}
+
+fn selected_text(lines_text: &[String], ranges: &[(usize, usize, usize)]) -> String {
+    ranges
+        .iter()
+        .filter_map(|&(line_idx, start, end)| {
+            let line = lines_text.get(line_idx)?;
+            Some(
+                line.chars()
+                    .skip(start)
+                    .take(end - start)
+                    .collect::<String>(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn apply_selection_highlight(
+    lines: &[Line<'static>],
+    ranges: &[(usize, usize, usize)],
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let mut lines = lines.to_vec();
+    let highlight = Style::new().bg(theme.search_bg).fg(theme.search_fg);
+    for &(line_idx, start, end) in ranges {
+        let Some(line) = lines.get_mut(line_idx) else {
+            continue;
+        };
+        let mut spans = Vec::new();
+        let mut char_index = 0usize;
+        for span in &line.spans {
+            for ch in span.content.chars() {
+                let style = if (start..end).contains(&char_index) {
+                    span.style.patch(highlight)
+                } else {
+                    span.style
+                };
+                match spans.last_mut() {
+                    Some(Span {
+                        content,
+                        style: last,
+                    }) if *last == style => {
+                        content.to_mut().push(ch);
+                    }
+                    _ => spans.push(Span::styled(ch.to_string(), style)),
+                }
+                char_index += 1;
+            }
+        }
+        line.spans = spans;
+    }
+    lines
+}
+
+/// Clipboard escape sequence and footer confirmation for copying a
+/// document's (canonical) path with `Y`.
+fn document_path_copy(path: &Path) -> (String, String) {
+    let text = path.display().to_string();
+    let message = format!("Copied path: {}", text);
+    (osc52_copy_sequence(&text), message)
+}
+
+/// OSC 52 escape asking the terminal to place `text` on the clipboard.
+fn osc52_copy_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
 fn char_index_at_col(line_text: &str, row_range: &RowRange, column: usize) -> Option<usize> {
     if row_range.start >= row_range.end {
         return None;
@@ -1307,7 +3671,7 @@ fn char_index_at_col(line_text: &str, row_range: &RowRange, column: usize) -> Op
         if i >= row_range.end {
             break;
         }
-        let width = UnicodeWidthChar::width(ch).unwrap_or(0).max(1);
+        let width = char_width(ch);
         if column < col + width {
             return Some(i);
         }
@@ -1316,26 +3680,88 @@ fn char_index_at_col(line_text: &str, row_range: &RowRange, column: usize) -> Op
     None
 }
 
-fn open_url(url: &str) -> io::Result<()> {
-    #[cfg(target_os = "macos")]
-    let mut cmd = {
-        let mut c = Command::new("open");
-        c.arg(url);
-        c
-    };
-    #[cfg(target_os = "linux")]
-    let mut cmd = {
-        let mut c = Command::new("xdg-open");
-        c.arg(url);
-        c
-    };
-    #[cfg(target_os = "windows")]
-    let mut cmd = {
-        let mut c = Command::new("cmd");
-        c.args(["/C", "start", url]);
-        c
-    };
-    cmd.status().map(|_| ())
+fn open_url(url: &str, browser: Option<&str>, schemes: &LinkSchemes) -> io::Result<()> {
+    let (program, args) = link_command(url, browser, schemes, std::env::consts::OS);
+    Command::new(program).args(args).status().map(|_| ())
+}
+
+/// Program and arguments used to open `url`: the handler registered for its
+/// scheme if there is one, otherwise the browser.
+fn link_command(
+    url: &str,
+    browser: Option<&str>,
+    schemes: &LinkSchemes,
+    target_os: &str,
+) -> (String, Vec<String>) {
+    browser_command(url, schemes.resolve(url, browser), target_os)
+}
+
+/// Program and arguments used to open `url`. A `browser` override is split
+/// on whitespace; a `%s` placeholder is replaced by the URL, otherwise the URL
+/// is appended.
+fn browser_command(url: &str, browser: Option<&str>, target_os: &str) -> (String, Vec<String>) {
+    if let Some(browser) = browser.map(str::trim).filter(|browser| !browser.is_empty()) {
+        let mut parts = browser.split_whitespace().map(str::to_string);
+        let program = parts.next().unwrap_or_default();
+        let mut args: Vec<String> = parts.collect();
+        if args.iter().any(|arg| arg.contains("%s")) {
+            for arg in &mut args {
+                *arg = arg.replace("%s", url);
+            }
+        } else {
+            args.push(url.to_string());
+        }
+        return (program, args);
+    }
+    match target_os {
+        "macos" => ("open".to_string(), vec![url.to_string()]),
+        "windows" => (
+            "cmd".to_string(),
+            vec!["/C".to_string(), "start".to_string(), url.to_string()],
+        ),
+        _ => ("xdg-open".to_string(), vec![url.to_string()]),
+    }
+}
+
+/// The browser command to use from `$BROWSER`, which by convention lists
+/// fallback commands separated like `$PATH` (`firefox:chromium`): the first
+/// entry whose program can be found, or `None` if none can.
+pub fn browser_from_env(value: &str) -> Option<String> {
+    let search_path = std::env::var_os("PATH").unwrap_or_default();
+    first_available_browser(value, |program| program_on_path(program, &search_path))
+}
+
+fn first_available_browser(value: &str, available: impl Fn(&str) -> bool) -> Option<String> {
+    std::env::split_paths(value)
+        .map(|entry| entry.to_string_lossy().trim().to_string())
+        .find(|entry| entry.split_whitespace().next().is_some_and(&available))
+}
+
+/// Whether `program` names a file directly or is found in a directory of
+/// `search_path`.
+fn program_on_path(program: &str, search_path: &std::ffi::OsStr) -> bool {
+    if Path::new(program).components().count() > 1 {
+        return Path::new(program).is_file();
+    }
+    std::env::split_paths(search_path).any(|dir| {
+        dir.join(program).is_file()
+            || (cfg!(windows) && dir.join(format!("{}.exe", program)).is_file())
+    })
+}
+
+/// Selection index one above `idx` in a list of `len` entries, wrapping from
+/// the first entry to the last.
+fn wrapping_prev(idx: usize, len: usize) -> usize {
+    if idx == 0 {
+        len.saturating_sub(1)
+    } else {
+        (idx - 1).min(len.saturating_sub(1))
+    }
+}
+
+/// Selection index one below `idx`, wrapping from the last entry to the first.
+fn wrapping_next(idx: usize, len: usize) -> usize {
+    if idx + 1 >= len { 0 } else { idx + 1 }
 }
 
 fn build_wraps(lines: &[String], width: u16) -> (Vec<LineWrap>, Vec<u16>) {
@@ -1354,91 +3780,52 @@ fn build_wraps(lines: &[String], width: u16) -> (Vec<LineWrap>, Vec<u16>) {
     (wraps, offsets)
 }
 
-fn wrap_line_ranges(line: &str, width: usize) -> LineWrap {
-    if line.is_empty() {
-        return LineWrap {
-            rows: vec![RowRange { start: 0, end: 0 }],
-        };
+/// Gutter rows (deduplicated, ascending) for document `positions` when
+/// `total_lines` rendered lines are squeezed into `height` rows.
+fn minimap_ticks(positions: &[u16], total_lines: u16, height: u16) -> Vec<u16> {
+    if total_lines == 0 || height == 0 {
+        return Vec::new();
     }
-    let chars: Vec<char> = line.chars().collect();
-    let widths: Vec<usize> = chars
+    let mut rows: Vec<u16> = positions
         .iter()
-        .map(|ch| UnicodeWidthChar::width(*ch).unwrap_or(0).max(1))
+        .map(|&pos| {
+            let row = u32::from(pos) * u32::from(height) / u32::from(total_lines);
+            row.min(u32::from(height) - 1) as u16
+        })
         .collect();
-    let mut tokens: Vec<(usize, usize)> = Vec::new();
-    let mut start = 0usize;
-    let mut in_ws = chars[0].is_whitespace();
-    for (i, ch) in chars.iter().enumerate() {
-        let is_ws = ch.is_whitespace();
-        if is_ws != in_ws {
-            tokens.push((start, i));
-            start = i;
-            in_ws = is_ws;
-        }
-    }
-    tokens.push((start, chars.len()));
-
-    let mut rows: Vec<RowRange> = Vec::new();
-    let mut row_start = 0usize;
-    let mut row_width = 0usize;
-    let mut row_end = 0usize;
-
-    for (tok_start, tok_end) in tokens {
-        let mut token_start = tok_start;
-        let mut token_width: usize = widths[tok_start..tok_end].iter().sum();
+    rows.sort_unstable();
+    rows.dedup();
+    rows
+}
 
-        loop {
-            let remaining = width.saturating_sub(row_width);
-            if token_width <= remaining {
-                if row_width == 0 {
-                    row_start = token_start;
-                }
-                row_width = row_width.saturating_add(token_width);
-                row_end = tok_end;
-                break;
-            }
-            if row_width > 0 {
-                rows.push(RowRange {
-                    start: row_start,
-                    end: row_end,
-                });
-                row_width = 0;
-                continue;
-            }
-            let mut consumed = 0usize;
-            let mut split_end = token_start;
-            while split_end < tok_end {
-                let w = widths[split_end];
-                if consumed + w > width && consumed > 0 {
-                    break;
-                }
-                consumed = consumed.saturating_add(w);
-                split_end += 1;
-                if consumed >= width {
-                    break;
-                }
-            }
-            let chunk_end = split_end.max(token_start + 1);
-            rows.push(RowRange {
-                start: token_start,
-                end: chunk_end,
-            });
-            token_start = chunk_end;
-            if token_start >= tok_end {
-                break;
-            }
-            token_width = widths[token_start..tok_end].iter().sum();
-        }
-    }
+fn line_rows(wraps: &[LineWrap], line_idx: usize) -> u16 {
+    wraps
+        .get(line_idx)
+        .map_or(1, |wrap| wrap.rows.len().max(1) as u16)
+}
 
-    if row_width > 0 {
-        rows.push(RowRange {
-            start: row_start,
-            end: row_end,
-        });
+/// Screen rows covered by logical line `line_idx` at the given scroll,
+/// clipped to `area`; `None` when the line is off screen.
+fn cursor_line_rect(
+    area: Rect,
+    wraps: &[LineWrap],
+    offsets: &[u16],
+    line_idx: usize,
+    scroll: u16,
+) -> Option<Rect> {
+    let start = *offsets.get(line_idx)?;
+    let end = start.saturating_add(line_rows(wraps, line_idx));
+    let top = start.max(scroll);
+    let bottom = end.min(scroll.saturating_add(area.height));
+    if top >= bottom {
+        return None;
     }
-
-    LineWrap { rows }
+    Some(Rect {
+        x: area.x,
+        y: area.y + (top - scroll),
+        width: area.width,
+        height: bottom - top,
+    })
 }
 
 fn row_for_char(wrap: &LineWrap, char_idx: usize) -> Option<usize> {
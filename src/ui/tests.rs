@@ -10,6 +10,7 @@ fn wrap_ranges_and_link_hit_test() {
         start_char: 2,
         end_char: 8,
         url: "https://example.com".to_string(),
+        exists: true,
     }];
 
     let hit_row0 = link_at_position(&links, &wraps, &offsets, &lines_text, 0, 2);
@@ -102,6 +103,53 @@ fn bracket_keys_emit_document_navigation_actions() {
     assert!(matches!(prev, KeyAction::PreviousDocument));
 }
 
+#[test]
+fn remapped_keys_emit_their_actions_and_others_keep_defaults() {
+    let mut state = AppState::new(true);
+    state.keymap.bind("next_document", "n").unwrap();
+    state.keymap.bind("previous_document", "p").unwrap();
+    state.keymap.bind("go_dialog", "ctrl-g").unwrap();
+
+    let next = state.handle_key_input(KeyCode::Char('n'), 100, 10);
+    let prev = state.handle_key_input(KeyCode::Char('p'), 100, 10);
+    let old_next = state.handle_key_input(KeyCode::Char(']'), 100, 10);
+    let picker = state.handle_key_input(KeyCode::Char('o'), 100, 10);
+    let go = state.handle_key_event(
+        KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL),
+        100,
+        10,
+    );
+
+    assert!(matches!(next, KeyAction::NextDocument));
+    assert!(matches!(prev, KeyAction::PreviousDocument));
+    assert!(matches!(old_next, KeyAction::None));
+    assert!(matches!(picker, KeyAction::OpenPicker));
+    assert!(matches!(go, KeyAction::OpenGoDialog));
+}
+
+#[test]
+fn remapped_keys_are_typed_into_a_search() {
+    let mut state = AppState::new(true);
+    state.keymap.bind("next_document", "n").unwrap();
+    state.search_mode = true;
+
+    let action = state.handle_key_input(KeyCode::Char('n'), 100, 10);
+
+    assert!(matches!(action, KeyAction::None));
+    assert_eq!(state.search_query, "n");
+}
+
+#[test]
+fn custom_bindings_win_over_the_built_in_key_they_shadow() {
+    let mut state = AppState::new(false);
+    state.keymap.bind("toggle_pin", "b").unwrap();
+
+    let action = state.handle_key_input(KeyCode::Char('b'), 100, 10);
+
+    assert!(matches!(action, KeyAction::TogglePin));
+    assert!(!state.beeline_enabled);
+}
+
 #[test]
 fn g_key_emits_go_dialog_action() {
     let mut state = AppState::new(true);
@@ -152,6 +200,28 @@ fn picker_escape_closes_overlay_without_queue_change() {
     assert_eq!(selected_before, 0);
 }
 
+#[test]
+fn picker_escape_clears_query_before_closing() {
+    let mut state = AppState::new(true);
+    let root = tempfile::tempdir().expect("tempdir");
+    std::fs::write(root.path().join("alpha.md"), "# a").expect("write md");
+    std::fs::write(root.path().join("beta.md"), "# b").expect("write md");
+    state.open_picker(root.path().to_path_buf());
+    let unfiltered = state.picker_entries.len();
+    for ch in "alp".chars() {
+        state.handle_picker_key_input(KeyCode::Char(ch));
+    }
+    assert!(state.picker_entries.len() < unfiltered);
+
+    state.handle_picker_key_input(KeyCode::Esc);
+    assert!(state.picker_open);
+    assert_eq!(state.picker_query, "");
+    assert_eq!(state.picker_entries.len(), unfiltered);
+
+    state.handle_picker_key_input(KeyCode::Esc);
+    assert!(!state.picker_open);
+}
+
 #[test]
 fn picker_enter_on_directory_descends_into_it() {
     let mut state = AppState::new(true);
@@ -195,6 +265,85 @@ fn picker_enter_on_markdown_file_returns_open_path() {
     );
 }
 
+#[test]
+fn picker_space_toggles_marks_on_files_only() {
+    let mut state = AppState::new(true);
+    let root = tempfile::tempdir().expect("tempdir");
+    std::fs::create_dir(root.path().join("sub")).expect("mkdir");
+    std::fs::write(root.path().join("a.md"), "# a").expect("write md");
+    state.open_picker(root.path().to_path_buf());
+    let index_of = |state: &AppState, label: &str| {
+        state
+            .picker_entries
+            .iter()
+            .position(|entry| entry.label == label)
+            .expect("entry should exist")
+    };
+
+    state.picker_selected = index_of(&state, "sub/");
+    state.handle_picker_key_input(KeyCode::Char(' '));
+    assert!(state.picker_marked.is_empty());
+
+    state.picker_selected = index_of(&state, "a.md");
+    state.handle_picker_key_input(KeyCode::Char(' '));
+    assert_eq!(state.picker_marked.len(), 1);
+    state.handle_picker_key_input(KeyCode::Char(' '));
+    assert!(state.picker_marked.is_empty());
+    assert_eq!(state.picker_query, "");
+}
+
+#[test]
+fn picker_enter_returns_every_marked_path() {
+    let mut state = AppState::new(true);
+    let root = tempfile::tempdir().expect("tempdir");
+    for name in ["a.md", "b.md", "c.md"] {
+        std::fs::write(root.path().join(name), "# x").expect("write md");
+    }
+    state.open_picker(root.path().to_path_buf());
+    for label in ["c.md", "a.md"] {
+        state.picker_selected = state
+            .picker_entries
+            .iter()
+            .position(|entry| entry.label == label)
+            .expect("entry should exist");
+        state.handle_picker_key_input(KeyCode::Char(' '));
+    }
+
+    let result = state.handle_picker_key_input(KeyCode::Enter);
+
+    let EventResult::OpenPaths(paths) = result else {
+        panic!("expected OpenPaths");
+    };
+    let names: Vec<_> = paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, ["a.md", "c.md"]);
+    assert!(!state.picker_open);
+}
+
+#[test]
+fn opening_marked_paths_queues_them_and_focuses_the_first() {
+    let root = tempfile::tempdir().expect("tempdir");
+    let a = root.path().join("a.md");
+    let b = root.path().join("b.md");
+    std::fs::write(&a, "# a").expect("write md");
+    std::fs::write(&b, "# b").expect("write md");
+    let mut queue =
+        DocumentQueue::new(vec![QueuedDocument::new("start.md".into(), String::new())]).unwrap();
+    let mut state = AppState::new(false);
+
+    apply_event_result(
+        EventResult::OpenPaths(vec![a.clone(), b]),
+        &mut queue,
+        &mut state,
+        root.path(),
+    );
+
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.current().path, a);
+}
+
 #[test]
 fn switching_documents_updates_queue_indicator() {
     assert_eq!(queue_label(0, 3, "a.md"), "[1/3] a.md");
@@ -223,12 +372,1646 @@ fn help_lines_show_separate_previous_and_next_bindings() {
 #[test]
 fn go_dialog_enter_returns_selected_index() {
     let mut state = AppState::new(true);
-    state.open_go_dialog(3, 1);
+    state.open_go_dialog(&[false; 3], 1);
 
-    let move_result = state.handle_go_dialog_key_input(KeyCode::Down);
+    let move_result = state.handle_go_dialog_key_input(KeyCode::Down, KeyModifiers::NONE);
     assert!(matches!(move_result, EventResult::Continue));
 
-    let result = state.handle_go_dialog_key_input(KeyCode::Enter);
+    let result = state.handle_go_dialog_key_input(KeyCode::Enter, KeyModifiers::NONE);
     assert!(matches!(result, EventResult::GoToIndex(2)));
     assert!(!state.go_dialog_open);
 }
+
+#[test]
+fn global_matches_aggregate_across_documents_with_doc_indices() {
+    let docs = vec![
+        vec!["alpha needle".to_string(), "nothing".to_string()],
+        vec![
+            "first line".to_string(),
+            "Needle here".to_string(),
+            "and needle again".to_string(),
+        ],
+    ];
+
+    let results = build_global_matches(&docs, "needle");
+
+    let summary: Vec<(usize, usize, usize)> = results
+        .iter()
+        .map(|r| (r.doc_index, r.line_idx, r.ordinal))
+        .collect();
+    assert_eq!(summary, vec![(0, 0, 0), (1, 1, 0), (1, 2, 1)]);
+    assert_eq!(results[1].preview, "Needle here");
+}
+
+#[test]
+fn global_search_enter_goes_to_document_and_queues_search_jump() {
+    let mut state = AppState::new(true);
+    state.open_global_search(vec![
+        vec!["one".to_string()],
+        vec!["two".to_string(), "needle".to_string()],
+    ]);
+    for c in "needle".chars() {
+        state.handle_global_search_key_input(KeyCode::Char(c));
+    }
+
+    let result = state.handle_global_search_key_input(KeyCode::Enter);
+    assert!(matches!(result, EventResult::GoToIndex(1)));
+    assert!(!state.global_search_open);
+
+    state.on_document_changed();
+    assert_eq!(state.search_query, "needle");
+    assert!(state.scroll_to_search_match);
+}
+
+#[test]
+fn global_search_jump_skips_matches_hidden_in_link_urls() {
+    let theme = Theme::pastel();
+    let second: String = (0..40)
+        .map(|i| match i {
+            5 => "a needle first\n\n".to_string(),
+            10 => "see [the docs](https://needle.example)\n\n".to_string(),
+            30 => "second needle\n\n".to_string(),
+            _ => format!("filler {i}\n\n"),
+        })
+        .collect();
+    let mut queue = DocumentQueue::new(vec![
+        QueuedDocument::new("a.md".into(), "start\n".to_string()),
+        QueuedDocument::new("b.md".into(), second),
+    ])
+    .unwrap();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(40, 12)).expect("test terminal");
+    let draw = |terminal: &mut Terminal<ratatui::backend::TestBackend>,
+                state: &mut AppState,
+                queue: &DocumentQueue| {
+        let doc = queue.current();
+        let context = RenderContext {
+            path: "doc.md",
+            markdown: &doc.content,
+            queue_index: queue.current_index(),
+            queue_len: queue.len(),
+            queue_paths: &[],
+            plain_text: false,
+        };
+        terminal
+            .draw(|frame| state.render(frame, &context, &theme))
+            .unwrap();
+    };
+
+    let mut state = AppState::new(false);
+    draw(&mut terminal, &mut state, &queue);
+    apply_event_result(
+        EventResult::OpenGlobalSearch,
+        &mut queue,
+        &mut state,
+        Path::new("."),
+    );
+    for c in "needle".chars() {
+        state.handle_global_search_key_input(KeyCode::Char(c));
+    }
+    // The URL is not rendered, so it is not a result.
+    let previews: Vec<&str> = state
+        .global_search_results
+        .iter()
+        .map(|result| result.preview.as_str())
+        .collect();
+    assert_eq!(previews, vec!["a needle first", "second needle"]);
+
+    state.handle_global_search_key_input(KeyCode::Down);
+    let result = state.handle_global_search_key_input(KeyCode::Enter);
+    apply_event_result(result, &mut queue, &mut state, Path::new("."));
+    draw(&mut terminal, &mut state, &queue);
+
+    assert_eq!(state.search_index, 1);
+    let active = &state.search_matches[state.search_index];
+    assert_eq!(state.current_lines_text[active.line_idx], "second needle");
+    assert!(state.scroll > 0);
+}
+
+#[test]
+fn match_ranges_folds_unicode_case_on_char_boundaries() {
+    let line = "Café CAFÉ café";
+    let ranges = match_ranges(line, "café");
+    assert_eq!(ranges.len(), 3);
+    for (start, end) in &ranges {
+        assert!(line.is_char_boundary(*start) && line.is_char_boundary(*end));
+        assert_eq!(line[*start..*end].to_lowercase(), "café");
+    }
+
+    let ranges = match_ranges("Die Straße", "STRASSE");
+    assert_eq!(ranges, vec![(4, 11)]);
+}
+
+#[test]
+fn match_ranges_with_multibyte_query_does_not_split_characters() {
+    let line = "日本語のテキスト 日本";
+    let ranges = match_ranges(line, "日本");
+    assert_eq!(ranges, vec![(0, 6), (25, 31)]);
+    assert!(match_ranges("éé", "\u{301}").is_empty());
+
+    let highlighted = apply_search_highlight_line(
+        &Line::raw(line.to_string()),
+        "日本",
+        None,
+        0,
+        &Theme::pastel(),
+    );
+    let text: String = highlighted
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    assert_eq!(text, line);
+}
+
+#[test]
+fn render_cache_hits_only_for_unchanged_inputs() {
+    let key = RenderKey {
+        doc_index: 0,
+        width: 80,
+        plain_mode: false,
+        beeline_enabled: true,
+        bionic_enabled: false,
+        beeline_intensity: 100,
+        no_wrap: false,
+        line_numbers: false,
+    };
+    let mut cache = RenderCache::default();
+    assert!(cache.lookup(&key).is_none());
+
+    cache.store(key, vec![Line::raw("cached")], 0);
+    assert_eq!(
+        cache
+            .lookup(&key)
+            .map(|(lines, gutter)| (lines.len(), gutter)),
+        Some((1, 0))
+    );
+
+    let resized = RenderKey { width: 60, ..key };
+    assert!(cache.lookup(&resized).is_none());
+
+    cache.invalidate();
+    assert!(cache.lookup(&key).is_none());
+}
+
+#[test]
+fn large_documents_fall_back_to_plain_unless_forced() {
+    assert!(!use_plain_fallback(1024, false));
+    assert!(!use_plain_fallback(LARGE_DOCUMENT_BYTES, false));
+    assert!(use_plain_fallback(LARGE_DOCUMENT_BYTES + 1, false));
+    assert!(!use_plain_fallback(LARGE_DOCUMENT_BYTES + 1, true));
+}
+
+#[test]
+fn reading_time_rounds_up_to_whole_minutes() {
+    assert_eq!(reading_time_minutes(0, 200), 0);
+    assert_eq!(reading_time_minutes(1, 200), 1);
+    assert_eq!(reading_time_minutes(200, 200), 1);
+    assert_eq!(reading_time_minutes(201, 200), 2);
+    assert_eq!(reading_time_minutes(800, 200), 4);
+    assert_eq!(reading_time_minutes(10, 0), 10);
+}
+
+#[test]
+fn document_stats_counts_words_chars_and_lines() {
+    let text = vec![
+        "Hello, world!".to_string(),
+        String::new(),
+        "- one-two; three...".to_string(),
+    ];
+
+    let stats = document_stats(&text);
+
+    assert_eq!(
+        stats,
+        DocStats {
+            words: 5,
+            chars: 32,
+            lines: 3,
+        }
+    );
+}
+
+#[test]
+fn w_key_toggles_stats_readout() {
+    let mut state = AppState::new(true);
+    state.handle_key_input(KeyCode::Char('w'), 100, 10);
+    assert!(state.show_stats);
+    state.handle_key_input(KeyCode::Char('w'), 100, 10);
+    assert!(!state.show_stats);
+}
+
+#[test]
+fn horizontal_scroll_clamps_to_widest_line() {
+    assert_eq!(clamp_h_scroll(0, 120, 80), 0);
+    assert_eq!(clamp_h_scroll(30, 120, 80), 30);
+    assert_eq!(clamp_h_scroll(50, 120, 80), 40);
+    assert_eq!(clamp_h_scroll(10, 60, 80), 0);
+}
+
+#[test]
+fn shift_w_toggles_no_wrap_and_arrows_scroll_horizontally() {
+    let mut state = AppState::new(true);
+    state.max_line_width = 100;
+    state.viewport_width = 90;
+
+    state.handle_key_input(KeyCode::Char('W'), 100, 10);
+    assert!(state.no_wrap);
+    state.handle_key_input(KeyCode::Right, 100, 10);
+    state.handle_key_input(KeyCode::Right, 100, 10);
+    state.handle_key_input(KeyCode::Right, 100, 10);
+    assert_eq!(state.h_scroll, 10);
+    state.handle_key_input(KeyCode::Left, 100, 10);
+    assert_eq!(state.h_scroll, 6);
+}
+
+#[test]
+fn jump_target_centers_match_and_clamps_at_edges() {
+    assert_eq!(jump_scroll_target(50, 20, 200, true), 40);
+    assert_eq!(jump_scroll_target(5, 20, 200, true), 0);
+    assert_eq!(jump_scroll_target(195, 20, 180, true), 180);
+    assert_eq!(jump_scroll_target(50, 20, 200, false), 50);
+    assert_eq!(jump_scroll_target(195, 20, 180, false), 180);
+}
+
+#[test]
+fn n_key_centers_next_match_in_viewport() {
+    let mut state = AppState::new(true);
+    state.viewport_height = 10;
+    state.search_query = "x".to_string();
+    state.search_matches = vec![
+        SearchMatch {
+            line_idx: 0,
+            start: 0,
+            end: 1,
+            start_char: 0,
+            scroll_pos: 2,
+        },
+        SearchMatch {
+            line_idx: 30,
+            start: 0,
+            end: 1,
+            start_char: 0,
+            scroll_pos: 30,
+        },
+    ];
+
+    state.handle_key_input(KeyCode::Char('n'), 100, 10);
+    assert_eq!(state.scroll, 25);
+
+    state.center_search_matches = false;
+    state.handle_key_input(KeyCode::Char('N'), 100, 10);
+    assert_eq!(state.scroll, 2);
+}
+
+#[test]
+fn beeline_and_bionic_modes_are_mutually_exclusive() {
+    let mut state = AppState::new(true);
+
+    state.handle_key_input(KeyCode::Char('B'), 100, 10);
+    assert!(state.bionic_enabled);
+    assert!(!state.beeline_enabled);
+
+    state.handle_key_input(KeyCode::Char('b'), 100, 10);
+    assert!(state.beeline_enabled);
+    assert!(!state.bionic_enabled);
+}
+
+#[test]
+fn plus_and_minus_adjust_beeline_intensity_within_bounds() {
+    let mut state = AppState::new(true);
+    state.handle_key_input(KeyCode::Char('+'), 100, 10);
+    assert_eq!(state.beeline_intensity, 100);
+
+    for _ in 0..12 {
+        state.handle_key_input(KeyCode::Char('-'), 100, 10);
+    }
+    assert_eq!(state.beeline_intensity, 0);
+
+    state.handle_key_input(KeyCode::Char('+'), 100, 10);
+    assert_eq!(state.beeline_intensity, 10);
+}
+
+#[test]
+fn browser_override_replaces_platform_default() {
+    let url = "https://example.com";
+
+    let (program, args) = browser_command(url, Some("firefox --new-tab"), "linux");
+    assert_eq!(program, "firefox");
+    assert_eq!(args, vec!["--new-tab", url]);
+
+    let (program, args) = browser_command(url, Some("w3m %s"), "macos");
+    assert_eq!(program, "w3m");
+    assert_eq!(args, vec![url]);
+
+    let (program, _) = browser_command(url, Some("  "), "linux");
+    assert_eq!(program, "xdg-open");
+}
+
+#[test]
+fn browser_env_uses_first_entry_that_is_installed() {
+    let url = "https://example.com";
+    let installed = |program: &str| program == "chromium";
+
+    let browser = first_available_browser("firefox:chromium --new-window %s", installed);
+    assert_eq!(browser.as_deref(), Some("chromium --new-window %s"));
+    let (program, args) = browser_command(url, browser.as_deref(), "linux");
+    assert_eq!(program, "chromium");
+    assert_eq!(args, vec!["--new-window", url]);
+
+    assert_eq!(first_available_browser("firefox:w3m", installed), None);
+    assert_eq!(first_available_browser("", installed), None);
+}
+
+#[test]
+fn programs_are_looked_up_on_the_search_path() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("lynx"), "").unwrap();
+    let search_path = std::env::join_paths([dir.path()]).unwrap();
+
+    assert!(program_on_path("lynx", &search_path));
+    assert!(!program_on_path("links", &search_path));
+    let full = dir.path().join("lynx");
+    assert!(program_on_path(full.to_str().unwrap(), &search_path));
+}
+
+#[test]
+fn browser_defaults_follow_target_platform() {
+    let url = "https://example.com";
+    assert_eq!(
+        browser_command(url, None, "macos"),
+        ("open".to_string(), vec![url.to_string()])
+    );
+    assert_eq!(
+        browser_command(url, None, "linux"),
+        ("xdg-open".to_string(), vec![url.to_string()])
+    );
+    assert_eq!(
+        browser_command(url, None, "windows"),
+        (
+            "cmd".to_string(),
+            vec!["/C".to_string(), "start".to_string(), url.to_string()]
+        )
+    );
+}
+
+#[test]
+fn custom_link_schemes_bypass_the_browser() {
+    let mut schemes = LinkSchemes::default();
+    schemes
+        .insert("obsidian", "obsidian-open --vault %s")
+        .unwrap();
+
+    let url = "obsidian://open?file=note";
+    assert_eq!(
+        link_command(url, Some("firefox"), &schemes, "linux"),
+        (
+            "obsidian-open".to_string(),
+            vec!["--vault".to_string(), url.to_string()]
+        )
+    );
+
+    let url = "https://example.com";
+    assert_eq!(
+        link_command(url, Some("firefox"), &schemes, "linux"),
+        ("firefox".to_string(), vec![url.to_string()])
+    );
+    assert_eq!(
+        link_command(url, None, &schemes, "macos"),
+        ("open".to_string(), vec![url.to_string()])
+    );
+}
+
+#[test]
+fn open_error_message_names_path_and_reason() {
+    let err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "permission denied");
+    let message = open_error_message(std::path::Path::new("docs/secret.md"), &err);
+    assert_eq!(message, "Could not open docs/secret.md: permission denied");
+}
+
+#[test]
+fn document_picker_dir_uses_parent_of_current_document() {
+    let root = std::path::Path::new("/repo");
+    assert_eq!(
+        document_picker_dir(std::path::Path::new("/repo/docs/guide/intro.md"), root),
+        std::path::PathBuf::from("/repo/docs/guide")
+    );
+    assert_eq!(
+        document_picker_dir(std::path::Path::new("README.md"), root),
+        std::path::PathBuf::from("/repo")
+    );
+}
+
+#[test]
+fn go_dialog_shift_arrows_move_selected_document() {
+    let mut state = AppState::new(true);
+    state.open_go_dialog(&[false; 3], 1);
+
+    let result = state.handle_go_dialog_key_input(KeyCode::Down, KeyModifiers::SHIFT);
+    assert!(matches!(
+        result,
+        EventResult::MoveDocument { from: 1, to: 2 }
+    ));
+    assert_eq!(state.go_dialog_selected, 2);
+    assert!(state.go_dialog_open);
+
+    let result = state.handle_go_dialog_key_input(KeyCode::Down, KeyModifiers::SHIFT);
+    assert!(matches!(result, EventResult::Continue));
+    assert_eq!(state.go_dialog_selected, 2);
+}
+
+#[test]
+fn go_dialog_lists_pinned_documents_first() {
+    assert_eq!(
+        go_dialog_order(&[false, true, false, true]),
+        vec![1, 3, 0, 2]
+    );
+    assert_eq!(go_dialog_order(&[false, false]), vec![0, 1]);
+    assert!(go_dialog_order(&[]).is_empty());
+}
+
+#[test]
+fn go_dialog_rows_map_to_queue_indices_with_pins() {
+    let mut state = AppState::new(true);
+    state.open_go_dialog(&[false, false, true], 0);
+    assert_eq!(state.go_dialog_order, vec![2, 0, 1]);
+    // The current document (queue index 0) sits on row 1.
+    assert_eq!(state.go_dialog_selected, 1);
+
+    // Unpinned documents cannot move above the pinned group.
+    let result = state.handle_go_dialog_key_input(KeyCode::Up, KeyModifiers::SHIFT);
+    assert!(matches!(result, EventResult::Continue));
+    assert_eq!(state.go_dialog_selected, 1);
+
+    let result = state.handle_go_dialog_key_input(KeyCode::Down, KeyModifiers::SHIFT);
+    assert!(matches!(
+        result,
+        EventResult::MoveDocument { from: 0, to: 1 }
+    ));
+
+    state.handle_go_dialog_key_input(KeyCode::Home, KeyModifiers::NONE);
+    let result = state.handle_go_dialog_key_input(KeyCode::Enter, KeyModifiers::NONE);
+    assert!(matches!(result, EventResult::GoToIndex(2)));
+}
+
+#[test]
+fn p_requests_a_pin_toggle() {
+    let mut state = AppState::new(true);
+    assert!(matches!(
+        state.handle_key_input(KeyCode::Char('p'), 10, 5),
+        KeyAction::TogglePin
+    ));
+}
+
+#[test]
+fn go_dialog_arrows_wrap_around_the_ends() {
+    let mut state = AppState::new(true);
+    state.open_go_dialog(&[false; 3], 2);
+
+    state.handle_go_dialog_key_input(KeyCode::Down, KeyModifiers::NONE);
+    assert_eq!(state.go_dialog_selected, 0);
+    state.handle_go_dialog_key_input(KeyCode::Up, KeyModifiers::NONE);
+    assert_eq!(state.go_dialog_selected, 2);
+    state.handle_go_dialog_key_input(KeyCode::Up, KeyModifiers::NONE);
+    assert_eq!(state.go_dialog_selected, 1);
+}
+
+#[test]
+fn picker_arrows_wrap_around_the_ends() {
+    let mut state = AppState::new(true);
+    let root = tempfile::tempdir().expect("tempdir");
+    std::fs::write(root.path().join("a.md"), "# a").expect("write md");
+    std::fs::write(root.path().join("b.md"), "# b").expect("write md");
+    state.open_picker(root.path().to_path_buf());
+    let last = state.picker_entries.len() - 1;
+    assert!(last > 0);
+
+    state.handle_picker_key_input(KeyCode::Up);
+    assert_eq!(state.picker_selected, last);
+    state.handle_picker_key_input(KeyCode::Down);
+    assert_eq!(state.picker_selected, 0);
+}
+
+#[test]
+fn wrapping_selection_handles_empty_lists() {
+    assert_eq!(wrapping_next(0, 0), 0);
+    assert_eq!(wrapping_prev(0, 0), 0);
+}
+
+#[test]
+fn restore_terminal_is_idempotent() {
+    assert!(restore_terminal().is_ok());
+    assert!(restore_terminal().is_ok());
+}
+
+fn pos(line_idx: usize, char_idx: usize) -> TextPosition {
+    TextPosition { line_idx, char_idx }
+}
+
+#[test]
+fn selection_ranges_span_lines_in_either_direction() {
+    let lines = vec![
+        "first line".to_string(),
+        "second".to_string(),
+        "third line".to_string(),
+    ];
+
+    let forward = selection_line_ranges(pos(0, 6), pos(2, 4), &lines);
+    assert_eq!(forward, vec![(0, 6, 10), (1, 0, 6), (2, 0, 5)]);
+    assert_eq!(selected_text(&lines, &forward), "line\nsecond\nthird");
+
+    let backward = selection_line_ranges(pos(2, 4), pos(0, 6), &lines);
+    assert_eq!(backward, forward);
+}
+
+#[test]
+fn selection_within_one_line_includes_both_endpoints() {
+    let lines = vec!["hello world".to_string()];
+    let ranges = selection_line_ranges(pos(0, 6), pos(0, 10), &lines);
+    assert_eq!(ranges, vec![(0, 6, 11)]);
+    assert_eq!(selected_text(&lines, &ranges), "world");
+}
+
+#[test]
+fn osc52_sequence_base64_encodes_text() {
+    assert_eq!(osc52_copy_sequence("hi!"), "\x1b]52;c;aGkh\x07");
+    assert_eq!(base64_encode(b"ab"), "YWI=");
+    assert_eq!(base64_encode(b"a"), "YQ==");
+}
+
+#[test]
+fn source_lines_map_to_scroll_offsets() {
+    let markdown = "# Title\n\nFirst paragraph\nstill first.\n\n## Section\n\nSecond paragraph.\n";
+    let rendered = render_markdown_with_links(
+        markdown,
+        80,
+        &Theme::pastel(),
+        &crate::markdown::RenderOptions::default(),
+    );
+    let texts: Vec<String> = rendered
+        .lines
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect()
+        })
+        .collect();
+    let (_, offsets) = build_wraps(&texts, 80);
+    let row_of = |needle: &str| {
+        let idx = texts.iter().position(|text| text.contains(needle)).unwrap();
+        offsets[idx]
+    };
+
+    let source = &rendered.source_lines;
+    assert_eq!(scroll_for_source_line(source, &offsets, 1), row_of("Title"));
+    assert_eq!(
+        scroll_for_source_line(source, &offsets, 3),
+        row_of("First paragraph")
+    );
+    assert_eq!(
+        scroll_for_source_line(source, &offsets, 4),
+        row_of("First paragraph")
+    );
+    assert_eq!(
+        scroll_for_source_line(source, &offsets, 6),
+        row_of("Section")
+    );
+    assert_eq!(
+        scroll_for_source_line(source, &offsets, 8),
+        row_of("Second paragraph")
+    );
+    assert_eq!(
+        scroll_for_source_line(source, &offsets, 99),
+        row_of("Second paragraph")
+    );
+}
+
+#[test]
+fn breadcrumb_tracks_enclosing_headings() {
+    let heading = |level, text: &str, scroll_pos| HeadingAnchor {
+        level,
+        text: text.to_string(),
+        scroll_pos,
+    };
+    let headings = vec![
+        heading(1, "Guide", 0),
+        heading(2, "Installation", 10),
+        heading(3, "Linux", 20),
+        heading(3, "macOS", 30),
+        heading(2, "Usage", 40),
+    ];
+
+    assert_eq!(breadcrumb_for_scroll(&headings, 5), ["Guide"]);
+    assert_eq!(
+        breadcrumb_for_scroll(&headings, 20),
+        ["Guide", "Installation", "Linux"]
+    );
+    assert_eq!(
+        breadcrumb_for_scroll(&headings, 35),
+        ["Guide", "Installation", "macOS"]
+    );
+    assert_eq!(breadcrumb_for_scroll(&headings, 45), ["Guide", "Usage"]);
+    assert!(breadcrumb_for_scroll(&headings[1..], 5).is_empty());
+}
+
+#[test]
+fn slow_renders_report_their_time() {
+    assert_eq!(render_time_message(Duration::from_millis(50)), None);
+    assert_eq!(render_time_message(Duration::from_millis(199)), None);
+    assert_eq!(
+        render_time_message(SLOW_RENDER_THRESHOLD).as_deref(),
+        Some("rendered in 200ms")
+    );
+    assert_eq!(
+        render_time_message(Duration::from_millis(320)).as_deref(),
+        Some("rendered in 320ms")
+    );
+    assert_eq!(
+        render_time_message(Duration::from_millis(1540)).as_deref(),
+        Some("rendered in 1.5s")
+    );
+}
+
+#[test]
+fn heading_jumps_pick_the_nearest_heading_in_each_direction() {
+    let positions = [0, 10, 25];
+    assert_eq!(next_heading_pos(&positions, 0), Some(10));
+    assert_eq!(next_heading_pos(&positions, 12), Some(25));
+    assert_eq!(next_heading_pos(&positions, 25), None);
+    assert_eq!(previous_heading_pos(&positions, 25), Some(10));
+    assert_eq!(previous_heading_pos(&positions, 12), Some(10));
+    assert_eq!(previous_heading_pos(&positions, 0), None);
+    assert_eq!(next_heading_pos(&[], 5), None);
+}
+
+#[test]
+fn brace_keys_scroll_between_headings() {
+    let mut state = AppState::new(false);
+    state.current_headings = [3u16, 40, 90]
+        .into_iter()
+        .map(|scroll_pos| HeadingAnchor {
+            level: 2,
+            text: format!("at {}", scroll_pos),
+            scroll_pos,
+        })
+        .collect();
+
+    state.handle_key_input(KeyCode::Char('}'), 60, 10);
+    assert_eq!(state.scroll, 3);
+    state.handle_key_input(KeyCode::Char('}'), 60, 10);
+    assert_eq!(state.scroll, 40);
+    // The last heading sits past the end; stop at the bottom instead.
+    state.handle_key_input(KeyCode::Char('}'), 60, 10);
+    assert_eq!(state.scroll, 60);
+    state.handle_key_input(KeyCode::Char('{'), 60, 10);
+    assert_eq!(state.scroll, 40);
+    state.handle_key_input(KeyCode::Char('{'), 60, 10);
+    state.handle_key_input(KeyCode::Char('{'), 60, 10);
+    assert_eq!(state.scroll, 3);
+}
+
+#[test]
+fn diagnostics_lines_describe_the_session() {
+    let info = Diagnostics {
+        terminal: (120, 40),
+        color_depth: ColorDepth::Ansi256,
+        theme_name: "light",
+        code_theme: "InspiredGitHub",
+        path: "docs/guide.md",
+        size_bytes: 2048,
+        queue_len: 3,
+    };
+    assert_eq!(
+        diagnostics_lines(&info),
+        vec![
+            format!("mdr {}", env!("CARGO_PKG_VERSION")),
+            "Terminal:  120x40".to_string(),
+            "Colors:    256 colors".to_string(),
+            "Theme:     light (code: InspiredGitHub)".to_string(),
+            "Document:  docs/guide.md".to_string(),
+            "Size:      2048 bytes".to_string(),
+            "Queue:     3 documents".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn resize_anchor_keeps_the_same_text_at_the_top() {
+    let lines = vec![
+        "intro".to_string(),
+        "alpha beta gamma delta epsilon zeta eta theta".to_string(),
+        "outro".to_string(),
+    ];
+    let source_lines = vec![0, 2, 4];
+    let (wide_wraps, wide_offsets) = build_wraps(&lines, 20);
+    let (narrow_wraps, narrow_offsets) = build_wraps(&lines, 10);
+
+    // Wide: the long line starts at row 1; its second row begins at "delta".
+    let scroll = wide_offsets[1] + 1;
+    let anchor = anchor_at_scroll(&source_lines, &wide_wraps, &wide_offsets, scroll).unwrap();
+    let top = wide_wraps[1].rows[1].start;
+    assert_eq!(&lines[1][top..top + 5], "delta");
+
+    let new_scroll = scroll_for_anchor(&anchor, &source_lines, &narrow_wraps, &narrow_offsets);
+    let (line_idx, row) = line_from_rendered(&narrow_offsets, new_scroll).unwrap();
+    assert_eq!(line_idx, 1);
+    let start = narrow_wraps[1].rows[usize::from(row)].start;
+    assert!(start <= top && top < narrow_wraps[1].rows[usize::from(row)].end);
+
+    // Lines after the anchor block keep their place too.
+    let anchor =
+        anchor_at_scroll(&source_lines, &wide_wraps, &wide_offsets, wide_offsets[2]).unwrap();
+    assert_eq!(
+        scroll_for_anchor(&anchor, &source_lines, &narrow_wraps, &narrow_offsets),
+        narrow_offsets[2]
+    );
+}
+
+#[test]
+fn follow_only_moves_a_view_that_was_at_the_bottom() {
+    assert!(is_at_bottom(30, 40, 10));
+    assert!(!is_at_bottom(29, 40, 10));
+    assert!(is_at_bottom(0, 5, 10));
+
+    assert_eq!(follow_scroll(true, true, 30), u16::MAX);
+    assert_eq!(follow_scroll(true, false, 12), 12);
+    assert_eq!(follow_scroll(false, true, 30), 30);
+}
+
+#[test]
+fn follow_keeps_the_bottom_in_view_as_the_document_grows() {
+    let theme = Theme::pastel();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(60, 12)).expect("test terminal");
+    let log = |entries: usize| {
+        (1..=entries)
+            .map(|n| format!("entry {}", n))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+    let draw = |terminal: &mut Terminal<ratatui::backend::TestBackend>,
+                state: &mut AppState,
+                markdown: &str| {
+        let context = RenderContext {
+            path: "log.md",
+            markdown,
+            queue_index: 0,
+            queue_len: 1,
+            queue_paths: &[],
+            plain_text: false,
+        };
+        terminal
+            .draw(|frame| state.render(frame, &context, &theme))
+            .expect("draw");
+    };
+    let bottom = |state: &AppState| state.rendered_lines - state.viewport_height;
+
+    let mut state = AppState::new(false);
+    state.follow = true;
+    state.scroll = u16::MAX;
+    draw(&mut terminal, &mut state, &log(20));
+    let old_bottom = bottom(&state);
+    state.on_document_reloaded(Instant::now());
+    draw(&mut terminal, &mut state, &log(25));
+    assert!(bottom(&state) > old_bottom);
+    assert_eq!(state.scroll, bottom(&state));
+
+    // Scrolled up: the view stays where the reader left it.
+    state.scroll = 3;
+    state.on_document_reloaded(Instant::now());
+    draw(&mut terminal, &mut state, &log(30));
+    assert_eq!(state.scroll, 3);
+}
+
+#[test]
+fn reload_flash_clears_after_timeout() {
+    assert!(!flash_expired(Duration::from_millis(1999)));
+    assert!(flash_expired(FLASH_DURATION));
+
+    let mut state = AppState::new(true);
+    let start = Instant::now();
+    state.on_document_reloaded(start);
+    state.on_document_reloaded(start);
+    assert_eq!(state.reload_count, 2);
+
+    state.expire_flash(start + Duration::from_secs(1));
+    assert_eq!(
+        state.flash.as_ref().map(|(text, _)| text.as_str()),
+        Some("⟳ reloaded (2)")
+    );
+    state.expire_flash(start + Duration::from_secs(3));
+    assert!(state.flash.is_none());
+}
+
+#[test]
+fn flash_time_left_counts_down_to_zero() {
+    let mut state = AppState::new(true);
+    let start = Instant::now();
+    assert_eq!(state.flash_time_left(start), None);
+
+    state.flash = Some(("rendered in 320ms".to_string(), start));
+    assert_eq!(
+        state.flash_time_left(start + Duration::from_millis(500)),
+        Some(FLASH_DURATION - Duration::from_millis(500))
+    );
+    assert_eq!(
+        state.flash_time_left(start + FLASH_DURATION * 2),
+        Some(Duration::ZERO)
+    );
+}
+
+#[test]
+fn smooth_scroll_steps_converge_monotonically() {
+    for (start, target) in [(0u16, 40u16), (40, 0), (7, 8), (5, 5)] {
+        let mut positions = vec![start];
+        let mut current = start;
+        while current != target {
+            current = smooth_scroll_step(current, target);
+            positions.push(current);
+            assert!(positions.len() < 64, "did not converge");
+        }
+        let ascending = start <= target;
+        assert!(positions.windows(2).all(|pair| if ascending {
+            pair[0] < pair[1]
+        } else {
+            pair[0] > pair[1]
+        }));
+        assert_eq!(*positions.last().unwrap(), target);
+    }
+}
+
+#[test]
+fn smooth_page_down_animates_toward_clamped_target() {
+    let mut state = AppState::new(true);
+    state.smooth_scroll = true;
+    state.handle_key_input(KeyCode::PageDown, 30, 20);
+    state.handle_key_input(KeyCode::PageDown, 30, 20);
+    assert_eq!(state.scroll, 0);
+    assert_eq!(state.scroll_target, Some(30));
+
+    while state.scroll_target.is_some() {
+        state.advance_smooth_scroll();
+    }
+    assert_eq!(state.scroll, 30);
+}
+
+#[test]
+fn cursor_line_rect_covers_every_wrapped_row_of_the_line() {
+    let texts = vec![
+        "short".to_string(),
+        "abcd efgh ijkl".to_string(),
+        "tail".to_string(),
+    ];
+    let (wraps, offsets) = build_wraps(&texts, 5);
+    let area = Rect::new(2, 1, 5, 10);
+
+    let rect = cursor_line_rect(area, &wraps, &offsets, 1, 0).unwrap();
+    assert_eq!((rect.x, rect.y, rect.width, rect.height), (2, 2, 5, 3));
+
+    // Scrolled past the first row of line 1, only its remaining rows show.
+    let rect = cursor_line_rect(area, &wraps, &offsets, 1, 2).unwrap();
+    assert_eq!((rect.y, rect.height), (1, 2));
+
+    assert!(cursor_line_rect(area, &wraps, &offsets, 0, 1).is_none());
+}
+
+#[test]
+fn cursor_line_moves_with_j_and_k_and_keeps_line_visible() {
+    let mut state = AppState::new(true);
+    state.current_lines_text = (0..10).map(|i| format!("line {i}")).collect();
+    let (wraps, offsets) = build_wraps(&state.current_lines_text, 80);
+    state.current_wraps = wraps;
+    state.current_line_offsets = offsets;
+    state.viewport_height = 3;
+
+    state.handle_key_input(KeyCode::Char('c'), 7, 3);
+    assert!(state.cursor_line_enabled);
+    for _ in 0..4 {
+        state.handle_key_input(KeyCode::Char('j'), 7, 3);
+    }
+    assert_eq!(state.cursor_line, 4);
+    assert_eq!(state.scroll, 2);
+
+    state.handle_key_input(KeyCode::Char('k'), 7, 3);
+    state.handle_key_input(KeyCode::Char('k'), 7, 3);
+    state.handle_key_input(KeyCode::Char('k'), 7, 3);
+    assert_eq!(state.cursor_line, 1);
+    assert_eq!(state.scroll, 1);
+}
+
+#[test]
+fn search_terms_must_all_appear_on_a_line_in_any_order() {
+    let lines = vec![
+        "bar then foo".to_string(),
+        "only foo here".to_string(),
+        "foo and bar, foo again".to_string(),
+    ];
+    let matches = find_matches(&lines, "foo bar");
+    let found: Vec<(usize, usize)> = matches.iter().map(|m| (m.line_idx, m.start)).collect();
+    assert_eq!(found, vec![(0, 0), (0, 9), (2, 0), (2, 8), (2, 13)]);
+}
+
+#[test]
+fn quoted_search_query_matches_literally() {
+    let lines = vec!["bar foo".to_string(), "a foo bar b".to_string()];
+    let matches = find_matches(&lines, "\"foo bar\"");
+    assert_eq!(matches.len(), 1);
+    assert_eq!(
+        (matches[0].line_idx, matches[0].start, matches[0].end),
+        (1, 2, 9)
+    );
+
+    assert_eq!(
+        search_terms("\"foo bar\" baz \"qu"),
+        vec!["foo bar", "baz", "qu"]
+    );
+    assert!(search_terms("  \"\" ").is_empty());
+}
+
+#[test]
+fn picker_preview_shows_first_rendered_lines_or_entry_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("notes.md");
+    let body: String = (1..=30).map(|i| format!("para {i}\n\n")).collect();
+    fs::write(&path, format!("# Notes\n\n{body}")).unwrap();
+    let theme = Theme::pastel();
+    let options = RenderOptions::default();
+    let text = |lines: Vec<Line<'static>>| -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect()
+    };
+
+    let file = PickerEntry {
+        path: path.clone(),
+        kind: PickerEntryKind::MarkdownFile,
+        label: "notes.md".to_string(),
+    };
+    let preview = text(picker_preview_lines(&file, &theme, &options));
+    assert_eq!(preview.len(), PICKER_PREVIEW_LINES);
+    assert!(preview[0].contains("Notes"));
+    assert!(preview.iter().any(|line| line == "para 1"));
+    assert!(!preview.iter().any(|line| line == "para 30"));
+
+    let folder = PickerEntry {
+        path: dir.path().to_path_buf(),
+        kind: PickerEntryKind::Directory,
+        label: "dir/".to_string(),
+    };
+    assert_eq!(
+        text(picker_preview_lines(&folder, &theme, &options)),
+        ["1 entry"]
+    );
+}
+
+#[test]
+fn document_path_copy_uses_the_given_path_verbatim() {
+    let (sequence, message) = document_path_copy(Path::new("/docs/a.md"));
+    assert_eq!(sequence, osc52_copy_sequence("/docs/a.md"));
+    assert_eq!(message, "Copied path: /docs/a.md");
+}
+
+#[test]
+fn copy_path_key_requests_a_copy() {
+    let mut state = AppState::new(true);
+    assert!(matches!(
+        state.handle_key_input(KeyCode::Char('Y'), 0, 1),
+        KeyAction::CopyDocumentPath
+    ));
+}
+
+#[test]
+fn minimap_ticks_scale_positions_to_gutter_height() {
+    assert_eq!(minimap_ticks(&[0, 50, 99], 100, 10), vec![0, 5, 9]);
+    // Positions landing on the same row collapse to one tick.
+    assert_eq!(minimap_ticks(&[40, 10, 12], 100, 10), vec![1, 4]);
+    // Short documents spread out instead of bunching at the top.
+    assert_eq!(minimap_ticks(&[1, 3], 4, 8), vec![2, 6]);
+    // Out-of-range positions clamp to the last row.
+    assert_eq!(minimap_ticks(&[500], 100, 10), vec![9]);
+    assert!(minimap_ticks(&[1], 0, 10).is_empty());
+    assert!(minimap_ticks(&[1], 10, 0).is_empty());
+}
+
+#[test]
+fn empty_documents_render_a_placeholder_and_never_scroll() {
+    let theme = Theme::pastel();
+    for markdown in ["", "  \n\n\t\n"] {
+        let mut state = AppState::new(false);
+        let context = RenderContext {
+            path: "empty.md",
+            markdown,
+            queue_index: 0,
+            queue_len: 1,
+            queue_paths: &[],
+            plain_text: false,
+        };
+        let lines = state.render_document(&context, 40, false, &theme);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans[0].content, "(empty document)");
+        assert_eq!(lines[0].alignment, Some(ratatui::layout::Alignment::Center));
+        assert_eq!(state.current_stats, DocStats::default());
+
+        let mut terminal =
+            Terminal::new(ratatui::backend::TestBackend::new(40, 12)).expect("test terminal");
+        for key in [KeyCode::PageDown, KeyCode::Down, KeyCode::End] {
+            terminal
+                .draw(|frame| state.render(frame, &context, &theme))
+                .unwrap();
+            let max_scroll = state.rendered_lines.saturating_sub(state.viewport_height);
+            state.handle_key_input(key, max_scroll, state.viewport_height.max(1));
+        }
+        terminal
+            .draw(|frame| state.render(frame, &context, &theme))
+            .unwrap();
+        assert_eq!(state.scroll, 0);
+        assert_eq!(state.rendered_lines, 1);
+    }
+}
+
+#[test]
+fn scroll_for_percent_scales_and_clamps() {
+    assert_eq!(scroll_for_percent(0, 200), 0);
+    assert_eq!(scroll_for_percent(50, 200), 100);
+    assert_eq!(scroll_for_percent(100, 200), 200);
+    assert_eq!(scroll_for_percent(250, 200), 200);
+    assert_eq!(scroll_for_percent(u16::MAX, u16::MAX), u16::MAX);
+    assert_eq!(scroll_for_percent(50, 0), 0);
+}
+
+#[test]
+fn digits_then_percent_jump_through_the_document() {
+    let mut state = AppState::new(true);
+    for key in ['7', '5', '%'] {
+        state.handle_key_input(KeyCode::Char(key), 400, 10);
+    }
+    assert_eq!(state.scroll, 300);
+
+    // Another key in between drops the pending number.
+    state.handle_key_input(KeyCode::Char('2'), 400, 10);
+    state.handle_key_input(KeyCode::Down, 400, 10);
+    state.handle_key_input(KeyCode::Char('%'), 400, 10);
+    assert_eq!(state.scroll, 301);
+}
+
+#[test]
+fn sticky_search_recomputes_matches_in_the_next_document() {
+    let theme = Theme::pastel();
+    let first = "alpha needle\n";
+    let second: String = (0..40)
+        .map(|i| {
+            if i == 30 {
+                "the needle\n\n".to_string()
+            } else {
+                format!("filler {i}\n\n")
+            }
+        })
+        .collect();
+    let mut queue = DocumentQueue::new(vec![
+        QueuedDocument::new("a.md".into(), first.to_string()),
+        QueuedDocument::new("b.md".into(), second),
+    ])
+    .unwrap();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(40, 12)).expect("test terminal");
+    let draw = |terminal: &mut Terminal<ratatui::backend::TestBackend>,
+                state: &mut AppState,
+                queue: &DocumentQueue| {
+        let doc = queue.current();
+        let context = RenderContext {
+            path: "doc.md",
+            markdown: &doc.content,
+            queue_index: queue.current_index(),
+            queue_len: queue.len(),
+            queue_paths: &[],
+            plain_text: false,
+        };
+        terminal
+            .draw(|frame| state.render(frame, &context, &theme))
+            .unwrap();
+    };
+
+    for sticky in [false, true] {
+        let mut state = AppState::new(false);
+        state.sticky_search = sticky;
+        queue.focus_index(0);
+        state.search_query = "needle".to_string();
+        draw(&mut terminal, &mut state, &queue);
+        assert_eq!(state.search_matches.len(), 1);
+
+        apply_event_result(
+            EventResult::NextDocument,
+            &mut queue,
+            &mut state,
+            Path::new("."),
+        );
+        draw(&mut terminal, &mut state, &queue);
+        if sticky {
+            assert_eq!(state.search_query, "needle");
+            assert_eq!(state.search_matches.len(), 1);
+            assert_eq!(state.search_matches[0].line_idx, 60);
+            assert!(state.scroll > 0);
+        } else {
+            assert!(state.search_query.is_empty());
+            assert!(state.search_matches.is_empty());
+            assert_eq!(state.scroll, 0);
+        }
+    }
+}
+
+fn paragraph_rows(lines: &[Line<'static>], width: u16) -> u16 {
+    // A sentinel line after the content shows where the paragraph put it.
+    let mut content = lines.to_vec();
+    content.push(Line::raw("END"));
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(width, 200)).expect("test terminal");
+    terminal
+        .draw(|frame| {
+            frame.render_widget(
+                Paragraph::new(content).wrap(Wrap { trim: false }),
+                frame.size(),
+            )
+        })
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..200)
+        .find(|&y| buffer.get(0, y).symbol() == "E" && buffer.get(2, y).symbol() == "D")
+        .expect("sentinel row")
+}
+
+#[test]
+fn over_long_words_wrap_to_the_same_rows_as_the_paragraph() {
+    for text in [
+        "x".repeat(500),
+        format!("see {}", "x".repeat(500)),
+        format!("https://example.com/{}", "p".repeat(480)),
+        "e\u{301}".repeat(60),
+        "日".repeat(50),
+    ] {
+        let lines = vec![Line::raw(text.clone())];
+        for width in [20u16, 21] {
+            assert_eq!(
+                estimate_rendered_lines(&lines, width),
+                paragraph_rows(&lines, width),
+                "{text:?} at width {width}"
+            );
+        }
+    }
+    assert_eq!(
+        estimate_rendered_lines(&[Line::raw("x".repeat(500))], 20),
+        25
+    );
+}
+
+#[test]
+fn source_pane_follows_the_rendered_scroll_by_source_line() {
+    let theme = Theme::pastel();
+    let markdown = "# Title\n\nFirst paragraph\nspans two lines.\n\n- one\n- two\n\nLast.\n";
+    let rendered = render_markdown_with_links(markdown, 80, &theme, &RenderOptions::default());
+    let texts: Vec<String> = rendered.lines.iter().map(|line| line.to_string()).collect();
+    let (_, offsets) = build_wraps(&texts, 80);
+    let source_lines = rendered.source_lines;
+
+    // Jumping to a source line and mapping the resulting scroll back lands
+    // on the start of the block containing it.
+    for (line_number, block_start) in [(1, 0), (3, 2), (4, 2), (6, 5), (7, 6), (9, 8)] {
+        let scroll = scroll_for_source_line(&source_lines, &offsets, line_number);
+        assert_eq!(
+            source_line_at_scroll(&source_lines, &offsets, scroll),
+            block_start,
+            "source line {line_number}"
+        );
+    }
+    assert_eq!(source_line_at_scroll(&[], &[], 3), 0);
+}
+
+#[test]
+fn command_prompt_parses_typed_commands() {
+    let mut state = AppState::new(false);
+    state.line_prompt = Some(String::new());
+    for ch in "theme light".chars() {
+        state.handle_line_prompt_key_input(KeyCode::Char(ch), 100);
+    }
+    let result = state.handle_line_prompt_key_input(KeyCode::Enter, 100);
+    assert!(matches!(
+        result,
+        EventResult::RunCommand(command::Command::Theme(Background::Light))
+    ));
+    assert_eq!(state.line_prompt, None);
+
+    state.line_prompt = Some("nope".to_string());
+    let result = state.handle_line_prompt_key_input(KeyCode::Enter, 100);
+    assert!(matches!(result, EventResult::Continue));
+    assert_eq!(
+        state.status_message.as_deref(),
+        Some("Unknown command: nope")
+    );
+}
+
+#[test]
+fn toc_selects_current_section_and_jumps_to_heading() {
+    let mut state = AppState::new(false);
+    state.current_headings = vec![
+        HeadingAnchor {
+            level: 1,
+            text: "Intro".to_string(),
+            scroll_pos: 0,
+        },
+        HeadingAnchor {
+            level: 2,
+            text: "Usage".to_string(),
+            scroll_pos: 20,
+        },
+        HeadingAnchor {
+            level: 2,
+            text: "Notes".to_string(),
+            scroll_pos: 40,
+        },
+    ];
+    state.scroll = 25;
+    state.open_toc();
+    assert!(state.toc_open);
+    assert_eq!(state.toc_selected, 1);
+
+    state.handle_toc_key_input(KeyCode::Down, 30);
+    state.handle_toc_key_input(KeyCode::Enter, 30);
+    assert!(!state.toc_open);
+    assert_eq!(state.scroll, 30);
+
+    state.current_headings.clear();
+    state.open_toc();
+    assert!(!state.toc_open);
+    assert!(state.status_message.is_some());
+}
+
+#[test]
+fn toc_and_heading_jumps_center_the_heading_when_enabled() {
+    let mut state = AppState::new(false);
+    state.viewport_height = 10;
+    state.current_headings = [3u16, 40, 90]
+        .into_iter()
+        .map(|scroll_pos| HeadingAnchor {
+            level: 2,
+            text: format!("at {}", scroll_pos),
+            scroll_pos,
+        })
+        .collect();
+
+    state.open_toc();
+    state.handle_toc_key_input(KeyCode::Down, 100);
+    state.handle_toc_key_input(KeyCode::Enter, 100);
+    assert_eq!(state.scroll, 35);
+
+    state.handle_key_input(KeyCode::Char('}'), 100, 10);
+    assert_eq!(state.scroll, 85);
+    state.handle_key_input(KeyCode::Char('{'), 100, 10);
+    assert_eq!(state.scroll, 35);
+
+    state.center_search_matches = false;
+    state.handle_key_input(KeyCode::Char('}'), 100, 10);
+    assert_eq!(state.scroll, 40);
+}
+
+#[test]
+fn switching_palette_keeps_a_user_chosen_code_theme() {
+    let dark = Theme::dark();
+    assert_eq!(
+        switch_palette(&dark, Background::Light).code_theme,
+        Theme::light().code_theme
+    );
+    let mut custom = Theme::dark();
+    custom.code_theme = "custom-theme";
+    assert_eq!(
+        switch_palette(&custom, Background::Light).code_theme,
+        "custom-theme"
+    );
+}
+
+#[test]
+fn ctrl_c_quits_outside_search_input() {
+    let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    let mut state = AppState::new(false);
+    assert!(matches!(
+        state.handle_key_event(ctrl_c, 100, 10),
+        KeyAction::Quit
+    ));
+    assert!(!state.cursor_line_enabled);
+
+    state.search_mode = true;
+    assert!(matches!(
+        state.handle_key_event(ctrl_c, 100, 10),
+        KeyAction::None
+    ));
+    assert_eq!(state.search_query, "");
+}
+
+#[test]
+fn snippet_returns_short_lines_whole_and_trimmed() {
+    let line = "   find the needle here  ";
+    let start = line.find("needle").unwrap();
+    assert_eq!(
+        snippet_around(line, start..start + 6, 40),
+        "find the needle here"
+    );
+}
+
+#[test]
+fn snippet_centers_the_match_with_ellipses_on_cut_ends() {
+    let line = "aaaaaaaaaa bbbbbbbbbb needle cccccccccc dddddddddd";
+    let start = line.find("needle").unwrap();
+    let snippet = snippet_around(line, start..start + 6, 16);
+    assert_eq!(snippet, "…bbb needle ccc…");
+    assert_eq!(snippet.chars().count(), 16);
+
+    // Near an edge the window slides instead of padding past the text.
+    let snippet = snippet_around(line, 0..4, 16);
+    assert_eq!(snippet, "aaaaaaaaaa bbbb…");
+    let end = line.len();
+    let snippet = snippet_around(line, end - 4..end, 16);
+    assert_eq!(snippet, "…cccc dddddddddd");
+}
+
+#[test]
+fn tab_toggles_search_results_and_enter_jumps_to_selection() {
+    let mut state = AppState::new(false);
+    state.viewport_height = 10;
+    state.search_mode = true;
+    state.search_query = "x".to_string();
+    state.search_matches = [5u16, 30, 60]
+        .into_iter()
+        .enumerate()
+        .map(|(idx, scroll_pos)| SearchMatch {
+            line_idx: idx,
+            start: 0,
+            end: 1,
+            start_char: 0,
+            scroll_pos,
+        })
+        .collect();
+
+    state.handle_key_input(KeyCode::Tab, 100, 10);
+    assert!(state.search_results_open);
+    state.handle_key_input(KeyCode::Down, 100, 10);
+    state.handle_key_input(KeyCode::Down, 100, 10);
+    state.handle_key_input(KeyCode::Down, 100, 10);
+    assert_eq!(state.search_results_selected, 2);
+    state.handle_key_input(KeyCode::Enter, 100, 10);
+
+    assert!(!state.search_results_open);
+    assert!(!state.search_mode);
+    assert_eq!(state.search_index, 2);
+    assert_eq!(state.scroll, state.match_scroll_target(60, 100));
+}
+
+#[test]
+fn link_hover_follows_padded_content_area() {
+    let markdown = "[link](https://example.com)";
+    let context = RenderContext {
+        path: "doc.md",
+        markdown,
+        queue_index: 0,
+        queue_len: 1,
+        queue_paths: &[],
+        plain_text: false,
+    };
+    let theme = Theme::pastel();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(60, 10)).expect("test terminal");
+    let mut state = AppState::new(false);
+    state.padding = 5;
+    terminal
+        .draw(|frame| state.render(frame, &context, &theme))
+        .expect("draw");
+
+    // Outer margin 1 + border 1 + padding 5.
+    assert_eq!(state.content_area.x, 7);
+    assert_eq!(state.content_area.width, 60 - 2 - 2 - 1 - 10);
+    let row = state.content_area.y;
+    let buffer = terminal.backend().buffer();
+    assert_eq!(buffer.get(7, row).symbol(), "l");
+    assert_eq!(buffer.get(6, row).symbol(), " ");
+    assert_eq!(
+        update_hover(&state, 7, row).as_deref(),
+        Some("https://example.com")
+    );
+    assert_eq!(update_hover(&state, 6, row), None);
+}
+
+#[test]
+fn line_number_gutter_grows_with_the_line_count() {
+    assert_eq!(line_number_gutter_width(0), 2);
+    assert_eq!(line_number_gutter_width(9), 2);
+    assert_eq!(line_number_gutter_width(10), 3);
+    assert_eq!(line_number_gutter_width(1234), 5);
+
+    let lines = vec!["alpha".to_string(), "beta gamma delta".to_string()];
+    let (_, offsets) = build_wraps(&lines, 6);
+    let rows: Vec<String> = line_number_gutter(&offsets, 0, 4, 3)
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+    assert_eq!(rows, vec![" 1 ", " 2 ", "", ""]);
+}
+
+#[test]
+fn link_hover_skips_the_line_number_gutter() {
+    let markdown = format!("[link](https://example.com)\n\n{}", "para\n\n".repeat(60));
+    let context = RenderContext {
+        path: "doc.md",
+        markdown: &markdown,
+        queue_index: 0,
+        queue_len: 1,
+        queue_paths: &[],
+        plain_text: false,
+    };
+    let theme = Theme::pastel();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(60, 10)).expect("test terminal");
+    let mut state = AppState::new(false);
+    state.line_numbers = true;
+    terminal
+        .draw(|frame| state.render(frame, &context, &theme))
+        .expect("draw");
+
+    // Over 100 rendered lines: three digits and a space after margin + border.
+    assert!(state.current_lines_text.len() >= 100);
+    assert_eq!(state.content_area.x, 2 + 4);
+    assert_eq!(state.content_area.width, 60 - 2 - 2 - 1 - 4);
+    let row = state.content_area.y;
+    let buffer = terminal.backend().buffer();
+    assert_eq!(buffer.get(4, row).symbol(), "1");
+    assert_eq!(buffer.get(6, row).symbol(), "l");
+    assert_eq!(
+        update_hover(&state, 6, row).as_deref(),
+        Some("https://example.com")
+    );
+    assert_eq!(update_hover(&state, 4, row), None);
+}
+
+#[test]
+fn capped_area_centers_the_text_column() {
+    let area = Rect::new(2, 2, 115, 20);
+    assert_eq!(capped_area(area, Some(40)), Rect::new(39, 2, 40, 20));
+    assert_eq!(capped_area(area, Some(200)), area);
+    assert_eq!(capped_area(area, None), area);
+}
+
+#[test]
+fn max_text_width_wraps_and_hit_tests_the_centered_column() {
+    let markdown = format!("[link](https://example.com) {}", "word ".repeat(40));
+    let context = RenderContext {
+        path: "doc.md",
+        markdown: &markdown,
+        queue_index: 0,
+        queue_len: 1,
+        queue_paths: &[],
+        plain_text: false,
+    };
+    let theme = Theme::pastel();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(120, 20)).expect("test terminal");
+    let mut state = AppState::new(false);
+    state.max_text_width = Some(40);
+    terminal
+        .draw(|frame| state.render(frame, &context, &theme))
+        .expect("draw");
+
+    assert_eq!(state.content_area.x, 39);
+    assert_eq!(state.content_area.width, 40);
+    let widest = state.current_wraps[0]
+        .rows
+        .iter()
+        .map(|row| row.end - row.start)
+        .max();
+    assert!(widest.is_some_and(|width| width <= 40));
+    assert!(state.current_wraps[0].rows.len() > 1);
+
+    let row = state.content_area.y;
+    assert_eq!(terminal.backend().buffer().get(39, row).symbol(), "l");
+    assert_eq!(
+        update_hover(&state, 39, row).as_deref(),
+        Some("https://example.com")
+    );
+    assert_eq!(update_hover(&state, 38, row), None);
+}
+
+#[test]
+fn padding_never_hides_the_whole_content_area() {
+    let area = Rect::new(2, 2, 5, 3);
+    assert_eq!(padded_area(area, 1), Rect::new(3, 2, 3, 3));
+    assert_eq!(padded_area(area, 40), Rect::new(4, 2, 1, 3));
+}
+
+#[test]
+fn start_heading_scrolls_to_the_matching_slug_once() {
+    let markdown = format!(
+        "# Top\n\n{}## Getting Started\n\nbody\n\n{}",
+        "filler\n\n".repeat(20),
+        "more\n\n".repeat(20)
+    );
+    let context = RenderContext {
+        path: "doc.md",
+        markdown: &markdown,
+        queue_index: 0,
+        queue_len: 1,
+        queue_paths: &[],
+        plain_text: false,
+    };
+    let theme = Theme::pastel();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(60, 12)).expect("test terminal");
+
+    let mut state = AppState::new(false);
+    state.pending_heading = Some("getting-started".to_string());
+    terminal
+        .draw(|frame| state.render(frame, &context, &theme))
+        .expect("draw");
+    let target = state.current_headings[1].scroll_pos;
+    assert!(target > 0);
+    assert_eq!(state.scroll, target);
+    assert_eq!(state.pending_heading, None);
+
+    let mut state = AppState::new(false);
+    state.pending_heading = Some("no-such-heading".to_string());
+    terminal
+        .draw(|frame| state.render(frame, &context, &theme))
+        .expect("draw");
+    assert_eq!(state.scroll, 0);
+}
+
+#[test]
+fn start_line_scroll_maps_source_lines_and_clamps_past_the_end() {
+    // Three blocks starting at source lines 0, 2, and 5; the middle one
+    // renders as two lines.
+    let source_lines = [0, 2, 2, 5];
+    let offsets = [0, 1, 3, 4];
+    assert_eq!(start_line_scroll(&source_lines, &offsets, 6, 1), 0);
+    assert_eq!(start_line_scroll(&source_lines, &offsets, 6, 3), 1);
+    assert_eq!(start_line_scroll(&source_lines, &offsets, 6, 5), 1);
+    assert_eq!(start_line_scroll(&source_lines, &offsets, 6, 6), 4);
+    assert_eq!(start_line_scroll(&source_lines, &offsets, 6, 7), u16::MAX);
+}
+
+#[test]
+fn start_line_sets_the_initial_scroll_once() {
+    let markdown = (1..=40)
+        .map(|n| format!("para {}", n))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let context = RenderContext {
+        path: "doc.md",
+        markdown: &markdown,
+        queue_index: 0,
+        queue_len: 1,
+        queue_paths: &[],
+        plain_text: false,
+    };
+    let theme = Theme::pastel();
+    let mut terminal =
+        Terminal::new(ratatui::backend::TestBackend::new(60, 12)).expect("test terminal");
+
+    let mut state = AppState::new(false);
+    state.pending_start_line = Some(21);
+    terminal
+        .draw(|frame| state.render(frame, &context, &theme))
+        .expect("draw");
+    let row = state
+        .current_lines_text
+        .iter()
+        .position(|line| line == "para 11");
+    assert_eq!(
+        row.map(|idx| state.current_line_offsets[idx]),
+        Some(state.scroll)
+    );
+    assert_eq!(state.pending_start_line, None);
+
+    let mut state = AppState::new(false);
+    state.pending_start_line = Some(1000);
+    terminal
+        .draw(|frame| state.render(frame, &context, &theme))
+        .expect("draw");
+    assert_eq!(
+        state.scroll,
+        state.rendered_lines.saturating_sub(state.viewport_height)
+    );
+    assert!(state.scroll > 0);
+}
+
+#[test]
+fn quote_and_ctrl_caret_request_the_alternate_document() {
+    let mut state = AppState::new(false);
+    assert!(matches!(
+        state.handle_key_input(KeyCode::Char('\''), 100, 10),
+        KeyAction::AlternateDocument
+    ));
+    for code in [KeyCode::Char('^'), KeyCode::Char('6')] {
+        let key = KeyEvent::new(code, KeyModifiers::CONTROL);
+        assert!(matches!(
+            state.handle_key_event(key, 100, 10),
+            KeyAction::AlternateDocument
+        ));
+    }
+}
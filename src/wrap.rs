@@ -0,0 +1,109 @@
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Clone, Debug)]
+pub struct LineWrap {
+    pub rows: Vec<RowRange>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RowRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Display columns for `ch`, matching how the terminal paragraph measures
+/// it: combining marks and other zero-width characters take no space.
+pub fn char_width(ch: char) -> usize {
+    UnicodeWidthChar::width(ch).unwrap_or(0)
+}
+
+/// Splits `line` into rows of at most `width` columns the way the reader's
+/// paragraph wraps it: at word boundaries, hard-splitting words that are
+/// longer than a row.
+pub fn wrap_line_ranges(line: &str, width: usize) -> LineWrap {
+    if line.is_empty() {
+        return LineWrap {
+            rows: vec![RowRange { start: 0, end: 0 }],
+        };
+    }
+    let chars: Vec<char> = line.chars().collect();
+    let widths: Vec<usize> = chars.iter().map(|&ch| char_width(ch)).collect();
+    let mut tokens: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0usize;
+    let mut in_ws = chars[0].is_whitespace();
+    for (i, ch) in chars.iter().enumerate() {
+        let is_ws = ch.is_whitespace();
+        if is_ws != in_ws {
+            tokens.push((start, i));
+            start = i;
+            in_ws = is_ws;
+        }
+    }
+    tokens.push((start, chars.len()));
+
+    let mut rows: Vec<RowRange> = Vec::new();
+    let mut row_start = 0usize;
+    let mut row_width = 0usize;
+    let mut row_end = 0usize;
+
+    for (tok_start, tok_end) in tokens {
+        let mut token_start = tok_start;
+        let mut token_width: usize = widths[tok_start..tok_end].iter().sum();
+
+        loop {
+            let remaining = width.saturating_sub(row_width);
+            if token_width <= remaining {
+                if row_width == 0 {
+                    row_start = token_start;
+                }
+                row_width = row_width.saturating_add(token_width);
+                row_end = tok_end;
+                break;
+            }
+            if row_width > 0 {
+                rows.push(RowRange {
+                    start: row_start,
+                    end: row_end,
+                });
+                row_width = 0;
+                continue;
+            }
+            let mut consumed = 0usize;
+            let mut split_end = token_start;
+            while split_end < tok_end {
+                let w = widths[split_end];
+                if consumed + w > width && consumed > 0 {
+                    break;
+                }
+                consumed = consumed.saturating_add(w);
+                split_end += 1;
+                if consumed >= width {
+                    break;
+                }
+            }
+            // Combining marks stay on the row of the character they modify.
+            while split_end < tok_end && widths[split_end] == 0 {
+                split_end += 1;
+            }
+            let chunk_end = split_end.max(token_start + 1);
+            rows.push(RowRange {
+                start: token_start,
+                end: chunk_end,
+            });
+            token_start = chunk_end;
+            if token_start >= tok_end {
+                break;
+            }
+            token_width = widths[token_start..tok_end].iter().sum();
+        }
+    }
+
+    if row_width > 0 {
+        rows.push(RowRange {
+            start: row_start,
+            end: row_end,
+        });
+    }
+
+    LineWrap { rows }
+}
@@ -4,6 +4,8 @@
 mod document_queue;
 #[path = "../src/file_discovery.rs"]
 mod file_discovery;
+#[path = "../src/file_kind.rs"]
+mod file_kind;
 
 use std::fs;
 
@@ -24,7 +26,7 @@ fn startup_with_directory_input_loads_markdown_queue() {
     fs::write(&second, "# second").expect("write second");
     fs::write(&ignored, "ignore").expect("write ignored");
 
-    let discovered = discover_markdown_paths(&[docs]).expect("discover markdown files");
+    let discovered = discover_markdown_paths(&[docs], false).expect("discover markdown files");
     assert_eq!(discovered.len(), 2);
 
     let queued: Vec<QueuedDocument> = discovered
@@ -0,0 +1,29 @@
+use mdr::{LineKind, RenderOptions, Theme, apply_beeline, render_markdown_with_links};
+
+fn line_text(line: &ratatui::text::Line<'_>) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+#[test]
+fn library_renders_sample_document() {
+    let theme = Theme::pastel();
+    let markdown = "# Title\n\nSee [docs](https://example.com).\n\n```\nlet x = 1;\n```\n";
+
+    let rendered = render_markdown_with_links(markdown, 80, &theme, &RenderOptions::default());
+    let texts: Vec<String> = rendered.lines.iter().map(line_text).collect();
+
+    assert!(texts.iter().any(|text| text.contains("Title")));
+    assert!(texts.iter().any(|text| text.contains("See docs")));
+    assert!(texts.iter().any(|text| text.contains("let x = 1;")));
+    assert_eq!(rendered.kinds.len(), rendered.lines.len());
+    assert!(rendered.kinds.contains(&LineKind::Code));
+    assert_eq!(rendered.links.len(), 1);
+    assert_eq!(rendered.links[0].url, "https://example.com");
+
+    let tinted = apply_beeline(&rendered.lines, &rendered.kinds, &theme, 1.0);
+    let tinted_texts: Vec<String> = tinted.iter().map(line_text).collect();
+    assert_eq!(tinted_texts, texts);
+}